@@ -0,0 +1,238 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::building::{Building, BuildingConfig, BuildingKind};
+use super::placement::{Placement, Rotation, GRID_SIZE};
+use crate::iams::Inventory;
+
+#[derive(Debug)]
+pub enum BlueprintError {
+    Empty,
+    Io(std::io::Error),
+    Serialize(ron::Error),
+    Deserialize(ron::error::SpannedError),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlueprintEntry {
+    pub kind: BuildingKind,
+    /// Position of the building's centre from the corner of the blueprint.
+    pub offset: [f32; 3],
+    pub rotation: Rotation,
+    #[serde(default)]
+    pub config: BuildingConfig,
+}
+
+/// A copied group of buildings that can be pasted elsewhere.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Blueprint {
+    pub name: String,
+    pub entries: Vec<BlueprintEntry>,
+}
+
+/// Lowest corner, on the x and z axes, of a building centred on `translation`.
+fn min_corner(kind: BuildingKind, translation: Vec3, rotation: Rotation) -> Vec2 {
+    let size = rotation.rotate_footprint(kind.footprint()).as_vec3() * GRID_SIZE;
+    translation.xz() - size.xz() / 2.0
+}
+
+/// Rounds to the nearest half cell to get rid of floating point error. Every
+/// building centre is on a cell centre or a grid line.
+fn to_half_cells(value: Vec3) -> Vec3 {
+    (value / GRID_SIZE * 2.0).round() / 2.0 * GRID_SIZE
+}
+
+impl Blueprint {
+    /// Copies placed buildings. Their positions are stored relative to the
+    /// corner of the area they cover, resting on the lowest of them.
+    pub fn capture<'a>(
+        name: impl Into<String>,
+        buildings: impl IntoIterator<Item = (Vec3, &'a Building)>,
+    ) -> Result<Self, BlueprintError> {
+        let buildings: Vec<_> = buildings.into_iter().collect();
+
+        let corner = buildings
+            .iter()
+            .map(|(translation, building)| {
+                let corner = min_corner(building.kind, *translation, building.rotation);
+                let height = building.kind.footprint().y as f32 * GRID_SIZE;
+                Vec3::new(corner.x, translation.y - height / 2.0, corner.y)
+            })
+            .reduce(|a, b| a.min(b))
+            .ok_or(BlueprintError::Empty)?;
+
+        let entries = buildings
+            .iter()
+            .map(|(translation, building)| BlueprintEntry {
+                kind: building.kind,
+                offset: to_half_cells(*translation - corner).to_array(),
+                rotation: building.rotation,
+                config: building.config.clone(),
+            })
+            .collect();
+
+        Ok(Blueprint {
+            name: name.into(),
+            entries,
+        })
+    }
+
+    /// Where every building goes when the blueprint is pasted with its corner
+    /// at `corner`, turned by `rotation`.
+    pub fn placements(&self, corner: Vec3, rotation: Rotation) -> Vec<(Placement, BuildingConfig)> {
+        let rotated: Vec<_> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let offset = to_half_cells(rotation.quat() * Vec3::from_array(entry.offset));
+                (entry, offset, entry.rotation.then(rotation))
+            })
+            .collect();
+
+        // Turning the blueprint swings some buildings behind the corner, so
+        // shift everything back in front of it.
+        let shift = rotated
+            .iter()
+            .map(|(entry, offset, rotation)| min_corner(entry.kind, *offset, *rotation))
+            .reduce(|a, b| a.min(b))
+            .unwrap_or(Vec2::ZERO);
+
+        rotated
+            .into_iter()
+            .map(|(entry, offset, rotation)| {
+                let placement = Placement {
+                    kind: entry.kind,
+                    translation: corner + offset - Vec3::new(shift.x, 0.0, shift.y),
+                    rotation,
+                };
+                (placement, entry.config.clone())
+            })
+            .collect()
+    }
+
+    /// How many of each building the blueprint needs.
+    pub fn cost(&self) -> BTreeMap<BuildingKind, usize> {
+        let mut cost = BTreeMap::new();
+        for entry in self.entries.iter() {
+            *cost.entry(entry.kind).or_insert(0) += 1;
+        }
+        cost
+    }
+
+    /// Returns the first building there are not enough of in `inventory`.
+    pub fn missing(&self, inventory: &Inventory) -> Option<BuildingKind> {
+        self.cost()
+            .into_iter()
+            .find(|(kind, count)| inventory.amount_of(kind.name()) < *count as f32)
+            .map(|(kind, _)| kind)
+    }
+
+    /// A single line of text that can be shared and turned back into the
+    /// blueprint with [Blueprint::from_text].
+    pub fn to_text(&self) -> Result<String, BlueprintError> {
+        ron::to_string(self).map_err(BlueprintError::Serialize)
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, BlueprintError> {
+        ron::from_str(text.trim()).map_err(BlueprintError::Deserialize)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), BlueprintError> {
+        std::fs::write(path, self.to_text()?).map_err(BlueprintError::Io)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BlueprintError> {
+        let text = std::fs::read_to_string(path).map_err(BlueprintError::Io)?;
+        Self::from_text(&text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn building(kind: BuildingKind, rotation: Rotation) -> Building {
+        Building {
+            kind,
+            rotation,
+            config: BuildingConfig::default(),
+        }
+    }
+
+    fn sample() -> Blueprint {
+        let mut furnace = building(BuildingKind::Furnace, Rotation::North);
+        furnace.config.recipe = Some("iron_ingot".to_string());
+        let pipe = building(BuildingKind::Pipe, Rotation::North);
+
+        let buildings = [
+            (Vec3::new(11.0, 1.0, 5.0), &furnace),
+            (Vec3::new(12.5, 0.5, 5.5), &pipe),
+        ];
+        Blueprint::capture("smelter", buildings).unwrap()
+    }
+
+    #[test]
+    fn test_capture_is_relative() {
+        let blueprint = sample();
+        assert_eq!(blueprint.entries[0].offset, [1.0, 1.0, 1.0]);
+        assert_eq!(blueprint.entries[1].offset, [2.5, 0.5, 1.5]);
+        assert_eq!(
+            blueprint.entries[0].config.recipe.as_deref(),
+            Some("iron_ingot")
+        );
+    }
+
+    #[test]
+    fn test_capture_nothing() {
+        assert!(matches!(
+            Blueprint::capture("empty", Vec::<(Vec3, &Building)>::new()),
+            Err(BlueprintError::Empty)
+        ));
+    }
+
+    #[test]
+    fn test_paste() {
+        let placements = sample().placements(Vec3::new(-4.0, 0.0, 2.0), Rotation::North);
+        assert_eq!(placements[0].0.translation, Vec3::new(-3.0, 1.0, 3.0));
+        assert_eq!(placements[1].0.translation, Vec3::new(-1.5, 0.5, 3.5));
+    }
+
+    #[test]
+    fn test_paste_rotated_stays_in_front_of_corner() {
+        let placements = sample().placements(Vec3::ZERO, Rotation::East);
+        for (placement, _) in placements.iter() {
+            let corner = min_corner(placement.kind, placement.translation, placement.rotation);
+            assert!(corner.x >= 0.0 && corner.y >= 0.0);
+            assert_eq!(placement.rotation, Rotation::East);
+        }
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let blueprint = sample();
+        let text = blueprint.to_text().unwrap();
+        assert!(!text.contains('\n'));
+        assert_eq!(Blueprint::from_text(&text).unwrap(), blueprint);
+    }
+
+    #[test]
+    fn test_cost() {
+        let mut blueprint = sample();
+        blueprint.entries.push(blueprint.entries[1].clone());
+
+        let cost = blueprint.cost();
+        assert_eq!(cost[&BuildingKind::Furnace], 1);
+        assert_eq!(cost[&BuildingKind::Pipe], 2);
+
+        let mut inventory = Inventory::default();
+        inventory.add(crate::buildings::BuildingItem::new(
+            BuildingKind::Furnace,
+            1,
+            0,
+        ));
+        assert_eq!(blueprint.missing(&inventory), Some(BuildingKind::Pipe));
+    }
+}
@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::Collider;
+use serde::{Deserialize, Serialize};
+
+use super::placement::Rotation;
+use crate::anyify;
+use crate::items::{Item, ItemAction, ItemWeight, SpecificItem};
+use crate::physics::{PhysicsBundle, SurfaceMaterial};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum BuildingKind {
+    Furnace,
+    OreWasher,
+    Pipe,
+    Pump,
+    Tank,
+    Lab,
+}
+
+impl BuildingKind {
+    pub const ALL: [BuildingKind; 6] = [
+        BuildingKind::Furnace,
+        BuildingKind::OreWasher,
+        BuildingKind::Pipe,
+        BuildingKind::Pump,
+        BuildingKind::Tank,
+        BuildingKind::Lab,
+    ];
+
+    /// Matches the machine ids unlocked by research.
+    #[rustfmt::skip]
+    pub fn id(&self) -> &'static str {
+        match self {
+            BuildingKind::Furnace   => "furnace",
+            BuildingKind::OreWasher => "ore_washer",
+            BuildingKind::Pipe      => "pipe",
+            BuildingKind::Pump      => "pump",
+            BuildingKind::Tank      => "tank",
+            BuildingKind::Lab       => "lab",
+        }
+    }
+
+    #[rustfmt::skip]
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuildingKind::Furnace   => "Furnace",
+            BuildingKind::OreWasher => "Ore Washer",
+            BuildingKind::Pipe      => "Pipe",
+            BuildingKind::Pump      => "Pump",
+            BuildingKind::Tank      => "Tank",
+            BuildingKind::Lab       => "Lab",
+        }
+    }
+
+    /// Size in grid cells, before rotation.
+    #[rustfmt::skip]
+    pub fn footprint(&self) -> UVec3 {
+        match self {
+            BuildingKind::Furnace   => UVec3::new(2, 2, 2),
+            BuildingKind::OreWasher => UVec3::new(3, 2, 2),
+            BuildingKind::Pipe      => UVec3::new(1, 1, 1),
+            BuildingKind::Pump      => UVec3::new(1, 1, 2),
+            BuildingKind::Tank      => UVec3::new(2, 3, 2),
+            BuildingKind::Lab       => UVec3::new(2, 2, 2),
+        }
+    }
+
+    /// kW a machine draws while running.
+    #[rustfmt::skip]
+    pub fn power_draw(&self) -> f32 {
+        match self {
+            BuildingKind::Furnace   => 15.0,
+            BuildingKind::OreWasher => 8.0,
+            BuildingKind::Pump      => 4.0,
+            BuildingKind::Pipe      => 0.0,
+            BuildingKind::Tank      => 0.0,
+            BuildingKind::Lab       => 5.0,
+        }
+    }
+
+    /// Whether the building does work and so wears down.
+    pub fn is_machine(&self) -> bool {
+        matches!(
+            self,
+            BuildingKind::Furnace
+                | BuildingKind::OreWasher
+                | BuildingKind::Pump
+                | BuildingKind::Lab
+        )
+    }
+}
+
+/// Player chosen settings of a building, kept when it is copied into a
+/// blueprint.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildingConfig {
+    /// Id of the recipe a machine is making.
+    pub recipe: Option<String>,
+    /// [Item::type_name] of the only item let through.
+    pub filter: Option<String>,
+}
+
+/// A placed building in the world.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Building {
+    pub kind: BuildingKind,
+    pub rotation: Rotation,
+    pub config: BuildingConfig,
+}
+
+#[derive(Bundle)]
+pub struct BuildingItemBundle {
+    pub item: BuildingItem,
+    pub physics: PhysicsBundle,
+    pub model: Handle<Scene>,
+    pub transform: Transform,
+}
+
+impl BuildingItemBundle {
+    pub fn new(item: BuildingItem, model: Handle<Scene>, transform: Transform) -> Self {
+        Self {
+            item,
+            physics: PhysicsBundle::item(Collider::cuboid(0.5, 0.5, 0.5), SurfaceMaterial::Wood),
+            model,
+            transform,
+        }
+    }
+}
+
+/// A building packed up in an inventory, ready to be placed.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Component)]
+pub struct BuildingItem {
+    pub kind: BuildingKind,
+    pub count: usize,
+    pub id: usize,
+}
+
+impl BuildingItem {
+    pub fn new(kind: BuildingKind, count: usize, id: usize) -> Self {
+        BuildingItem { kind, count, id }
+    }
+}
+
+#[rustfmt::skip]
+impl Item for BuildingItem {
+    fn type_name(&self) -> &'static str { self.kind.name() }
+    fn type_description(&self) -> &'static str { "A building, ready to be placed." }
+    fn amount(&self) -> ItemWeight { ItemWeight::Discrete(self.count) }
+    fn id(&self) -> usize { self.id }
+    fn mass(&self) -> f32 { self.count as f32 * 5.0 }
+    fn icon_color(&self) -> Color { Color::rgb(0.5, 0.5, 0.55) }
+    fn tags(&self) -> &'static [&'static str] { &["Building"] }
+    fn value(&self) -> f32 { self.count as f32 * 50.0 }
+    fn action(&self) -> Option<ItemAction> { Some(ItemAction::Place) }
+}
+
+impl SpecificItem for BuildingItem {
+    type B = BuildingItemBundle;
+    type M = usize;
+
+    fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
+    fn split(&mut self, count: usize) -> Option<Self> {
+        if count > self.count {
+            return None;
+        }
+
+        self.count -= count;
+        Some(BuildingItem::new(self.kind, count, self.id))
+    }
+
+    fn stack(&mut self, other: Self) -> Option<Self> {
+        if other.kind != self.kind {
+            return Some(other);
+        }
+
+        self.count += other.count;
+        None
+    }
+}
+
+anyify!(BuildingItem);
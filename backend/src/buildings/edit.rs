@@ -0,0 +1,396 @@
+use bevy::prelude::*;
+
+use super::building::{BuildingConfig, BuildingItem};
+use super::placement::{Placement, Rotation};
+use crate::history::{EditCommand, EditContext};
+use crate::iams::Inventory;
+use crate::machines::MachineProcess;
+
+/// Identifies a building across being taken down and put back up, which gives
+/// it a new [Entity].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BuildingId(pub u64);
+
+/// Hands out [BuildingId]s.
+#[derive(Resource, Debug, Default)]
+pub struct BuildingIds {
+    next: u64,
+}
+
+impl BuildingIds {
+    pub fn next_id(&mut self) -> BuildingId {
+        self.next += 1;
+        BuildingId(self.next)
+    }
+}
+
+/// What building commands need from the world. Implemented by the frontend
+/// on top of the ECS.
+pub trait BuildWorld {
+    fn inventory(&mut self) -> &mut Inventory;
+    fn next_id(&mut self) -> BuildingId;
+    fn spawn(&mut self, id: BuildingId, placement: &Placement, config: &BuildingConfig);
+    /// Returns where the building was and how it was set up.
+    fn despawn(&mut self, id: BuildingId) -> Option<(Placement, BuildingConfig)>;
+    /// Takes out what the building's machine holds, so it is not lost while
+    /// the building is down.
+    fn take_contents(&mut self, id: BuildingId) -> Option<MachineProcess>;
+    fn restore_contents(&mut self, id: BuildingId, contents: MachineProcess);
+    fn set_rotation(&mut self, id: BuildingId, rotation: Rotation) -> bool;
+    fn set_config(&mut self, id: BuildingId, config: &BuildingConfig) -> bool;
+}
+
+/// Context of every building command, undone through a `History<BuildEdits>`.
+pub struct BuildEdits;
+
+impl EditContext for BuildEdits {
+    type Target<'a> = dyn BuildWorld + 'a;
+}
+
+fn refund(world: &mut (dyn BuildWorld + '_), placement: &Placement) {
+    world
+        .inventory()
+        .stack(BuildingItem::new(placement.kind, 1, 0));
+}
+
+fn charge(world: &mut (dyn BuildWorld + '_), placement: &Placement) -> bool {
+    world.inventory().take_by_name(placement.kind.name(), 1.0)
+}
+
+/// Places a building, paid for with its item.
+pub struct PlaceBuilding {
+    placement: Placement,
+    config: BuildingConfig,
+    id: Option<BuildingId>,
+    /// What the machine held when the placement was undone.
+    contents: Option<MachineProcess>,
+}
+
+impl PlaceBuilding {
+    pub fn new(placement: Placement, config: BuildingConfig) -> Self {
+        PlaceBuilding {
+            placement,
+            config,
+            id: None,
+            contents: None,
+        }
+    }
+}
+
+impl EditCommand<BuildEdits> for PlaceBuilding {
+    fn apply(&mut self, world: &mut (dyn BuildWorld + '_)) -> bool {
+        if !charge(world, &self.placement) {
+            return false;
+        }
+
+        let id = *self.id.get_or_insert_with(|| world.next_id());
+        world.spawn(id, &self.placement, &self.config);
+        if let Some(contents) = self.contents.take() {
+            world.restore_contents(id, contents);
+        }
+        true
+    }
+
+    fn undo(&mut self, world: &mut (dyn BuildWorld + '_)) -> bool {
+        let Some(id) = self.id else {
+            return false;
+        };
+        let contents = world.take_contents(id);
+        let Some((placement, _)) = world.despawn(id) else {
+            return false;
+        };
+
+        refund(world, &placement);
+        self.contents = contents;
+        true
+    }
+}
+
+/// Takes a building down, giving its item back. What its machine held is
+/// kept to be put back on undo.
+pub struct Deconstruct {
+    id: BuildingId,
+    removed: Option<(Placement, BuildingConfig)>,
+    contents: Option<MachineProcess>,
+}
+
+impl Deconstruct {
+    pub fn new(id: BuildingId) -> Self {
+        Deconstruct {
+            id,
+            removed: None,
+            contents: None,
+        }
+    }
+}
+
+impl EditCommand<BuildEdits> for Deconstruct {
+    fn apply(&mut self, world: &mut (dyn BuildWorld + '_)) -> bool {
+        let contents = world.take_contents(self.id);
+        let Some((placement, config)) = world.despawn(self.id) else {
+            return false;
+        };
+
+        refund(world, &placement);
+        self.removed = Some((placement, config));
+        self.contents = contents;
+        true
+    }
+
+    fn undo(&mut self, world: &mut (dyn BuildWorld + '_)) -> bool {
+        let Some((placement, config)) = self.removed.as_ref() else {
+            return false;
+        };
+        if !charge(world, placement) {
+            return false;
+        }
+
+        world.spawn(self.id, placement, config);
+        if let Some(contents) = self.contents.take() {
+            world.restore_contents(self.id, contents);
+        }
+        true
+    }
+}
+
+pub struct RotateBuilding {
+    id: BuildingId,
+    from: Rotation,
+    to: Rotation,
+}
+
+impl RotateBuilding {
+    pub fn new(id: BuildingId, from: Rotation, to: Rotation) -> Self {
+        RotateBuilding { id, from, to }
+    }
+}
+
+impl EditCommand<BuildEdits> for RotateBuilding {
+    fn apply(&mut self, world: &mut (dyn BuildWorld + '_)) -> bool {
+        world.set_rotation(self.id, self.to)
+    }
+
+    fn undo(&mut self, world: &mut (dyn BuildWorld + '_)) -> bool {
+        world.set_rotation(self.id, self.from)
+    }
+}
+
+pub struct ConfigureBuilding {
+    id: BuildingId,
+    from: BuildingConfig,
+    to: BuildingConfig,
+}
+
+impl ConfigureBuilding {
+    pub fn new(id: BuildingId, from: BuildingConfig, to: BuildingConfig) -> Self {
+        ConfigureBuilding { id, from, to }
+    }
+}
+
+impl EditCommand<BuildEdits> for ConfigureBuilding {
+    fn apply(&mut self, world: &mut (dyn BuildWorld + '_)) -> bool {
+        world.set_config(self.id, &self.to)
+    }
+
+    fn undo(&mut self, world: &mut (dyn BuildWorld + '_)) -> bool {
+        world.set_config(self.id, &self.from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::buildings::BuildingKind;
+    use crate::history::{Batch, History};
+    use crate::items::ore::{IronOre, Ore};
+
+    #[derive(Default)]
+    struct TestWorld {
+        inventory: Inventory,
+        ids: BuildingIds,
+        buildings: HashMap<BuildingId, (Placement, BuildingConfig)>,
+        contents: HashMap<BuildingId, MachineProcess>,
+    }
+
+    impl BuildWorld for TestWorld {
+        fn inventory(&mut self) -> &mut Inventory {
+            &mut self.inventory
+        }
+
+        fn next_id(&mut self) -> BuildingId {
+            self.ids.next_id()
+        }
+
+        fn spawn(&mut self, id: BuildingId, placement: &Placement, config: &BuildingConfig) {
+            self.buildings.insert(id, (*placement, config.clone()));
+        }
+
+        fn despawn(&mut self, id: BuildingId) -> Option<(Placement, BuildingConfig)> {
+            self.buildings.remove(&id)
+        }
+
+        fn take_contents(&mut self, id: BuildingId) -> Option<MachineProcess> {
+            self.contents.remove(&id)
+        }
+
+        fn restore_contents(&mut self, id: BuildingId, contents: MachineProcess) {
+            self.contents.insert(id, contents);
+        }
+
+        fn set_rotation(&mut self, id: BuildingId, rotation: Rotation) -> bool {
+            let Some((placement, _)) = self.buildings.get_mut(&id) else {
+                return false;
+            };
+            placement.rotation = rotation;
+            true
+        }
+
+        fn set_config(&mut self, id: BuildingId, config: &BuildingConfig) -> bool {
+            let Some((_, current)) = self.buildings.get_mut(&id) else {
+                return false;
+            };
+            *current = config.clone();
+            true
+        }
+    }
+
+    fn furnace() -> Placement {
+        Placement::at(BuildingKind::Furnace, Vec3::ZERO, Rotation::North)
+    }
+
+    fn furnaces(world: &TestWorld) -> f32 {
+        world.inventory.amount_of(BuildingKind::Furnace.name())
+    }
+
+    fn setup(furnaces: usize) -> (History<BuildEdits>, TestWorld) {
+        let mut world = TestWorld::default();
+        world
+            .inventory
+            .add(BuildingItem::new(BuildingKind::Furnace, furnaces, 0));
+        (History::default(), world)
+    }
+
+    #[test]
+    fn test_place_undo_refunds() {
+        let (mut history, mut world) = setup(1);
+
+        let place = PlaceBuilding::new(furnace(), BuildingConfig::default());
+        assert!(history.execute(Box::new(place), &mut world));
+        assert_eq!(furnaces(&world), 0.0);
+        assert_eq!(world.buildings.len(), 1);
+
+        assert!(history.undo(&mut world));
+        assert_eq!(furnaces(&world), 1.0);
+        assert!(world.buildings.is_empty());
+
+        assert!(history.redo(&mut world));
+        assert_eq!(furnaces(&world), 0.0);
+        assert_eq!(world.buildings.len(), 1);
+    }
+
+    #[test]
+    fn test_place_without_item() {
+        let (mut history, mut world) = setup(0);
+
+        let place = PlaceBuilding::new(furnace(), BuildingConfig::default());
+        assert!(!history.execute(Box::new(place), &mut world));
+        assert!(world.buildings.is_empty());
+    }
+
+    #[test]
+    fn test_deconstruct_keeps_id_and_config() {
+        let (mut history, mut world) = setup(1);
+        let config = BuildingConfig {
+            recipe: Some("iron_ingot".to_string()),
+            filter: None,
+        };
+
+        history.execute(
+            Box::new(PlaceBuilding::new(furnace(), config.clone())),
+            &mut world,
+        );
+        let id = *world.buildings.keys().next().unwrap();
+
+        assert!(history.execute(Box::new(Deconstruct::new(id)), &mut world));
+        assert_eq!(furnaces(&world), 1.0);
+
+        assert!(history.undo(&mut world));
+        assert_eq!(furnaces(&world), 0.0);
+        assert_eq!(world.buildings[&id].1, config);
+
+        // Undoing the original placement still finds the rebuilt furnace.
+        assert!(history.undo(&mut world));
+        assert!(world.buildings.is_empty());
+    }
+
+    #[test]
+    fn test_deconstruct_keeps_contents() {
+        let (mut history, mut world) = setup(1);
+        history.execute(
+            Box::new(PlaceBuilding::new(furnace(), BuildingConfig::default())),
+            &mut world,
+        );
+        let id = *world.buildings.keys().next().unwrap();
+        let mut process = MachineProcess::default();
+        process.input.add(Ore::<IronOre>::new(2.0, 0.5, 0));
+        process.progress = 0.5;
+        world.contents.insert(id, process);
+
+        assert!(history.execute(Box::new(Deconstruct::new(id)), &mut world));
+        assert!(world.contents.is_empty());
+
+        assert!(history.undo(&mut world));
+        assert_eq!(world.contents[&id].input.amount_of("Iron Ore"), 2.0);
+        assert_eq!(world.contents[&id].progress, 0.5);
+    }
+
+    #[test]
+    fn test_refunds_stack() {
+        let (mut history, mut world) = setup(2);
+        for x in [0.0, 2.0] {
+            let placement = Placement::at(BuildingKind::Furnace, Vec3::X * x, Rotation::North);
+            let place = PlaceBuilding::new(placement, BuildingConfig::default());
+            history.execute(Box::new(place), &mut world);
+        }
+
+        let ids: Vec<_> = world.buildings.keys().copied().collect();
+        for id in ids {
+            assert!(history.execute(Box::new(Deconstruct::new(id)), &mut world));
+        }
+        let items = world.inventory.query::<BuildingItem>().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].count, 2);
+    }
+
+    #[test]
+    fn test_rotate_and_configure() {
+        let (mut history, mut world) = setup(1);
+        history.execute(
+            Box::new(PlaceBuilding::new(furnace(), BuildingConfig::default())),
+            &mut world,
+        );
+        let id = *world.buildings.keys().next().unwrap();
+        let config = BuildingConfig {
+            recipe: Some("copper_ingot".to_string()),
+            filter: None,
+        };
+
+        let edits: Vec<Box<dyn EditCommand<BuildEdits>>> = vec![
+            Box::new(RotateBuilding::new(id, Rotation::North, Rotation::East)),
+            Box::new(ConfigureBuilding::new(
+                id,
+                BuildingConfig::default(),
+                config.clone(),
+            )),
+        ];
+        assert!(history.execute(Box::new(Batch(edits)), &mut world));
+        assert_eq!(world.buildings[&id].0.rotation, Rotation::East);
+        assert_eq!(world.buildings[&id].1, config);
+
+        assert!(history.undo(&mut world));
+        assert_eq!(world.buildings[&id].0.rotation, Rotation::North);
+        assert_eq!(world.buildings[&id].1, BuildingConfig::default());
+    }
+}
@@ -0,0 +1,17 @@
+//! Buildings are placed from [BuildingItem]s in an inventory onto a grid, and
+//! turn back into the item when deconstructed. Groups of them can be copied
+//! into a [Blueprint] and pasted elsewhere. Every edit goes through an
+//! [edit] command so it can be undone.
+
+pub mod blueprint;
+pub mod building;
+pub mod edit;
+pub mod placement;
+
+pub use blueprint::{Blueprint, BlueprintEntry, BlueprintError};
+pub use building::{Building, BuildingConfig, BuildingItem, BuildingKind};
+pub use edit::{
+    BuildEdits, BuildWorld, BuildingId, BuildingIds, ConfigureBuilding, Deconstruct, PlaceBuilding,
+    RotateBuilding,
+};
+pub use placement::{snap_to_grid, Placement, Rotation, GRID_SIZE};
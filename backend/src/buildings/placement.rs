@@ -0,0 +1,190 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::physics::Layer;
+
+use super::building::BuildingKind;
+
+/// Side length of a grid cell in world units.
+pub const GRID_SIZE: f32 = 1.0;
+
+/// Which way a building faces. Buildings only ever rotate in quarter turns
+/// so they stay on the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Rotation {
+    #[default]
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Rotation {
+    pub fn next(&self) -> Self {
+        match self {
+            Rotation::North => Rotation::East,
+            Rotation::East => Rotation::South,
+            Rotation::South => Rotation::West,
+            Rotation::West => Rotation::North,
+        }
+    }
+
+    pub fn quarter_turns(&self) -> u32 {
+        *self as u32
+    }
+
+    pub fn from_quarter_turns(turns: u32) -> Self {
+        match turns % 4 {
+            0 => Rotation::North,
+            1 => Rotation::East,
+            2 => Rotation::South,
+            _ => Rotation::West,
+        }
+    }
+
+    /// This rotation followed by `other`.
+    pub fn then(&self, other: Rotation) -> Self {
+        Self::from_quarter_turns(self.quarter_turns() + other.quarter_turns())
+    }
+
+    pub fn quat(&self) -> Quat {
+        Quat::from_rotation_y(-(self.quarter_turns() as f32) * FRAC_PI_2)
+    }
+
+    /// Grid cells covered by `footprint` once rotated.
+    pub fn rotate_footprint(&self, footprint: UVec3) -> UVec3 {
+        match self {
+            Rotation::North | Rotation::South => footprint,
+            Rotation::East | Rotation::West => UVec3::new(footprint.z, footprint.y, footprint.x),
+        }
+    }
+}
+
+/// Centre of a building with `footprint` resting on `point`, with its cells
+/// lined up on the grid.
+pub fn snap_to_grid(point: Vec3, footprint: UVec3) -> Vec3 {
+    let size = footprint.as_vec3() * GRID_SIZE;
+    let corner = ((point.xz() - size.xz() / 2.0) / GRID_SIZE).round() * GRID_SIZE;
+
+    Vec3::new(
+        corner.x + size.x / 2.0,
+        point.y + size.y / 2.0,
+        corner.y + size.z / 2.0,
+    )
+}
+
+/// Where and how a building is, or would be, placed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    pub kind: BuildingKind,
+    pub translation: Vec3,
+    pub rotation: Rotation,
+}
+
+impl Placement {
+    /// Snaps a building aimed at `point` onto the grid.
+    pub fn at(kind: BuildingKind, point: Vec3, rotation: Rotation) -> Self {
+        let footprint = rotation.rotate_footprint(kind.footprint());
+        Placement {
+            kind,
+            translation: snap_to_grid(point, footprint),
+            rotation,
+        }
+    }
+
+    pub fn transform(&self) -> Transform {
+        Transform::from_translation(self.translation).with_rotation(self.rotation.quat())
+    }
+
+    /// Size in world units, before [Placement::rotation] is applied.
+    pub fn size(&self) -> Vec3 {
+        self.kind.footprint().as_vec3() * GRID_SIZE
+    }
+
+    /// Whether the building would overlap something already there, another
+    /// building included.
+    pub fn is_blocked(&self, spatial_query: &SpatialQuery) -> bool {
+        // Shrunk slightly so resting on the floor or touching a neighbour does
+        // not count as overlapping.
+        let size = self.size() * 0.95;
+        !spatial_query
+            .shape_intersections(
+                &Collider::cuboid(size.x, size.y, size.z),
+                self.translation,
+                self.rotation.quat(),
+                SpatialQueryFilter::from_mask(Layer::Building.occupied()),
+            )
+            .is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snap_odd_footprint() {
+        let snapped = snap_to_grid(Vec3::new(0.3, 0.0, -0.2), UVec3::ONE);
+        assert_eq!(snapped, Vec3::new(0.5, 0.5, -0.5));
+    }
+
+    #[test]
+    fn test_snap_even_footprint() {
+        let snapped = snap_to_grid(Vec3::new(0.3, 1.0, 0.9), UVec3::new(2, 2, 2));
+        assert_eq!(snapped, Vec3::new(0.0, 2.0, 1.0));
+    }
+
+    #[test]
+    fn test_rotation() {
+        let footprint = UVec3::new(3, 2, 1);
+        assert_eq!(
+            Rotation::East.rotate_footprint(footprint),
+            UVec3::new(1, 2, 3)
+        );
+        assert_eq!(Rotation::South.rotate_footprint(footprint), footprint);
+        assert_eq!(Rotation::West.next(), Rotation::North);
+    }
+
+    #[test]
+    fn test_placement_uses_rotated_footprint() {
+        let placement = Placement::at(BuildingKind::OreWasher, Vec3::ZERO, Rotation::East);
+        // 3x2 footprint turned to 2x3: x lands on a grid line, z on a cell centre.
+        assert_eq!(placement.translation, Vec3::new(0.0, 1.0, -0.5));
+    }
+
+    #[test]
+    fn test_overlapping_buildings_block_placement() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        use crate::physics::testing::{app, run};
+        use crate::physics::{PhysicsBundle, SurfaceMaterial};
+
+        let mut app = app();
+        let placed = Placement::at(BuildingKind::Lab, Vec3::ZERO, Rotation::North);
+        let size = placed.size();
+        app.world.spawn((
+            PhysicsBundle::fixed(
+                Layer::Building,
+                Collider::cuboid(size.x, size.y, size.z),
+                SurfaceMaterial::Metal,
+            ),
+            TransformBundle::from_transform(placed.transform()),
+        ));
+        run(&mut app, 2);
+
+        let overlapping = Placement::at(BuildingKind::Lab, Vec3::X, Rotation::North);
+        let beside = Placement::at(BuildingKind::Lab, Vec3::X * 2.0, Rotation::North);
+        let blocked = app
+            .world
+            .run_system_once(move |spatial_query: SpatialQuery| {
+                (
+                    overlapping.is_blocked(&spatial_query),
+                    beside.is_blocked(&spatial_query),
+                )
+            });
+        assert_eq!(blocked, (true, false));
+    }
+}
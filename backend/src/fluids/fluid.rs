@@ -0,0 +1,103 @@
+use std::any::Any;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use crate::as_any::AsAny;
+use crate::items::{Item, ItemWeight, SpecificItem};
+
+/// Runtime tag for a fluid. [FluidNetwork](super::FluidNetwork) is type
+/// erased, so it uses this to stop different fluids from mixing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FluidKind {
+    Water,
+    Slurry,
+}
+
+/// Denotes the type of fluid the [Fluid] struct is representing
+pub trait FluidType: Debug + Send + Sync + PartialEq + Clone + Copy + PartialOrd + 'static {
+    const KIND: FluidKind;
+    const NAME: &'static str;
+    const DESCRIPTION: &'static str;
+}
+
+/// An amount of fluid. Like [Ore](crate::items::ore::Ore), the amount is
+/// continuous, so recipes can take solids and fluids in the same way.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Fluid<T: FluidType> {
+    fluid_type: PhantomData<T>,
+    /// Volume in litres.
+    pub amount: f32,
+    pub id: usize,
+}
+
+#[rustfmt::skip]
+impl<T: FluidType> Fluid<T> {
+    pub fn kind(&self) -> FluidKind { T::KIND }
+
+    pub fn new(amount: f32, id: usize) -> Self {
+        Fluid {
+            fluid_type: PhantomData,
+            amount,
+            id,
+        }
+    }
+}
+
+#[rustfmt::skip]
+impl<T: FluidType> Item for Fluid<T> {
+    fn type_name(&self) -> &'static str { T::NAME }
+    fn type_description(&self) -> &'static str { T::DESCRIPTION }
+    fn amount(&self) -> ItemWeight { ItemWeight::Continuous(self.amount) }
+    fn id(&self) -> usize { self.id }
+    fn unit(&self) -> &'static str { "L" }
+    fn tags(&self) -> &'static [&'static str] { &["Fluid"] }
+}
+
+impl<T: FluidType> SpecificItem for Fluid<T> {
+    // Fluids only exist inside tanks and pipes, never loose in the world.
+    type B = ();
+    type M = f32;
+
+    fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
+    fn split(&mut self, amount: f32) -> Option<Self> {
+        if amount > self.amount {
+            return None;
+        }
+
+        self.amount -= amount;
+        Some(Fluid::new(amount, self.id))
+    }
+
+    fn stack(&mut self, other: Self) -> Option<Self> {
+        self.amount += other.amount;
+        None
+    }
+}
+
+impl<T: FluidType> AsAny for Fluid<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Water;
+impl FluidType for Water {
+    const KIND: FluidKind = FluidKind::Water;
+    const NAME: &'static str = "Water";
+    const DESCRIPTION: &'static str = "Used for washing ore.";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Slurry;
+impl FluidType for Slurry {
+    const KIND: FluidKind = FluidKind::Slurry;
+    const NAME: &'static str = "Slurry";
+    const DESCRIPTION: &'static str = "Crushed ore suspended in water, ready for flotation.";
+}
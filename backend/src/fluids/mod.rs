@@ -0,0 +1,28 @@
+//! Fluids are moved between machines through pipes, pumps and tanks, which
+//! together form a [FluidNetwork]. Amounts of fluid taken out of a network are
+//! [Fluid] items, so they can be stored and used like any other item.
+//! Machines take fluids in and put them out through their [FluidPorts].
+
+pub mod fluid;
+pub mod network;
+
+pub use fluid::{Fluid, FluidKind, FluidType};
+pub use network::{
+    FluidError, FluidLink, FluidNetwork, FluidNode, FluidPort, FluidPorts, PortDirection,
+};
+
+use bevy::prelude::*;
+
+pub struct FluidPlugin;
+
+impl Plugin for FluidPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                network::step_fluid_networks,
+                network::exchange_port_fluids.after(network::step_fluid_networks),
+            ),
+        );
+    }
+}
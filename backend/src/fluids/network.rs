@@ -0,0 +1,421 @@
+use bevy::prelude::*;
+
+use super::fluid::{Fluid, FluidKind, FluidType, Slurry, Water};
+use crate::iams::Inventory;
+use crate::items::SpecificItem;
+use crate::machines::MachineProcess;
+
+/// Capacity in litres of a single pipe segment.
+pub const PIPE_CAPACITY: f32 = 10.0;
+/// Litres per second a machine's [FluidPort] moves in or out of its node.
+pub const PORT_RATE: f32 = 5.0;
+
+/// A pipe segment or tank. Pipes are just small tanks, which keeps the flow
+/// approximation the same for both.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FluidNode {
+    /// The fluid currently held, or [None] when empty.
+    pub kind: Option<FluidKind>,
+    pub amount: f32,
+    pub capacity: f32,
+}
+
+#[rustfmt::skip]
+impl FluidNode {
+    pub fn pipe() -> Self { Self::tank(PIPE_CAPACITY) }
+    pub fn tank(capacity: f32) -> Self { FluidNode { kind: None, amount: 0.0, capacity } }
+
+    /// Approximated as how full the node is, from 0.0 to 1.0.
+    pub fn pressure(&self) -> f32 { self.amount / self.capacity }
+    pub fn free(&self) -> f32 { self.capacity - self.amount }
+
+    fn accepts(&self, kind: FluidKind) -> bool {
+        self.kind.is_none_or(|held| held == kind)
+    }
+}
+
+/// A connection between two [FluidNode]s. A link with a `pump_head` is a pump:
+/// it pushes fluid from `from` to `to` and never lets it flow back.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FluidLink {
+    pub from: usize,
+    pub to: usize,
+    /// Litres per second per unit of pressure difference.
+    pub conductance: f32,
+    pub pump_head: f32,
+    /// Litres per second moved during the last [FluidNetwork::step]. Negative
+    /// when fluid flowed from `to` to `from`.
+    pub flow: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FluidError {
+    /// The network has no node with this index.
+    NoSuchNode(usize),
+}
+
+/// A connected set of pipes, pumps and tanks.
+#[derive(Component, Debug, Default)]
+pub struct FluidNetwork {
+    nodes: Vec<FluidNode>,
+    links: Vec<FluidLink>,
+}
+
+impl FluidNetwork {
+    pub fn add_node(&mut self, node: FluidNode) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    pub fn node(&self, index: usize) -> Option<&FluidNode> {
+        self.nodes.get(index)
+    }
+
+    pub fn links(&self) -> &[FluidLink] {
+        &self.links
+    }
+
+    pub fn connect(
+        &mut self,
+        from: usize,
+        to: usize,
+        conductance: f32,
+    ) -> Result<usize, FluidError> {
+        self.add_link(from, to, conductance, 0.0)
+    }
+
+    pub fn add_pump(
+        &mut self,
+        from: usize,
+        to: usize,
+        conductance: f32,
+        head: f32,
+    ) -> Result<usize, FluidError> {
+        self.add_link(from, to, conductance, head)
+    }
+
+    fn add_link(
+        &mut self,
+        from: usize,
+        to: usize,
+        conductance: f32,
+        pump_head: f32,
+    ) -> Result<usize, FluidError> {
+        if let Some(missing) = [from, to]
+            .into_iter()
+            .find(|node| *node >= self.nodes.len())
+        {
+            return Err(FluidError::NoSuchNode(missing));
+        }
+
+        self.links.push(FluidLink {
+            from,
+            to,
+            conductance,
+            pump_head,
+            flow: 0.0,
+        });
+        Ok(self.links.len() - 1)
+    }
+
+    /// Moves fluid along every link according to the pressure difference
+    /// between its ends. Flow is limited by what the source holds and what the
+    /// destination has room for, and fluids of different kinds never mix.
+    pub fn step(&mut self, delta_seconds: f32) {
+        for link in self.links.iter_mut() {
+            let (from, to) = (&self.nodes[link.from], &self.nodes[link.to]);
+            let mut rate = link.conductance * (from.pressure() - to.pressure() + link.pump_head);
+            if link.pump_head > 0.0 {
+                rate = rate.max(0.0);
+            }
+
+            let (source, dest) = match rate >= 0.0 {
+                true => (link.from, link.to),
+                false => (link.to, link.from),
+            };
+
+            let kind = match self.nodes[source].kind {
+                Some(kind) if self.nodes[dest].accepts(kind) => kind,
+                _ => {
+                    link.flow = 0.0;
+                    continue;
+                }
+            };
+
+            let moved = (rate.abs() * delta_seconds)
+                .min(self.nodes[source].amount)
+                .min(self.nodes[dest].free());
+
+            take(&mut self.nodes[source], moved);
+            self.nodes[dest].kind = Some(kind);
+            self.nodes[dest].amount += moved;
+
+            link.flow = moved.copysign(rate) / delta_seconds;
+        }
+    }
+
+    /// Removes up to `amount` litres of `T` from a node, e.g. for a machine
+    /// input port.
+    pub fn extract<T: FluidType>(
+        &mut self,
+        node: usize,
+        amount: f32,
+        id: usize,
+    ) -> Option<Fluid<T>> {
+        let node = self.nodes.get_mut(node)?;
+        if node.kind != Some(T::KIND) {
+            return None;
+        }
+
+        let moved = amount.min(node.amount);
+        take(node, moved);
+        Some(Fluid::new(moved, id))
+    }
+
+    /// Adds as much of `fluid` as fits into a node, e.g. from a machine output
+    /// port. Returns whatever did not fit.
+    pub fn insert<T: FluidType>(&mut self, node: usize, mut fluid: Fluid<T>) -> Option<Fluid<T>> {
+        let Some(node) = self.nodes.get_mut(node) else {
+            return Some(fluid);
+        };
+        if !node.accepts(T::KIND) {
+            return Some(fluid);
+        }
+
+        let moved = fluid.amount.min(node.free());
+        node.kind = Some(T::KIND);
+        node.amount += moved;
+        fluid.amount -= moved;
+
+        match fluid.amount > 0.0 {
+            true => Some(fluid),
+            false => None,
+        }
+    }
+
+    /// Moves up to `amount` litres of whatever a node holds into
+    /// `inventory`. Returns how much moved.
+    pub fn drain_into(&mut self, node: usize, inventory: &mut Inventory, amount: f32) -> f32 {
+        match self.node(node).and_then(|node| node.kind) {
+            Some(FluidKind::Water) => self.drain::<Water>(node, inventory, amount),
+            Some(FluidKind::Slurry) => self.drain::<Slurry>(node, inventory, amount),
+            None => 0.0,
+        }
+    }
+
+    fn drain<T: FluidType>(&mut self, node: usize, inventory: &mut Inventory, amount: f32) -> f32 {
+        let id = inventory.next_id::<Fluid<T>>();
+        let Some(fluid) = self.extract::<T>(node, amount, id) else {
+            return 0.0;
+        };
+
+        let moved = fluid.amount;
+        if moved > 0.0 {
+            inventory.stack(fluid);
+        }
+        moved
+    }
+
+    /// Moves up to `amount` litres of the fluids in `inventory` into a node,
+    /// as far as it has room. Returns how much moved.
+    pub fn fill_from(&mut self, node: usize, inventory: &mut Inventory, amount: f32) -> f32 {
+        let water = self.fill::<Water>(node, inventory, amount);
+        water + self.fill::<Slurry>(node, inventory, amount - water)
+    }
+
+    fn fill<T: FluidType>(&mut self, node: usize, inventory: &mut Inventory, amount: f32) -> f32 {
+        let Some(fluids) = inventory.query_mut::<Fluid<T>>() else {
+            return 0.0;
+        };
+
+        let mut moved = 0.0;
+        for fluid in fluids.iter_mut() {
+            let Some(part) = fluid.split(fluid.amount.min(amount - moved)) else {
+                continue;
+            };
+            let offered = part.amount;
+            let left = self.insert(node, part).map_or(0.0, |left| left.amount);
+            fluid.amount += left;
+            moved += offered - left;
+        }
+
+        fluids.retain(|fluid| fluid.amount > 0.0);
+        moved
+    }
+}
+
+fn take(node: &mut FluidNode, amount: f32) {
+    node.amount -= amount;
+    if node.amount <= f32::EPSILON {
+        node.amount = 0.0;
+        node.kind = None;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortDirection {
+    Input,
+    Output,
+}
+
+/// Connects a machine to a node of a [FluidNetwork] entity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FluidPort {
+    pub network: Entity,
+    pub node: usize,
+    pub direction: PortDirection,
+}
+
+/// The ports a machine takes fluids in and puts them out through.
+#[derive(Component, Debug, Default, Clone, PartialEq)]
+pub struct FluidPorts(pub Vec<FluidPort>);
+
+pub fn step_fluid_networks(time: Res<Time>, mut networks: Query<&mut FluidNetwork>) {
+    for mut network in networks.iter_mut() {
+        network.step(time.delta_seconds());
+    }
+}
+
+/// Fills machines from their input ports and empties the fluids they made
+/// out through their output ports.
+pub fn exchange_port_fluids(
+    time: Res<Time>,
+    mut machines: Query<(&FluidPorts, &mut MachineProcess)>,
+    mut networks: Query<&mut FluidNetwork>,
+) {
+    let amount = PORT_RATE * time.delta_seconds();
+    for (ports, mut process) in machines.iter_mut() {
+        for port in &ports.0 {
+            let Ok(mut network) = networks.get_mut(port.network) else {
+                continue;
+            };
+
+            match port.direction {
+                PortDirection::Input => network.drain_into(port.node, &mut process.input, amount),
+                PortDirection::Output => network.fill_from(port.node, &mut process.output, amount),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filled(capacity: f32, amount: f32, kind: FluidKind) -> FluidNode {
+        FluidNode {
+            kind: Some(kind),
+            amount,
+            capacity,
+        }
+    }
+
+    #[test]
+    fn test_flows_towards_lower_pressure() {
+        let mut network = FluidNetwork::default();
+        let full = network.add_node(filled(100.0, 100.0, FluidKind::Water));
+        let empty = network.add_node(FluidNode::tank(100.0));
+        network.connect(full, empty, 50.0).unwrap();
+
+        network.step(0.1);
+
+        assert!(network.node(full).unwrap().amount < 100.0);
+        assert_eq!(network.node(empty).unwrap().kind, Some(FluidKind::Water));
+        assert!(network.links()[0].flow > 0.0);
+    }
+
+    #[test]
+    fn test_conserves_volume() {
+        let mut network = FluidNetwork::default();
+        let a = network.add_node(filled(100.0, 80.0, FluidKind::Water));
+        let b = network.add_node(FluidNode::pipe());
+        let c = network.add_node(FluidNode::tank(100.0));
+        network.connect(a, b, 100.0).unwrap();
+        network.connect(b, c, 100.0).unwrap();
+
+        for _ in 0..100 {
+            network.step(0.05);
+        }
+
+        let total: f32 = (0..3).map(|i| network.node(i).unwrap().amount).sum();
+        assert!((total - 80.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_pump_pushes_against_pressure() {
+        let mut network = FluidNetwork::default();
+        let low = network.add_node(filled(100.0, 10.0, FluidKind::Water));
+        let high = network.add_node(filled(100.0, 90.0, FluidKind::Water));
+        network.add_pump(low, high, 20.0, 1.0).unwrap();
+
+        network.step(0.1);
+
+        assert!(network.node(high).unwrap().amount > 90.0);
+    }
+
+    #[test]
+    fn test_fluids_do_not_mix() {
+        let mut network = FluidNetwork::default();
+        let water = network.add_node(filled(100.0, 100.0, FluidKind::Water));
+        let slurry = network.add_node(filled(100.0, 10.0, FluidKind::Slurry));
+        network.connect(water, slurry, 50.0).unwrap();
+
+        network.step(0.1);
+
+        assert_eq!(network.node(water).unwrap().amount, 100.0);
+        assert_eq!(network.node(slurry).unwrap().amount, 10.0);
+    }
+
+    #[test]
+    fn test_extract_and_insert() {
+        let mut network = FluidNetwork::default();
+        let tank = network.add_node(FluidNode::tank(10.0));
+
+        let leftover = network.insert(tank, Fluid::<Water>::new(15.0, 0));
+        assert_eq!(leftover.unwrap().amount, 5.0);
+        assert!(network.extract::<Slurry>(tank, 1.0, 1).is_none());
+
+        let water = network.extract::<Water>(tank, 4.0, 1).unwrap();
+        assert_eq!(water.amount, 4.0);
+        assert_eq!(network.node(tank).unwrap().amount, 6.0);
+    }
+
+    #[test]
+    fn test_links_need_both_nodes() {
+        let mut network = FluidNetwork::default();
+        let tank = network.add_node(FluidNode::tank(10.0));
+
+        assert_eq!(
+            network.connect(tank, 3, 1.0),
+            Err(FluidError::NoSuchNode(3))
+        );
+        assert_eq!(
+            network.add_pump(2, tank, 1.0, 1.0),
+            Err(FluidError::NoSuchNode(2))
+        );
+        assert!(network.links().is_empty());
+
+        // Nothing dangling is left for a step to trip over.
+        network.step(0.1);
+    }
+
+    #[test]
+    fn test_ports_fill_and_empty_machines() {
+        let mut network = FluidNetwork::default();
+        let supply = network.add_node(filled(100.0, 50.0, FluidKind::Water));
+        let drain = network.add_node(FluidNode::tank(3.0));
+        let mut process = MachineProcess::default();
+        process.output.add(Fluid::<Slurry>::new(5.0, 0));
+
+        assert_eq!(network.drain_into(supply, &mut process.input, 4.0), 4.0);
+        assert_eq!(network.drain_into(supply, &mut process.input, 4.0), 4.0);
+        assert_eq!(process.input.amount_of("Water"), 8.0);
+        assert_eq!(process.input.query::<Fluid<Water>>().unwrap().len(), 1);
+        assert_eq!(network.node(supply).unwrap().amount, 42.0);
+
+        // Only as much as the node has room for leaves the machine.
+        assert_eq!(network.fill_from(drain, &mut process.output, 4.0), 3.0);
+        assert_eq!(process.output.amount_of("Slurry"), 2.0);
+        assert_eq!(network.node(drain).unwrap().kind, Some(FluidKind::Slurry));
+    }
+}
@@ -0,0 +1,242 @@
+//! Undo and redo for editing actions. Anything that edits the world through an
+//! [EditContext] can be made undoable by implementing [EditCommand] for it and
+//! running it through a [History].
+
+use bevy::prelude::*;
+
+/// Names what commands edit. The target may borrow from a system, e.g. its
+/// queries, which is why it gets a lifetime.
+pub trait EditContext: 'static {
+    type Target<'a>: ?Sized;
+}
+
+/// A reversible edit. Both directions return false, changing nothing, when
+/// they can no longer be done, e.g. because the items they need are gone.
+pub trait EditCommand<C: EditContext>: Send + Sync {
+    fn apply(&mut self, context: &mut C::Target<'_>) -> bool;
+    fn undo(&mut self, context: &mut C::Target<'_>) -> bool;
+}
+
+/// Several commands done and undone as one.
+pub struct Batch<C: EditContext>(pub Vec<Box<dyn EditCommand<C>>>);
+
+impl<C: EditContext> EditCommand<C> for Batch<C> {
+    fn apply(&mut self, context: &mut C::Target<'_>) -> bool {
+        for applied in 0..self.0.len() {
+            if !self.0[applied].apply(context) {
+                for command in self.0[..applied].iter_mut().rev() {
+                    command.undo(context);
+                }
+                return false;
+            }
+        }
+        true
+    }
+
+    fn undo(&mut self, context: &mut C::Target<'_>) -> bool {
+        for undone in (0..self.0.len()).rev() {
+            if !self.0[undone].undo(context) {
+                for command in self.0[undone + 1..].iter_mut() {
+                    command.apply(context);
+                }
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[derive(Resource)]
+pub struct History<C: EditContext> {
+    done: Vec<Box<dyn EditCommand<C>>>,
+    undone: Vec<Box<dyn EditCommand<C>>>,
+    /// How many commands are remembered before the oldest are forgotten.
+    pub limit: usize,
+}
+
+impl<C: EditContext> Default for History<C> {
+    fn default() -> Self {
+        Self {
+            done: Vec::new(),
+            undone: Vec::new(),
+            limit: 100,
+        }
+    }
+}
+
+impl<C: EditContext> History<C> {
+    /// Applies a command and remembers it so it can be undone. Doing something
+    /// new forgets everything that could have been redone.
+    pub fn execute(
+        &mut self,
+        mut command: Box<dyn EditCommand<C>>,
+        context: &mut C::Target<'_>,
+    ) -> bool {
+        if !command.apply(context) {
+            return false;
+        }
+
+        self.undone.clear();
+        self.done.push(command);
+        if self.done.len() > self.limit {
+            self.done.remove(0);
+        }
+        true
+    }
+
+    pub fn undo(&mut self, context: &mut C::Target<'_>) -> bool {
+        let Some(mut command) = self.done.pop() else {
+            return false;
+        };
+
+        if !command.undo(context) {
+            self.done.push(command);
+            return false;
+        }
+
+        self.undone.push(command);
+        true
+    }
+
+    pub fn redo(&mut self, context: &mut C::Target<'_>) -> bool {
+        let Some(mut command) = self.undone.pop() else {
+            return false;
+        };
+
+        if !command.apply(context) {
+            self.undone.push(command);
+            return false;
+        }
+
+        self.done.push(command);
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Counter;
+
+    impl EditContext for Counter {
+        type Target<'a> = i32;
+    }
+
+    /// Adds to a counter, but never past 10.
+    struct Add(i32);
+
+    impl EditCommand<Counter> for Add {
+        fn apply(&mut self, context: &mut i32) -> bool {
+            if *context + self.0 > 10 {
+                return false;
+            }
+            *context += self.0;
+            true
+        }
+
+        fn undo(&mut self, context: &mut i32) -> bool {
+            *context -= self.0;
+            true
+        }
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut history = History::<Counter>::default();
+        let mut counter = 0;
+
+        assert!(history.execute(Box::new(Add(2)), &mut counter));
+        assert!(history.execute(Box::new(Add(3)), &mut counter));
+        assert_eq!(counter, 5);
+
+        assert!(history.undo(&mut counter));
+        assert_eq!(counter, 2);
+        assert!(history.redo(&mut counter));
+        assert_eq!(counter, 5);
+        assert!(!history.redo(&mut counter));
+    }
+
+    #[test]
+    fn test_new_command_clears_redo() {
+        let mut history = History::<Counter>::default();
+        let mut counter = 0;
+
+        history.execute(Box::new(Add(2)), &mut counter);
+        history.undo(&mut counter);
+        history.execute(Box::new(Add(1)), &mut counter);
+
+        assert!(!history.can_redo());
+        assert_eq!(counter, 1);
+    }
+
+    #[test]
+    fn test_failed_commands_are_not_remembered() {
+        let mut history = History::<Counter>::default();
+        let mut counter = 0;
+
+        assert!(!history.execute(Box::new(Add(11)), &mut counter));
+        assert!(!history.can_undo());
+        assert_eq!(counter, 0);
+    }
+
+    #[test]
+    fn test_failed_redo_stays_redoable() {
+        let mut history = History::<Counter>::default();
+        let mut counter = 0;
+
+        history.execute(Box::new(Add(6)), &mut counter);
+        history.undo(&mut counter);
+        counter = 8;
+
+        assert!(!history.redo(&mut counter));
+        assert!(history.can_redo());
+    }
+
+    #[test]
+    fn test_batch_is_all_or_nothing() {
+        let mut history = History::<Counter>::default();
+        let mut counter = 0;
+
+        let batch = Batch(vec![Box::new(Add(4)), Box::new(Add(4)), Box::new(Add(4))]);
+        assert!(!history.execute(Box::new(batch), &mut counter));
+        assert_eq!(counter, 0);
+
+        let batch = Batch(vec![Box::new(Add(4)), Box::new(Add(4))]);
+        assert!(history.execute(Box::new(batch), &mut counter));
+        assert_eq!(counter, 8);
+        assert!(history.undo(&mut counter));
+        assert_eq!(counter, 0);
+    }
+
+    #[test]
+    fn test_limit() {
+        let mut history = History::<Counter> {
+            limit: 2,
+            ..Default::default()
+        };
+        let mut counter = 0;
+
+        for _ in 0..3 {
+            history.execute(Box::new(Add(1)), &mut counter);
+        }
+
+        assert!(history.undo(&mut counter));
+        assert!(history.undo(&mut counter));
+        assert!(!history.undo(&mut counter));
+        assert_eq!(counter, 1);
+    }
+}
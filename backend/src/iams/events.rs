@@ -0,0 +1,134 @@
+//! Events for items coming into and leaving inventories. They are worked out
+//! by comparing totals, so every way items can move is caught without each
+//! one having to send them.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::prelude::*;
+
+use super::Inventory;
+use crate::items::ItemWeight;
+
+/// Changes smaller than this are rounding, not items moving.
+const IGNORED_CHANGE: f32 = 1e-4;
+
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct InventoryChanged {
+    pub entity: Entity,
+    pub item: &'static str,
+    /// Positive for items gained, negative for items lost.
+    pub change: f32,
+    /// Unit of the change, or None for items counted one by one.
+    pub unit: Option<&'static str>,
+}
+
+impl InventoryChanged {
+    /// The change as shown to players, like "+0.5 kg Iron Ore".
+    pub fn label(&self) -> String {
+        match self.unit {
+            Some(unit) => format!("{:+.1} {unit} {}", self.change, self.item),
+            None => format!("{:+} {}", self.change.round() as i64, self.item),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Total {
+    amount: f32,
+    unit: Option<&'static str>,
+}
+
+/// How much of each item an inventory held when it was last checked.
+#[derive(Component, Debug, Default, Clone, PartialEq)]
+pub struct InventoryTotals(BTreeMap<&'static str, Total>);
+
+impl InventoryTotals {
+    pub fn new(inventory: &Inventory) -> Self {
+        Self(Self::count(inventory))
+    }
+
+    fn count(inventory: &Inventory) -> BTreeMap<&'static str, Total> {
+        let mut totals = BTreeMap::new();
+        for item in inventory.get_all() {
+            let unit = match item.amount() {
+                ItemWeight::Continuous(_) => Some(item.unit()),
+                ItemWeight::Discrete(_) => None,
+            };
+            totals
+                .entry(item.type_name())
+                .or_insert(Total { amount: 0.0, unit })
+                .amount += item.amount().as_f32();
+        }
+        totals
+    }
+
+    /// Catches up with `inventory`, the inventory of `entity`. Returns a
+    /// change for every item whose total is different since the last update.
+    pub fn update(&mut self, entity: Entity, inventory: &Inventory) -> Vec<InventoryChanged> {
+        let totals = Self::count(inventory);
+        let items: BTreeSet<_> = self.0.keys().chain(totals.keys()).copied().collect();
+
+        let changes = items
+            .into_iter()
+            .filter_map(|item| {
+                let before = self.0.get(item);
+                let after = totals.get(item);
+                let change = after.map_or(0.0, |total| total.amount)
+                    - before.map_or(0.0, |total| total.amount);
+                let unit = after.or(before)?.unit;
+                (change.abs() > IGNORED_CHANGE).then_some(InventoryChanged {
+                    entity,
+                    item,
+                    change,
+                    unit,
+                })
+            })
+            .collect();
+
+        self.0 = totals;
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::ore::{IronOre, Ore};
+    use crate::items::spare_part::SparePart;
+    use crate::items::SpecificItem;
+
+    #[test]
+    fn test_totals_start_caught_up() {
+        let mut inventory = Inventory::default();
+        inventory.add(SparePart::new(2, 0));
+
+        let mut totals = InventoryTotals::new(&inventory);
+        assert!(totals.update(Entity::PLACEHOLDER, &inventory).is_empty());
+    }
+
+    #[test]
+    fn test_gains_and_losses() {
+        let mut inventory = Inventory::default();
+        inventory.add(SparePart::new(2, 0));
+        let mut totals = InventoryTotals::new(&inventory);
+
+        inventory.add(Ore::<IronOre>::new(0.5, 0.6, 0));
+        inventory.take_by_name("Spare Part", 2.0);
+        let changes = totals.update(Entity::PLACEHOLDER, &inventory);
+
+        let labels: Vec<_> = changes.iter().map(InventoryChanged::label).collect();
+        assert_eq!(labels, ["+0.5 kg Iron Ore", "-2 Spare Part"]);
+        assert!(totals.update(Entity::PLACEHOLDER, &inventory).is_empty());
+    }
+
+    #[test]
+    fn test_moving_within_inventory_is_not_a_change() {
+        let mut inventory = Inventory::default();
+        inventory.add(SparePart::new(3, 0));
+        let mut totals = InventoryTotals::new(&inventory);
+
+        let part = inventory.query_mut::<SparePart>().unwrap()[0].split(1);
+        inventory.add(part.unwrap());
+        assert!(totals.update(Entity::PLACEHOLDER, &inventory).is_empty());
+    }
+}
@@ -1,16 +1,56 @@
 use crate::as_any::AsAny;
-use crate::items::{Item, SpecificItem};
+use crate::items::{Item, ItemWeight, SpecificItem, SplitAmount};
 use std::any::Any;
 use std::fmt::Debug;
 
+/// Picks out one item in an inventory. Ids are only unique among items of
+/// the same type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ItemKey {
+    pub type_name: &'static str,
+    pub id: usize,
+}
+
+impl ItemKey {
+    pub fn of(item: &dyn Item) -> Self {
+        Self {
+            type_name: item.type_name(),
+            id: item.id(),
+        }
+    }
+}
+
+/// How much of `item` can be split off when `amount` of it is asked for.
+/// Discrete items only split into whole ones, so fractions round down.
+fn splittable(item: &dyn Item, amount: f32) -> f32 {
+    match item.amount() {
+        ItemWeight::Discrete(_) => amount.floor(),
+        ItemWeight::Continuous(_) => amount,
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct Inventory {
     items: Vec<Box<dyn ItemVecTrait>>,
 }
 
-pub trait ItemVecTrait: Any + Debug + AsAny {
+pub trait ItemVecTrait: Any + Debug + AsAny + Send + Sync {
     fn as_generic(&self) -> Vec<&dyn Item>;
     fn as_generic_mut(&mut self) -> Vec<&mut dyn Item>;
+    /// Removes up to `amount` of the items called `type_name`, splitting
+    /// items where needed. Returns how much was actually removed.
+    fn take_amount(&mut self, type_name: &str, amount: f32) -> f32;
+    /// Moves the item picked out by `key` into `to`. With an `amount`, only
+    /// that much is split off and moved. Returns where it landed in `to`.
+    fn move_item(
+        &mut self,
+        key: ItemKey,
+        amount: Option<f32>,
+        to: &mut Inventory,
+    ) -> Option<ItemKey>;
+    /// Splits `amount` off the item picked out by `key` into an item of its
+    /// own, with a new id. Returns the new item's key.
+    fn split_item(&mut self, key: ItemKey, amount: f32) -> Option<ItemKey>;
 }
 
 impl<T: SpecificItem> ItemVecTrait for Vec<T> {
@@ -21,6 +61,62 @@ impl<T: SpecificItem> ItemVecTrait for Vec<T> {
     fn as_generic_mut(&mut self) -> Vec<&mut dyn Item> {
         self.iter_mut().map(|item| item as &mut dyn Item).collect()
     }
+
+    fn take_amount(&mut self, type_name: &str, amount: f32) -> f32 {
+        let mut remaining = amount;
+        for item in self.iter_mut().filter(|item| item.type_name() == type_name) {
+            if remaining <= 0.0 {
+                break;
+            }
+
+            // Discrete items round up, so count what was really split off.
+            let wanted = T::M::from_f32(remaining.min(item.amount().as_f32()));
+            let taken = item
+                .split(wanted)
+                .map_or(0.0, |taken| taken.amount().as_f32());
+            remaining -= taken;
+        }
+
+        self.retain(|item| item.type_name() != type_name || item.amount().as_f32() > 0.0);
+        amount - remaining
+    }
+
+    fn move_item(
+        &mut self,
+        key: ItemKey,
+        amount: Option<f32>,
+        to: &mut Inventory,
+    ) -> Option<ItemKey> {
+        let index = self.iter().position(|item| ItemKey::of(item) == key)?;
+
+        let whole = self[index].amount().as_f32();
+        let moved = match amount.map(|amount| splittable(&self[index], amount)) {
+            Some(amount) if amount <= 0.0 => None,
+            Some(amount) if amount < whole => {
+                let moved = self[index].split(T::M::from_f32(amount));
+                if self[index].amount().as_f32() <= 0.0 {
+                    self.remove(index);
+                }
+                moved
+            }
+            _ => Some(self.remove(index)),
+        }?;
+        Some(to.stack(moved))
+    }
+
+    fn split_item(&mut self, key: ItemKey, amount: f32) -> Option<ItemKey> {
+        let index = self.iter().position(|item| ItemKey::of(item) == key)?;
+        let amount = splittable(&self[index], amount);
+        if amount <= 0.0 || amount >= self[index].amount().as_f32() {
+            return None;
+        }
+
+        let mut split = self[index].split(T::M::from_f32(amount))?;
+        let last = self.iter().map(|item| item.id()).max();
+        split.set_id(last.map_or(0, |id| id + 1));
+        self.push(split);
+        Some(ItemKey::of(&split))
+    }
 }
 
 impl<T: SpecificItem> AsAny for Vec<T> {
@@ -61,6 +157,44 @@ impl Inventory {
         })
     }
 
+    pub fn get_all_mut(&mut self) -> Vec<&mut dyn Item> {
+        self.items
+            .iter_mut()
+            .flat_map(|vec| vec.as_generic_mut())
+            .collect()
+    }
+
+    /// Total mass in kg of everything in the inventory. See [Item::mass].
+    pub fn total_mass(&self) -> f32 {
+        self.get_all().iter().map(|item| item.mass()).sum()
+    }
+
+    /// Total amount of every item called `type_name`. See [Item::type_name].
+    pub fn amount_of(&self, type_name: &str) -> f32 {
+        self.get_all()
+            .iter()
+            .filter(|item| item.type_name() == type_name)
+            .map(|item| item.amount().as_f32())
+            .sum()
+    }
+
+    /// Removes `amount` of the item called `type_name`, or nothing at all if
+    /// there is not enough of it.
+    pub fn take_by_name(&mut self, type_name: &str, amount: f32) -> bool {
+        if self.amount_of(type_name) < amount {
+            return false;
+        }
+
+        let mut remaining = amount;
+        for vec in self.items.iter_mut() {
+            if remaining <= 0.0 {
+                break;
+            }
+            remaining -= vec.take_amount(type_name, remaining);
+        }
+        true
+    }
+
     pub fn add<T: SpecificItem>(&mut self, item: T) {
         match self.query_mut::<T>() {
             Some(vec) => vec.push(item),
@@ -71,12 +205,76 @@ impl Inventory {
         }
     }
 
+    /// Adds `item` onto the first item it stacks with, or on its own with a
+    /// new id if there is none. See [SpecificItem::stack]. Returns where it
+    /// landed.
+    pub fn stack<T: SpecificItem>(&mut self, item: T) -> ItemKey {
+        let mut item = item;
+        let id = self.next_id::<T>();
+        if let Some(vec) = self.query_mut::<T>() {
+            for existing in vec.iter_mut() {
+                match existing.stack(item) {
+                    Some(rest) => item = rest,
+                    None => return ItemKey::of(existing),
+                }
+            }
+        }
+        item.set_id(id);
+        self.add(item);
+        ItemKey::of(&item)
+    }
+
     pub fn remove<T: SpecificItem>(&mut self, to_remove: T) -> Option<T> {
         let vec = self.query_mut::<T>()?;
         let index = vec.iter().position(|item| *item == to_remove)?;
         Some(vec.remove(index))
     }
 
+    pub fn get(&self, key: ItemKey) -> Option<&dyn Item> {
+        self.get_all()
+            .into_iter()
+            .find(|item| ItemKey::of(*item) == key)
+    }
+
+    pub fn contains(&self, key: ItemKey) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Moves the item picked out by `key` into `to`, or only `amount` of it,
+    /// split off with [SpecificItem::split]. What moves is stacked onto what
+    /// `to` already has, or given an id of its own there. Returns where it
+    /// landed in `to`.
+    pub fn move_item(
+        &mut self,
+        key: ItemKey,
+        amount: Option<f32>,
+        to: &mut Inventory,
+    ) -> Option<ItemKey> {
+        self.items
+            .iter_mut()
+            .find_map(|vec| vec.move_item(key, amount, to))
+    }
+
+    /// Splits `amount` off the item picked out by `key`, keeping it as a
+    /// separate item. Returns its key, or None if there is not more than
+    /// `amount` of the item.
+    pub fn split_item(&mut self, key: ItemKey, amount: f32) -> Option<ItemKey> {
+        self.items
+            .iter_mut()
+            .find_map(|vec| vec.split_item(key, amount))
+    }
+
+    /// An id no item of type `T` in the inventory has yet.
+    pub fn next_id<T: SpecificItem>(&self) -> usize {
+        self.query::<T>()
+            .and_then(|vec| vec.iter().map(|item| item.id()).max())
+            .map_or(0, |id| id + 1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.get_all().is_empty()
+    }
+
     pub fn remove_by_id<T: SpecificItem>(&mut self, id: usize) -> Option<T> {
         let vec = self.query_mut::<T>()?;
         let index = vec.iter().position(|item| item.id() == id)?;
@@ -87,6 +285,7 @@ impl Inventory {
 #[cfg(test)]
 mod tests {
     use crate::items::ore::{CopperOre, IronOre, Ore};
+    use crate::items::spare_part::SparePart;
     use crate::items::ItemWeight;
 
     use super::*;
@@ -151,4 +350,195 @@ mod tests {
         let all = inventory.get_all();
         assert_eq!(all.len(), 3);
     }
+
+    #[test]
+    fn test_take_by_name() {
+        let mut inventory = Inventory::default();
+        inventory.add(Ore::<IronOre>::new(1.0, 1.0, 0));
+        inventory.add(Ore::<IronOre>::new(2.0, 1.0, 1));
+        inventory.add(SparePart::new(3, 2));
+
+        assert_eq!(inventory.amount_of("Iron Ore"), 3.0);
+        assert!(!inventory.take_by_name("Iron Ore", 4.0));
+        assert!(inventory.take_by_name("Iron Ore", 1.5));
+        assert!(inventory.take_by_name("Spare Part", 2.0));
+
+        assert_eq!(inventory.amount_of("Iron Ore"), 1.5);
+        assert_eq!(inventory.query::<Ore<IronOre>>().unwrap().len(), 1);
+        assert_eq!(inventory.amount_of("Spare Part"), 1.0);
+    }
+
+    #[test]
+    fn test_take_amount_reports_whole_items() {
+        let mut inventory = Inventory::default();
+        inventory.add(SparePart::new(3, 0));
+
+        let parts = inventory.query_mut::<SparePart>().unwrap();
+        assert_eq!(parts.take_amount("Spare Part", 1.5), 2.0);
+        assert_eq!(inventory.amount_of("Spare Part"), 1.0);
+    }
+
+    #[test]
+    fn test_move_item() {
+        let mut from = Inventory::default();
+        let mut to = Inventory::default();
+        from.add(Ore::<IronOre>::new(1.0, 1.0, 0));
+        from.add(Ore::<CopperOre>::new(2.0, 1.0, 0));
+
+        let key = ItemKey {
+            type_name: "Copper Ore",
+            id: 0,
+        };
+        assert_eq!(from.move_item(key, None, &mut to), Some(key));
+        assert!(!from.contains(key));
+        assert!(to.contains(key));
+        assert_eq!(from.amount_of("Iron Ore"), 1.0);
+
+        assert_eq!(from.move_item(key, None, &mut to), None);
+    }
+
+    #[test]
+    fn test_move_item_onto_taken_id() {
+        let mut from = Inventory::default();
+        let mut to = Inventory::default();
+        from.add(Ore::<IronOre>::new(1.0, 0.5, 0));
+        to.add(Ore::<IronOre>::new(2.0, 0.9, 0));
+
+        let key = ItemKey {
+            type_name: "Iron Ore",
+            id: 0,
+        };
+        let landed = from.move_item(key, None, &mut to).unwrap();
+        assert_eq!(landed.id, 1);
+        assert_eq!(to.get(key).unwrap().purity(), Some(0.9));
+        assert_eq!(to.get(landed).unwrap().purity(), Some(0.5));
+
+        // Stacking items land on what they stack with.
+        from.add(SparePart::new(2, 3));
+        to.add(SparePart::new(1, 0));
+        let part = ItemKey {
+            type_name: "Spare Part",
+            id: 3,
+        };
+        let landed = from.move_item(part, None, &mut to).unwrap();
+        assert_eq!(landed.id, 0);
+        assert_eq!(to.get(landed).unwrap().amount(), ItemWeight::Discrete(3));
+    }
+
+    #[test]
+    fn test_move_part_of_item() {
+        let mut from = Inventory::default();
+        let mut to = Inventory::default();
+        from.add(Ore::<IronOre>::new(3.0, 0.5, 4));
+        from.add(SparePart::new(5, 1));
+
+        let ore = ItemKey {
+            type_name: "Iron Ore",
+            id: 4,
+        };
+        let landed = from.move_item(ore, Some(1.0), &mut to).unwrap();
+        assert_eq!(from.amount_of("Iron Ore"), 2.0);
+        assert_eq!(to.amount_of("Iron Ore"), 1.0);
+        assert_eq!(to.get(landed).unwrap().purity(), Some(0.5));
+
+        let part = ItemKey {
+            type_name: "Spare Part",
+            id: 1,
+        };
+        assert!(from.move_item(part, Some(2.0), &mut to).is_some());
+        assert_eq!(from.amount_of("Spare Part"), 3.0);
+        assert_eq!(to.amount_of("Spare Part"), 2.0);
+
+        // Asking for more than there is moves the lot.
+        assert!(from.move_item(part, Some(10.0), &mut to).is_some());
+        assert!(!from.contains(part));
+        assert_eq!(to.amount_of("Spare Part"), 5.0);
+    }
+
+    #[test]
+    fn test_split_item() {
+        let mut inventory = Inventory::default();
+        inventory.add(SparePart::new(5, 2));
+        let key = ItemKey {
+            type_name: "Spare Part",
+            id: 2,
+        };
+
+        let split = inventory.split_item(key, 2.0).unwrap();
+        assert_eq!(split.id, 3);
+        assert_eq!(
+            inventory.get(key).unwrap().amount(),
+            ItemWeight::Discrete(3)
+        );
+        assert_eq!(
+            inventory.get(split).unwrap().amount(),
+            ItemWeight::Discrete(2)
+        );
+
+        // Splitting off all of it leaves nothing to split from.
+        assert_eq!(inventory.split_item(key, 3.0), None);
+    }
+
+    #[test]
+    fn test_fractions_of_discrete_items_round_down() {
+        let mut from = Inventory::default();
+        let mut to = Inventory::default();
+        from.add(SparePart::new(3, 0));
+        let key = ItemKey {
+            type_name: "Spare Part",
+            id: 0,
+        };
+
+        let split = from.split_item(key, 1.5).unwrap();
+        assert_eq!(from.get(split).unwrap().amount(), ItemWeight::Discrete(1));
+        assert_eq!(from.get(key).unwrap().amount(), ItemWeight::Discrete(2));
+
+        let landed = from.move_item(key, Some(1.5), &mut to).unwrap();
+        assert_eq!(to.get(landed).unwrap().amount(), ItemWeight::Discrete(1));
+        assert_eq!(from.get(key).unwrap().amount(), ItemWeight::Discrete(1));
+
+        // Less than one whole item moves nothing, and nothing is left at zero.
+        assert_eq!(from.move_item(key, Some(0.5), &mut to), None);
+        assert_eq!(from.amount_of("Spare Part"), 2.0);
+        assert!(from
+            .get_all()
+            .iter()
+            .all(|item| item.amount().as_f32() > 0.0));
+    }
+
+    #[test]
+    fn test_next_id() {
+        let mut inventory = Inventory::default();
+        assert_eq!(inventory.next_id::<SparePart>(), 0);
+
+        inventory.add(SparePart::new(1, 4));
+        inventory.add(Ore::<IronOre>::new(1.0, 1.0, 9));
+        assert_eq!(inventory.next_id::<SparePart>(), 5);
+        assert_eq!(inventory.next_id::<Ore<IronOre>>(), 10);
+    }
+
+    #[test]
+    fn test_stack() {
+        let mut inventory = Inventory::default();
+        inventory.stack(SparePart::new(2, 0));
+        inventory.stack(SparePart::new(3, 1));
+        assert_eq!(inventory.query::<SparePart>().unwrap().len(), 1);
+        assert_eq!(inventory.amount_of("Spare Part"), 5.0);
+
+        // Ores do not stack, as their purities would be lost.
+        inventory.stack(Ore::<IronOre>::new(1.0, 0.5, 0));
+        inventory.stack(Ore::<IronOre>::new(1.0, 0.5, 1));
+        assert_eq!(inventory.query::<Ore<IronOre>>().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_total_mass() {
+        let mut inventory = Inventory::default();
+        assert_eq!(inventory.total_mass(), 0.0);
+
+        inventory.add(Ore::<IronOre>::new(1.5, 1.0, 0));
+        inventory.add(Ore::<CopperOre>::new(2.0, 1.0, 1));
+        inventory.add(SparePart::new(3, 2));
+        assert_eq!(inventory.total_mass(), 9.5);
+    }
 }
@@ -1,5 +1,9 @@
 //! IAMS (Inventory and Asset Management System) encompasses the management of
 //! [item]s. See [item] for definition of item.
 
+pub mod events;
 pub mod inventory;
+pub mod tooltip;
+pub mod view;
+
 pub use inventory::Inventory;
@@ -0,0 +1,185 @@
+//! What the tooltip for an item says, worked out from the item and the
+//! recipe book so the UI only has to lay it out.
+
+use super::view::format_purity;
+use crate::buildings::BuildingKind;
+use crate::items::{Item, ItemWeight};
+use crate::recipes::{Recipe, RecipeBook, HAND};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tooltip {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// The exact amount, with units.
+    pub amount: String,
+    pub purity: Option<String>,
+    pub condition: Option<String>,
+    pub tags: Vec<&'static str>,
+    /// Recipes taking the item, with the machine making them.
+    pub used_in: Vec<String>,
+    /// Recipes making the item, with the machine making them.
+    pub produced_by: Vec<String>,
+    /// The recipe repairing the item, for tools.
+    pub repaired_by: Option<String>,
+    pub value: String,
+}
+
+impl Tooltip {
+    pub fn new(item: &dyn Item, recipes: &RecipeBook) -> Self {
+        let name = item.type_name();
+        Self {
+            name,
+            description: item.type_description(),
+            amount: format_exact_amount(item),
+            purity: item.purity().map(format_purity),
+            condition: item.condition().map(format_condition),
+            tags: item.tags().to_vec(),
+            used_in: recipes.used_in(name).map(recipe_label).collect(),
+            produced_by: recipes.produced_by(name).map(recipe_label).collect(),
+            repaired_by: recipes.repairing(name).map(recipe_label),
+            value: format_value(item.value()),
+        }
+    }
+
+    /// The tooltip as lines of text, in the order they are shown.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![self.description.to_string(), self.amount.clone()];
+        lines.extend(self.purity.clone());
+        lines.extend(self.condition.clone());
+        if !self.tags.is_empty() {
+            lines.push(format!("Tags: {}", self.tags.join(", ")));
+        }
+        if !self.used_in.is_empty() {
+            lines.push(format!("Used in: {}", self.used_in.join(", ")));
+        }
+        if !self.produced_by.is_empty() {
+            lines.push(format!("Made by: {}", self.produced_by.join(", ")));
+        }
+        if let Some(repaired_by) = &self.repaired_by {
+            lines.push(format!("Repaired by: {repaired_by}"));
+        }
+        lines.push(format!("Value: {}", self.value));
+        lines
+    }
+}
+
+/// The amount without rounding. Continuous amounts are in the item's
+/// [Item::unit].
+pub fn format_exact_amount(item: &dyn Item) -> String {
+    match item.amount() {
+        ItemWeight::Continuous(amount) => format!("{amount} {}", item.unit()),
+        ItemWeight::Discrete(1) => "1 item".to_string(),
+        ItemWeight::Discrete(count) => format!("{count} items"),
+    }
+}
+
+pub fn format_condition(condition: f32) -> String {
+    format!("{:.0}% condition", condition * 100.0)
+}
+
+pub fn format_value(credits: f32) -> String {
+    format!("{credits:.2} cr")
+}
+
+fn recipe_label(recipe: &Recipe) -> String {
+    if recipe.machine == HAND {
+        return format!("{} (by hand)", recipe.name);
+    }
+    let machine = BuildingKind::ALL
+        .into_iter()
+        .find(|kind| kind.id() == recipe.machine)
+        .map_or(recipe.machine.as_str(), |kind| kind.name());
+    format!("{} ({machine})", recipe.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fluids::fluid::{Fluid, Water};
+    use crate::items::ore::{IronOre, Ore};
+    use crate::items::spare_part::SparePart;
+    use crate::items::tool::{Tool, ToolKind};
+    use crate::recipes::recipe_book::testing::recipes;
+
+    #[test]
+    fn test_ore_tooltip() {
+        let tooltip = Tooltip::new(&Ore::<IronOre>::new(1.234, 0.5, 0), &recipes());
+
+        assert_eq!(tooltip.name, "Iron Ore");
+        assert_eq!(tooltip.description, "A rock containing iron.");
+        assert_eq!(tooltip.amount, "1.234 kg");
+        assert_eq!(tooltip.purity.as_deref(), Some("50% pure"));
+        assert_eq!(tooltip.tags, ["Ore", "Smeltable"]);
+        assert_eq!(
+            tooltip.used_in,
+            ["Iron Ingot (Furnace)", "Washed Iron Ore (Ore Washer)"]
+        );
+        assert_eq!(tooltip.produced_by, ["Washed Iron Ore (Ore Washer)"]);
+        assert_eq!(tooltip.value, "1.23 cr");
+    }
+
+    #[test]
+    fn test_discrete_tooltip() {
+        let tooltip = Tooltip::new(&SparePart::new(3, 0), &recipes());
+
+        assert_eq!(tooltip.amount, "3 items");
+        assert_eq!(tooltip.purity, None);
+        assert!(tooltip.used_in.is_empty());
+        assert_eq!(tooltip.value, "30.00 cr");
+
+        let single = Tooltip::new(&SparePart::new(1, 0), &recipes());
+        assert_eq!(single.amount, "1 item");
+    }
+
+    #[test]
+    fn test_fluids_are_in_litres() {
+        let tooltip = Tooltip::new(&Fluid::<Water>::new(2.5, 0), &recipes());
+        assert_eq!(tooltip.amount, "2.5 L");
+        assert_eq!(tooltip.used_in, ["Washed Iron Ore (Ore Washer)"]);
+    }
+
+    #[test]
+    fn test_tool_condition() {
+        let recipes = RecipeBook::from_ron(
+            r#"[
+                (
+                    id: "repair_pickaxe",
+                    name: "Repair Pickaxe",
+                    machine: "hand",
+                    time: 2.0,
+                    inputs: [(item: "Spare Part", amount: 1.0)],
+                    outputs: [(item: "Pickaxe", amount: 1.0)],
+                    repairs: Some("Pickaxe"),
+                ),
+            ]"#,
+        )
+        .unwrap();
+        let pickaxe = Tool {
+            durability: 90,
+            ..Tool::new(ToolKind::Pickaxe, 0)
+        };
+        let tooltip = Tooltip::new(&pickaxe, &recipes);
+
+        assert_eq!(tooltip.condition.as_deref(), Some("75% condition"));
+        assert_eq!(tooltip.value, "15.00 cr");
+        assert!(tooltip.produced_by.is_empty());
+        assert_eq!(
+            tooltip.repaired_by.as_deref(),
+            Some("Repair Pickaxe (by hand)")
+        );
+    }
+
+    #[test]
+    fn test_lines_skip_empty_sections() {
+        let lines = Tooltip::new(&SparePart::new(2, 0), &RecipeBook::default()).lines();
+        assert_eq!(
+            lines,
+            [
+                "Gears, seals and bearings for fixing machines.",
+                "2 items",
+                "Tags: Part",
+                "Value: 20.00 cr",
+            ]
+        );
+    }
+}
@@ -0,0 +1,237 @@
+//! What the inventory screen shows, worked out from an [Inventory] so the UI
+//! only has to draw it, and can tell when it needs drawing again by comparing
+//! with what it drew last.
+
+use bevy::render::color::Color;
+
+use super::inventory::ItemKey;
+use super::Inventory;
+use crate::items::{Item, ItemWeight};
+
+/// One item as the inventory screen shows it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemView {
+    pub type_name: &'static str,
+    pub id: usize,
+    pub amount: String,
+    pub purity: Option<String>,
+    pub icon: Color,
+}
+
+impl ItemView {
+    pub fn new(item: &dyn Item) -> Self {
+        Self {
+            type_name: item.type_name(),
+            id: item.id(),
+            amount: format_amount(item.amount(), item.unit()),
+            purity: item.purity().map(format_purity),
+            icon: item.icon_color(),
+        }
+    }
+}
+
+/// Continuous amounts are in `unit`, see [Item::unit], discrete amounts a
+/// count.
+pub fn format_amount(amount: ItemWeight, unit: &str) -> String {
+    match amount {
+        ItemWeight::Continuous(amount) => format!("{amount:.2} {unit}"),
+        ItemWeight::Discrete(count) => format!("x{count}"),
+    }
+}
+
+pub fn format_purity(purity: f32) -> String {
+    format!("{:.0}% pure", purity * 100.0)
+}
+
+/// The order the player has dragged their items into. Items it does not
+/// mention go after the rest, in the order they were picked up.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Layout(Vec<ItemKey>);
+
+impl Layout {
+    /// The items of `inventory` in this order.
+    pub fn sort<'a>(&self, inventory: &'a Inventory) -> Vec<&'a dyn Item> {
+        let mut items = inventory.get_all();
+        items.sort_by_key(|item| {
+            let key = ItemKey::of(*item);
+            self.0
+                .iter()
+                .position(|placed| *placed == key)
+                .unwrap_or(usize::MAX)
+        });
+        items
+    }
+
+    /// Moves `key` to where `target` is, pushing `target` and everything
+    /// after it back one slot.
+    pub fn place(&mut self, inventory: &Inventory, key: ItemKey, target: ItemKey) {
+        let mut order: Vec<ItemKey> = self
+            .sort(inventory)
+            .into_iter()
+            .map(ItemKey::of)
+            .filter(|placed| *placed != key)
+            .collect();
+        let Some(index) = order.iter().position(|placed| *placed == target) else {
+            return;
+        };
+        order.insert(index, key);
+        self.0 = order;
+    }
+}
+
+/// One page of the inventory screen.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InventoryPage {
+    pub items: Vec<ItemView>,
+    /// Index of this page, from 0.
+    pub page: usize,
+    /// Number of pages, at least one even when the inventory is empty.
+    pub pages: usize,
+}
+
+impl InventoryPage {
+    /// The `page`th page of `inventory` arranged by `layout`, with
+    /// `per_page` items on a page. Asking for a page past the end gives the
+    /// last page.
+    pub fn new(inventory: &Inventory, layout: &Layout, page: usize, per_page: usize) -> Self {
+        let per_page = per_page.max(1);
+        let items = layout.sort(inventory);
+        let pages = items.len().div_ceil(per_page).max(1);
+        let page = page.min(pages - 1);
+
+        Self {
+            items: items
+                .into_iter()
+                .skip(page * per_page)
+                .take(per_page)
+                .map(ItemView::new)
+                .collect(),
+            page,
+            pages,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fluids::fluid::{Fluid, Water};
+    use crate::items::ore::{IronOre, Ore};
+    use crate::items::spare_part::SparePart;
+
+    fn inventory(ores: usize) -> Inventory {
+        let mut inventory = Inventory::default();
+        for id in 0..ores {
+            inventory.add(Ore::<IronOre>::new(1.5, 0.8, id));
+        }
+        inventory
+    }
+
+    #[test]
+    fn test_item_view() {
+        let mut inventory = inventory(1);
+        inventory.add(SparePart::new(3, 7));
+        let page = InventoryPage::new(&inventory, &Layout::default(), 0, 10);
+
+        assert_eq!(page.items[0].type_name, "Iron Ore");
+        assert_eq!(page.items[0].amount, "1.50 kg");
+        assert_eq!(page.items[0].purity.as_deref(), Some("80% pure"));
+        assert_eq!(page.items[1].id, 7);
+        assert_eq!(page.items[1].amount, "x3");
+        assert_eq!(page.items[1].purity, None);
+    }
+
+    #[test]
+    fn test_fluids_show_litres() {
+        let mut inventory = Inventory::default();
+        inventory.add(Fluid::<Water>::new(2.5, 0));
+        let page = InventoryPage::new(&inventory, &Layout::default(), 0, 10);
+
+        assert_eq!(page.items[0].amount, "2.50 L");
+    }
+
+    #[test]
+    fn test_pages() {
+        let inventory = inventory(25);
+
+        let first = InventoryPage::new(&inventory, &Layout::default(), 0, 10);
+        assert_eq!(first.pages, 3);
+        assert_eq!(first.items.len(), 10);
+
+        let last = InventoryPage::new(&inventory, &Layout::default(), 2, 10);
+        assert_eq!(last.items.len(), 5);
+        assert_eq!(last.items[0].id, 20);
+    }
+
+    #[test]
+    fn test_page_past_end_is_last_page() {
+        let page = InventoryPage::new(&inventory(25), &Layout::default(), 9, 10);
+        assert_eq!(page.page, 2);
+    }
+
+    #[test]
+    fn test_empty_inventory_has_one_page() {
+        let page = InventoryPage::new(&Inventory::default(), &Layout::default(), 3, 10);
+        assert_eq!(page.page, 0);
+        assert_eq!(page.pages, 1);
+        assert!(page.items.is_empty());
+    }
+
+    fn ids(page: &InventoryPage) -> Vec<usize> {
+        page.items.iter().map(|item| item.id).collect()
+    }
+
+    #[test]
+    fn test_layout_places_items() {
+        let inventory = inventory(4);
+        let mut layout = Layout::default();
+        let key = |id| ItemKey {
+            type_name: "Iron Ore",
+            id,
+        };
+
+        layout.place(&inventory, key(3), key(1));
+        assert_eq!(
+            ids(&InventoryPage::new(&inventory, &layout, 0, 10)),
+            [0, 3, 1, 2]
+        );
+
+        layout.place(&inventory, key(0), key(2));
+        assert_eq!(
+            ids(&InventoryPage::new(&inventory, &layout, 0, 10)),
+            [3, 1, 0, 2]
+        );
+    }
+
+    #[test]
+    fn test_new_items_go_last() {
+        let mut inventory = inventory(2);
+        let mut layout = Layout::default();
+        let key = |id| ItemKey {
+            type_name: "Iron Ore",
+            id,
+        };
+        layout.place(&inventory, key(1), key(0));
+
+        inventory.add(SparePart::new(1, 0));
+        let page = InventoryPage::new(&inventory, &layout, 0, 10);
+        assert_eq!(ids(&page), [1, 0, 0]);
+        assert_eq!(page.items[2].type_name, "Spare Part");
+    }
+
+    #[test]
+    fn test_changes_are_noticed() {
+        let mut inventory = inventory(2);
+        let before = InventoryPage::new(&inventory, &Layout::default(), 0, 10);
+        assert_eq!(
+            before,
+            InventoryPage::new(&inventory, &Layout::default(), 0, 10)
+        );
+
+        inventory.take_by_name("Iron Ore", 0.5);
+        assert_ne!(
+            before,
+            InventoryPage::new(&inventory, &Layout::default(), 0, 10)
+        );
+    }
+}
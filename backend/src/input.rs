@@ -0,0 +1,680 @@
+//! Maps raw input onto game [Action]s. Systems read the [ActionState] instead
+//! of keys, so bindings can be changed by the player and tests can press
+//! actions directly.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::input::mouse::MouseWheel;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How far an analog input has to be pushed to count as pressed.
+pub const PRESS_THRESHOLD: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Sprint,
+    /// Down in the free-fly camera.
+    FlyDown,
+    LookLeft,
+    LookRight,
+    LookUp,
+    LookDown,
+    Interact,
+    ToggleInventory,
+    ToggleResearch,
+    ToggleControls,
+    ToggleCursor,
+    /// Opens the pause menu, stopping the game.
+    Pause,
+    SwitchCamera,
+    /// Held to zoom the third-person camera with the mouse wheel instead of
+    /// scrolling the hotbar.
+    Zoom,
+    Place,
+    SelectBuilding,
+    Rotate,
+    Deconstruct,
+    Copy,
+    Paste,
+    Undo,
+    Redo,
+    HotbarNext,
+    HotbarPrevious,
+    Hotbar1,
+    Hotbar2,
+    Hotbar3,
+    Hotbar4,
+    Hotbar5,
+    Hotbar6,
+    Hotbar7,
+    Hotbar8,
+    Hotbar9,
+    RepairTool,
+}
+
+impl Action {
+    pub const ALL: [Action; 39] = [
+        Action::MoveForward,
+        Action::MoveBack,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::Sprint,
+        Action::FlyDown,
+        Action::LookLeft,
+        Action::LookRight,
+        Action::LookUp,
+        Action::LookDown,
+        Action::Interact,
+        Action::ToggleInventory,
+        Action::ToggleResearch,
+        Action::ToggleControls,
+        Action::ToggleCursor,
+        Action::Pause,
+        Action::SwitchCamera,
+        Action::Zoom,
+        Action::Place,
+        Action::SelectBuilding,
+        Action::Rotate,
+        Action::Deconstruct,
+        Action::Copy,
+        Action::Paste,
+        Action::Undo,
+        Action::Redo,
+        Action::HotbarNext,
+        Action::HotbarPrevious,
+        Action::Hotbar1,
+        Action::Hotbar2,
+        Action::Hotbar3,
+        Action::Hotbar4,
+        Action::Hotbar5,
+        Action::Hotbar6,
+        Action::Hotbar7,
+        Action::Hotbar8,
+        Action::Hotbar9,
+        Action::RepairTool,
+    ];
+
+    /// The actions selecting each hotbar slot, in order.
+    pub const HOTBAR_SLOTS: [Action; 9] = [
+        Action::Hotbar1,
+        Action::Hotbar2,
+        Action::Hotbar3,
+        Action::Hotbar4,
+        Action::Hotbar5,
+        Action::Hotbar6,
+        Action::Hotbar7,
+        Action::Hotbar8,
+        Action::Hotbar9,
+    ];
+
+    #[rustfmt::skip]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveForward     => "Move forward",
+            Action::MoveBack        => "Move back",
+            Action::MoveLeft        => "Move left",
+            Action::MoveRight       => "Move right",
+            Action::Jump            => "Jump",
+            Action::Sprint          => "Sprint",
+            Action::FlyDown         => "Fly down",
+            Action::LookLeft        => "Look left",
+            Action::LookRight       => "Look right",
+            Action::LookUp          => "Look up",
+            Action::LookDown        => "Look down",
+            Action::Interact        => "Interact",
+            Action::ToggleInventory => "Inventory",
+            Action::ToggleResearch  => "Research",
+            Action::ToggleControls  => "Controls",
+            Action::ToggleCursor    => "Free cursor",
+            Action::Pause           => "Pause",
+            Action::SwitchCamera    => "Switch camera",
+            Action::Zoom            => "Zoom with wheel",
+            Action::Place           => "Use held item",
+            Action::SelectBuilding  => "Select building",
+            Action::Rotate          => "Rotate",
+            Action::Deconstruct     => "Deconstruct",
+            Action::Copy            => "Copy blueprint",
+            Action::Paste           => "Paste blueprint",
+            Action::Undo            => "Undo",
+            Action::Redo            => "Redo",
+            Action::HotbarNext      => "Next hotbar slot",
+            Action::HotbarPrevious  => "Previous hotbar slot",
+            Action::Hotbar1         => "Hotbar slot 1",
+            Action::Hotbar2         => "Hotbar slot 2",
+            Action::Hotbar3         => "Hotbar slot 3",
+            Action::Hotbar4         => "Hotbar slot 4",
+            Action::Hotbar5         => "Hotbar slot 5",
+            Action::Hotbar6         => "Hotbar slot 6",
+            Action::Hotbar7         => "Hotbar slot 7",
+            Action::Hotbar8         => "Hotbar slot 8",
+            Action::Hotbar9         => "Hotbar slot 9",
+            Action::RepairTool      => "Repair held tool",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AxisDirection {
+    Positive,
+    Negative,
+}
+
+/// Something the player can press or push to trigger an [Action].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    /// A key pressed while a modifier, e.g. control, is held.
+    Chord(KeyCode, KeyCode),
+    /// A key pressed while two modifiers, e.g. control and shift, are held.
+    DoubleChord(KeyCode, KeyCode, KeyCode),
+    Mouse(MouseButton),
+    GamepadButton(GamepadButtonType),
+    /// One direction of a stick.
+    GamepadAxis(GamepadAxisType, AxisDirection),
+    /// Scrolling the mouse wheel up or down, which only counts for the frame
+    /// it happens in.
+    MouseWheel(AxisDirection),
+}
+
+/// The raw input bindings are read from.
+pub struct RawInputs<'a> {
+    pub keys: &'a ButtonInput<KeyCode>,
+    pub mouse: &'a ButtonInput<MouseButton>,
+    /// How far the wheel was scrolled this frame, up being positive.
+    pub mouse_wheel: f32,
+    pub gamepads: &'a [Gamepad],
+    pub gamepad_buttons: &'a ButtonInput<GamepadButton>,
+    pub gamepad_axes: &'a Axis<GamepadAxis>,
+}
+
+impl Binding {
+    /// From 0 for not pressed to 1 for fully pressed.
+    pub fn value(&self, inputs: &RawInputs) -> f32 {
+        let pressed = |pressed: bool| match pressed {
+            true => 1.0,
+            false => 0.0,
+        };
+
+        match *self {
+            Binding::Key(key) => pressed(inputs.keys.pressed(key)),
+            Binding::Chord(modifier, key) => {
+                pressed(inputs.keys.pressed(modifier) && inputs.keys.pressed(key))
+            }
+            Binding::DoubleChord(first, second, key) => pressed(
+                inputs.keys.pressed(first)
+                    && inputs.keys.pressed(second)
+                    && inputs.keys.pressed(key),
+            ),
+            Binding::Mouse(button) => pressed(inputs.mouse.pressed(button)),
+            Binding::GamepadButton(button) => pressed(inputs.gamepads.iter().any(|gamepad| {
+                inputs
+                    .gamepad_buttons
+                    .pressed(GamepadButton::new(*gamepad, button))
+            })),
+            Binding::GamepadAxis(axis, direction) => inputs
+                .gamepads
+                .iter()
+                .filter_map(|gamepad| inputs.gamepad_axes.get(GamepadAxis::new(*gamepad, axis)))
+                .map(|value| match direction {
+                    AxisDirection::Positive => value.max(0.0),
+                    AxisDirection::Negative => (-value).max(0.0),
+                })
+                .fold(0.0, f32::max),
+            Binding::MouseWheel(direction) => pressed(match direction {
+                AxisDirection::Positive => inputs.mouse_wheel > 0.0,
+                AxisDirection::Negative => inputs.mouse_wheel < 0.0,
+            }),
+        }
+    }
+
+    /// The keys that have to be held, empty for anything but the keyboard.
+    fn keys(&self) -> Vec<KeyCode> {
+        match *self {
+            Binding::Key(key) => vec![key],
+            Binding::Chord(modifier, key) => vec![modifier, key],
+            Binding::DoubleChord(first, second, key) => vec![first, second, key],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Whether holding `other` also holds every key of this binding, e.g.
+    /// control for control+z, or control+z for control+shift+z.
+    fn is_part_of(&self, other: &Binding) -> bool {
+        let (keys, other_keys) = (self.keys(), other.keys());
+        !keys.is_empty()
+            && keys.len() < other_keys.len()
+            && keys.iter().all(|key| other_keys.contains(key))
+    }
+
+    /// Short text shown in the controls menu.
+    pub fn label(&self) -> String {
+        match self {
+            Binding::Key(key) => format!("{key:?}"),
+            Binding::Chord(modifier, key) => format!("{modifier:?}+{key:?}"),
+            Binding::DoubleChord(first, second, key) => format!("{first:?}+{second:?}+{key:?}"),
+            Binding::Mouse(button) => format!("Mouse {button:?}"),
+            Binding::GamepadButton(button) => format!("Gamepad {button:?}"),
+            Binding::GamepadAxis(axis, AxisDirection::Positive) => format!("Gamepad {axis:?}+"),
+            Binding::GamepadAxis(axis, AxisDirection::Negative) => format!("Gamepad {axis:?}-"),
+            Binding::MouseWheel(AxisDirection::Positive) => "Wheel up".to_string(),
+            Binding::MouseWheel(AxisDirection::Negative) => "Wheel down".to_string(),
+        }
+    }
+}
+
+/// Which inputs trigger each action. Saved in the settings file.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct InputBindings {
+    bindings: BTreeMap<Action, Vec<Binding>>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        use AxisDirection::{Negative, Positive};
+        type B = Binding;
+
+        let bindings = Action::ALL
+            .into_iter()
+            .map(|action| {
+                #[rustfmt::skip]
+                let bindings = match action {
+                    Action::MoveForward     => vec![B::Key(KeyCode::KeyW), B::GamepadAxis(GamepadAxisType::LeftStickY, Positive)],
+                    Action::MoveBack        => vec![B::Key(KeyCode::KeyS), B::GamepadAxis(GamepadAxisType::LeftStickY, Negative)],
+                    Action::MoveLeft        => vec![B::Key(KeyCode::KeyA), B::GamepadAxis(GamepadAxisType::LeftStickX, Negative)],
+                    Action::MoveRight       => vec![B::Key(KeyCode::KeyD), B::GamepadAxis(GamepadAxisType::LeftStickX, Positive)],
+                    Action::Jump            => vec![B::Key(KeyCode::Space), B::GamepadButton(GamepadButtonType::South)],
+                    Action::Sprint          => vec![B::Key(KeyCode::ShiftLeft), B::GamepadButton(GamepadButtonType::LeftThumb)],
+                    Action::FlyDown         => vec![B::Key(KeyCode::ControlLeft), B::GamepadButton(GamepadButtonType::East)],
+                    Action::LookLeft        => vec![B::GamepadAxis(GamepadAxisType::RightStickX, Negative)],
+                    Action::LookRight       => vec![B::GamepadAxis(GamepadAxisType::RightStickX, Positive)],
+                    Action::LookUp          => vec![B::GamepadAxis(GamepadAxisType::RightStickY, Positive)],
+                    Action::LookDown        => vec![B::GamepadAxis(GamepadAxisType::RightStickY, Negative)],
+                    Action::Interact        => vec![B::Key(KeyCode::KeyR), B::GamepadButton(GamepadButtonType::West)],
+                    Action::ToggleInventory => vec![B::Key(KeyCode::Tab), B::GamepadButton(GamepadButtonType::North)],
+                    Action::ToggleResearch  => vec![B::Key(KeyCode::KeyT)],
+                    Action::ToggleControls  => vec![B::Key(KeyCode::F1)],
+                    Action::ToggleCursor    => vec![B::Key(KeyCode::AltLeft)],
+                    Action::Pause           => vec![B::Key(KeyCode::Escape), B::GamepadButton(GamepadButtonType::Start)],
+                    Action::SwitchCamera    => vec![B::Key(KeyCode::F5), B::GamepadButton(GamepadButtonType::Select)],
+                    Action::Zoom            => vec![B::Key(KeyCode::KeyZ)],
+                    Action::Place           => vec![B::Mouse(MouseButton::Left), B::GamepadButton(GamepadButtonType::RightTrigger2)],
+                    Action::SelectBuilding  => vec![B::Key(KeyCode::KeyB), B::GamepadButton(GamepadButtonType::DPadUp)],
+                    Action::Rotate          => vec![B::Key(KeyCode::KeyQ), B::GamepadButton(GamepadButtonType::DPadRight)],
+                    Action::Deconstruct     => vec![B::Key(KeyCode::KeyX), B::GamepadButton(GamepadButtonType::DPadDown)],
+                    Action::Copy            => vec![B::Key(KeyCode::KeyC)],
+                    Action::Paste           => vec![B::Key(KeyCode::KeyV)],
+                    Action::Undo            => vec![B::Chord(KeyCode::ControlLeft, KeyCode::KeyZ)],
+                    Action::Redo            => vec![B::Chord(KeyCode::ControlLeft, KeyCode::KeyY), B::DoubleChord(KeyCode::ControlLeft, KeyCode::ShiftLeft, KeyCode::KeyZ)],
+                    Action::HotbarNext      => vec![B::MouseWheel(Negative), B::GamepadButton(GamepadButtonType::RightTrigger)],
+                    Action::HotbarPrevious  => vec![B::MouseWheel(Positive), B::GamepadButton(GamepadButtonType::LeftTrigger)],
+                    Action::Hotbar1         => vec![B::Key(KeyCode::Digit1)],
+                    Action::Hotbar2         => vec![B::Key(KeyCode::Digit2)],
+                    Action::Hotbar3         => vec![B::Key(KeyCode::Digit3)],
+                    Action::Hotbar4         => vec![B::Key(KeyCode::Digit4)],
+                    Action::Hotbar5         => vec![B::Key(KeyCode::Digit5)],
+                    Action::Hotbar6         => vec![B::Key(KeyCode::Digit6)],
+                    Action::Hotbar7         => vec![B::Key(KeyCode::Digit7)],
+                    Action::Hotbar8         => vec![B::Key(KeyCode::Digit8)],
+                    Action::Hotbar9         => vec![B::Key(KeyCode::Digit9)],
+                    Action::RepairTool      => vec![B::Key(KeyCode::KeyG)],
+                };
+                (action, bindings)
+            })
+            .collect();
+
+        Self { bindings }
+    }
+}
+
+impl InputBindings {
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Makes `binding` the main binding of `action`, replacing its old main
+    /// binding. Returns the actions it was taken away from, as one input only
+    /// does one thing.
+    pub fn bind(&mut self, action: Action, binding: Binding) -> Vec<Action> {
+        let mut unbound = Vec::new();
+        for (other, bindings) in self.bindings.iter_mut() {
+            if *other != action && bindings.contains(&binding) {
+                bindings.retain(|other_binding| *other_binding != binding);
+                unbound.push(*other);
+            }
+        }
+
+        let bindings = self.bindings.entry(action).or_default();
+        bindings.retain(|other_binding| *other_binding != binding);
+        match bindings.is_empty() {
+            true => bindings.push(binding),
+            false => bindings[0] = binding,
+        }
+        unbound
+    }
+
+    /// Gives actions missing from a settings file written by an older version
+    /// their default bindings. Older versions also freed the cursor with
+    /// escape, which now pauses, so pause bindings are taken off every other
+    /// action and actions left with none go back to their defaults.
+    pub fn fill_defaults(&mut self) {
+        let defaults = InputBindings::default();
+        for (action, bindings) in &defaults.bindings {
+            self.bindings.entry(*action).or_insert(bindings.clone());
+        }
+
+        let pause = self.get(Action::Pause).to_vec();
+        for (action, bindings) in self.bindings.iter_mut() {
+            if *action == Action::Pause || !bindings.iter().any(|b| pause.contains(b)) {
+                continue;
+            }
+            bindings.retain(|binding| !pause.contains(binding));
+            if bindings.is_empty() {
+                *bindings = defaults.get(*action).to_vec();
+            }
+        }
+    }
+
+    /// How far each action is pressed right now. A held chord consumes its
+    /// keys, so control+z does not fly down and control+shift+z does not undo.
+    pub fn values<'a>(
+        &'a self,
+        inputs: &'a RawInputs<'a>,
+    ) -> impl Iterator<Item = (Action, f32)> + 'a {
+        let held: Vec<Binding> = self
+            .bindings
+            .values()
+            .flatten()
+            .filter(|binding| binding.keys().len() > 1 && binding.value(inputs) > 0.0)
+            .copied()
+            .collect();
+
+        Action::ALL.into_iter().map(move |action| {
+            let value = self
+                .get(action)
+                .iter()
+                .filter(|binding| !held.iter().any(|chord| binding.is_part_of(chord)))
+                .map(|binding| binding.value(inputs))
+                .fold(0.0, f32::max);
+            (action, value)
+        })
+    }
+}
+
+/// How far every action is pressed this frame.
+#[derive(Resource, Debug, Default)]
+pub struct ActionState {
+    values: BTreeMap<Action, f32>,
+    just_pressed: BTreeSet<Action>,
+    just_released: BTreeSet<Action>,
+    /// Ignores new presses, e.g. while the controls menu waits for a new
+    /// binding. Inputs still held when it ends do not count as just pressed.
+    pub suspended: bool,
+}
+
+impl ActionState {
+    pub fn set(&mut self, action: Action, value: f32) {
+        let was_pressed = self.pressed(action);
+        self.values.insert(action, value);
+
+        match (was_pressed, self.pressed(action)) {
+            (false, true) => self.just_pressed.insert(action),
+            (true, false) => self.just_released.insert(action),
+            _ => false,
+        };
+    }
+
+    pub fn press(&mut self, action: Action) {
+        self.set(action, 1.0);
+    }
+
+    pub fn release(&mut self, action: Action) {
+        self.set(action, 0.0);
+    }
+
+    /// Forgets what was just pressed or released, at the start of a frame.
+    pub fn clear(&mut self) {
+        self.just_pressed.clear();
+        self.just_released.clear();
+    }
+
+    pub fn value(&self, action: Action) -> f32 {
+        self.values.get(&action).copied().unwrap_or(0.0)
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.value(action) >= PRESS_THRESHOLD
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn just_released(&self, action: Action) -> bool {
+        self.just_released.contains(&action)
+    }
+
+    /// From -1 when only `negative` is pressed to 1 when only `positive` is.
+    pub fn axis(&self, negative: Action, positive: Action) -> f32 {
+        self.value(positive) - self.value(negative)
+    }
+}
+
+pub struct ActionPlugin;
+
+impl Plugin for ActionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActionState>()
+            .init_resource::<InputBindings>()
+            .add_systems(PreUpdate, read_actions.after(InputSystem));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn read_actions(
+    bindings: Res<InputBindings>,
+    mut actions: ResMut<ActionState>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut wheel: EventReader<MouseWheel>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+) {
+    let gamepads: Vec<_> = gamepads.iter().collect();
+    let inputs = RawInputs {
+        keys: &keys,
+        mouse: &mouse,
+        mouse_wheel: wheel.read().map(|event| event.y).sum(),
+        gamepads: &gamepads,
+        gamepad_buttons: &gamepad_buttons,
+        gamepad_axes: &gamepad_axes,
+    };
+
+    actions.clear();
+    for (action, value) in bindings.values(&inputs) {
+        actions.set(action, value);
+    }
+    if actions.suspended {
+        actions.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Inputs {
+        keys: ButtonInput<KeyCode>,
+        mouse: ButtonInput<MouseButton>,
+        mouse_wheel: f32,
+        gamepad_buttons: ButtonInput<GamepadButton>,
+        gamepad_axes: Axis<GamepadAxis>,
+    }
+
+    impl Inputs {
+        fn new() -> Self {
+            Self {
+                keys: ButtonInput::default(),
+                mouse: ButtonInput::default(),
+                mouse_wheel: 0.0,
+                gamepad_buttons: ButtonInput::default(),
+                gamepad_axes: Axis::default(),
+            }
+        }
+
+        fn raw<'a>(&'a self, gamepads: &'a [Gamepad]) -> RawInputs<'a> {
+            RawInputs {
+                keys: &self.keys,
+                mouse: &self.mouse,
+                mouse_wheel: self.mouse_wheel,
+                gamepads,
+                gamepad_buttons: &self.gamepad_buttons,
+                gamepad_axes: &self.gamepad_axes,
+            }
+        }
+    }
+
+    fn value(bindings: &InputBindings, inputs: &RawInputs, action: Action) -> f32 {
+        bindings
+            .values(inputs)
+            .find(|(other, _)| *other == action)
+            .unwrap()
+            .1
+    }
+
+    #[test]
+    fn test_just_pressed_once() {
+        let mut actions = ActionState::default();
+
+        actions.press(Action::Jump);
+        assert!(actions.just_pressed(Action::Jump));
+
+        actions.clear();
+        actions.press(Action::Jump);
+        assert!(actions.pressed(Action::Jump));
+        assert!(!actions.just_pressed(Action::Jump));
+
+        actions.release(Action::Jump);
+        assert!(actions.just_released(Action::Jump));
+    }
+
+    #[test]
+    fn test_axis() {
+        let mut actions = ActionState::default();
+        actions.set(Action::MoveRight, 0.3);
+        actions.press(Action::MoveLeft);
+
+        assert!((actions.axis(Action::MoveLeft, Action::MoveRight) + 0.7).abs() < 1e-6);
+        assert!(!actions.pressed(Action::MoveRight));
+    }
+
+    #[test]
+    fn test_keys_and_chords() {
+        let bindings = InputBindings::default();
+        let mut inputs = Inputs::new();
+        inputs.keys.press(KeyCode::KeyZ);
+
+        assert_eq!(value(&bindings, &inputs.raw(&[]), Action::Undo), 0.0);
+
+        inputs.keys.press(KeyCode::ControlLeft);
+        assert_eq!(value(&bindings, &inputs.raw(&[]), Action::Undo), 1.0);
+        // The chord consumes control.
+        assert_eq!(value(&bindings, &inputs.raw(&[]), Action::FlyDown), 0.0);
+
+        inputs.keys.release(KeyCode::KeyZ);
+        assert_eq!(value(&bindings, &inputs.raw(&[]), Action::FlyDown), 1.0);
+    }
+
+    #[test]
+    fn test_redo_with_shift() {
+        let bindings = InputBindings::default();
+        let mut inputs = Inputs::new();
+        inputs.keys.press(KeyCode::ControlLeft);
+        inputs.keys.press(KeyCode::ShiftLeft);
+        inputs.keys.press(KeyCode::KeyZ);
+
+        let raw = inputs.raw(&[]);
+        assert_eq!(value(&bindings, &raw, Action::Redo), 1.0);
+        assert_eq!(value(&bindings, &raw, Action::Undo), 0.0);
+        assert_eq!(value(&bindings, &raw, Action::Sprint), 0.0);
+    }
+
+    #[test]
+    fn test_gamepad_axes() {
+        let bindings = InputBindings::default();
+        let gamepad = Gamepad::new(0);
+        let mut inputs = Inputs::new();
+        inputs
+            .gamepad_axes
+            .set(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY), -0.8);
+
+        let gamepads = [gamepad];
+        let raw = inputs.raw(&gamepads);
+        assert!((value(&bindings, &raw, Action::MoveBack) - 0.8).abs() < 1e-6);
+        assert_eq!(value(&bindings, &raw, Action::MoveForward), 0.0);
+    }
+
+    #[test]
+    fn test_mouse_wheel() {
+        let bindings = InputBindings::default();
+        let mut inputs = Inputs::new();
+
+        inputs.mouse_wheel = -1.0;
+        let raw = inputs.raw(&[]);
+        assert_eq!(value(&bindings, &raw, Action::HotbarNext), 1.0);
+        assert_eq!(value(&bindings, &raw, Action::HotbarPrevious), 0.0);
+
+        inputs.mouse_wheel = 2.0;
+        let raw = inputs.raw(&[]);
+        assert_eq!(value(&bindings, &raw, Action::HotbarNext), 0.0);
+        assert_eq!(value(&bindings, &raw, Action::HotbarPrevious), 1.0);
+    }
+
+    #[test]
+    fn test_bind_takes_binding_from_others() {
+        let mut bindings = InputBindings::default();
+
+        let unbound = bindings.bind(Action::Jump, Binding::Key(KeyCode::KeyV));
+        assert_eq!(unbound, vec![Action::Paste]);
+        assert_eq!(bindings.get(Action::Jump)[0], Binding::Key(KeyCode::KeyV));
+        assert!(bindings.get(Action::Paste).is_empty());
+        // The gamepad binding is kept.
+        assert_eq!(bindings.get(Action::Jump).len(), 2);
+    }
+
+    #[test]
+    fn test_fill_defaults() {
+        let mut bindings: InputBindings = ron::from_str("{Jump: [Key(KeyJ)]}").unwrap();
+        bindings.fill_defaults();
+
+        assert_eq!(bindings.get(Action::Jump), &[Binding::Key(KeyCode::KeyJ)]);
+        assert_eq!(
+            bindings.get(Action::Sprint),
+            InputBindings::default().get(Action::Sprint)
+        );
+    }
+
+    #[test]
+    fn test_fill_defaults_frees_escape_for_pause() {
+        let mut bindings: InputBindings = ron::from_str("{ToggleCursor: [Key(Escape)]}").unwrap();
+        bindings.fill_defaults();
+
+        assert_eq!(
+            bindings.get(Action::ToggleCursor),
+            InputBindings::default().get(Action::ToggleCursor)
+        );
+        assert_eq!(
+            bindings.get(Action::Pause)[0],
+            Binding::Key(KeyCode::Escape)
+        );
+    }
+}
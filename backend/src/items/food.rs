@@ -0,0 +1,84 @@
+use bevy::asset::Handle;
+use bevy::ecs::bundle::Bundle;
+use bevy::ecs::component::Component;
+use bevy::render::color::Color;
+use bevy::scene::Scene;
+use bevy::transform::components::Transform;
+use bevy_xpbd_3d::prelude::Collider;
+
+use super::{Item, ItemAction, ItemWeight, SpecificItem};
+use crate::anyify;
+use crate::physics::{PhysicsBundle, SurfaceMaterial};
+
+/// Health one ration gives back when eaten.
+pub const RATION_HEALING: f32 = 25.0;
+
+#[derive(Bundle)]
+pub struct FoodBundle {
+    pub food: Food,
+    pub physics: PhysicsBundle,
+    pub model: Handle<Scene>,
+    pub transform: Transform,
+}
+
+impl FoodBundle {
+    pub fn new(food: Food, model: Handle<Scene>, transform: Transform) -> Self {
+        Self {
+            food,
+            physics: PhysicsBundle::item(Collider::cuboid(0.2, 0.1, 0.2), SurfaceMaterial::Wood),
+            model,
+            transform,
+        }
+    }
+}
+
+/// Eaten from the hotbar to heal.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Component)]
+pub struct Food {
+    pub count: usize,
+    pub id: usize,
+}
+
+impl Food {
+    pub fn new(count: usize, id: usize) -> Self {
+        Food { count, id }
+    }
+}
+
+#[rustfmt::skip]
+impl Item for Food {
+    fn type_name(&self) -> &'static str { "Ration" }
+    fn type_description(&self) -> &'static str { "Dried and tinned, but it keeps you going." }
+    fn amount(&self) -> ItemWeight { ItemWeight::Discrete(self.count) }
+    fn id(&self) -> usize { self.id }
+    fn mass(&self) -> f32 { self.count as f32 * 0.5 }
+    fn icon_color(&self) -> Color { Color::rgb(0.8, 0.6, 0.3) }
+    fn tags(&self) -> &'static [&'static str] { &["Food"] }
+    fn value(&self) -> f32 { self.count as f32 * 5.0 }
+    fn action(&self) -> Option<ItemAction> { Some(ItemAction::Eat) }
+}
+
+impl SpecificItem for Food {
+    type B = FoodBundle;
+    type M = usize;
+
+    fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
+    fn split(&mut self, count: usize) -> Option<Self> {
+        if count > self.count {
+            return None;
+        }
+
+        self.count -= count;
+        Some(Food::new(count, self.id))
+    }
+
+    fn stack(&mut self, other: Self) -> Option<Self> {
+        self.count += other.count;
+        None
+    }
+}
+
+anyify!(Food);
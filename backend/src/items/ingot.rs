@@ -0,0 +1,110 @@
+use bevy::asset::Handle;
+use bevy::ecs::bundle::Bundle;
+use bevy::ecs::component::Component;
+use bevy::render::color::Color;
+use bevy::scene::Scene;
+use bevy::transform::components::Transform;
+use bevy_xpbd_3d::prelude::Collider;
+
+use super::{Item, ItemWeight, SpecificItem};
+use crate::anyify;
+use crate::physics::{PhysicsBundle, SurfaceMaterial};
+
+#[derive(Bundle)]
+pub struct IngotBundle {
+    pub ingot: Ingot,
+    pub physics: PhysicsBundle,
+    pub model: Handle<Scene>,
+    pub transform: Transform,
+}
+
+impl IngotBundle {
+    pub fn new(ingot: Ingot, model: Handle<Scene>, transform: Transform) -> Self {
+        Self {
+            ingot,
+            physics: PhysicsBundle::item(Collider::cuboid(0.3, 0.1, 0.15), SurfaceMaterial::Metal),
+            model,
+            transform,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd)]
+pub enum Metal {
+    Copper,
+    Iron,
+}
+
+/// Smelted metal, free of the rock it came in, so always pure.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Component)]
+pub struct Ingot {
+    pub metal: Metal,
+    pub amount: f32,
+    pub id: usize,
+}
+
+impl Ingot {
+    pub fn new(metal: Metal, amount: f32, id: usize) -> Self {
+        Ingot { metal, amount, id }
+    }
+}
+
+#[rustfmt::skip]
+impl Item for Ingot {
+    fn type_name(&self) -> &'static str {
+        match self.metal {
+            Metal::Copper => "Copper Ingot",
+            Metal::Iron   => "Iron Ingot",
+        }
+    }
+    fn type_description(&self) -> &'static str {
+        match self.metal {
+            Metal::Copper => "Copper smelted out of its ore.",
+            Metal::Iron   => "Iron smelted out of its ore.",
+        }
+    }
+    fn amount(&self) -> ItemWeight { ItemWeight::Continuous(self.amount) }
+    fn id(&self) -> usize { self.id }
+    fn icon_color(&self) -> Color {
+        match self.metal {
+            Metal::Copper => Color::rgb(0.85, 0.5, 0.25),
+            Metal::Iron   => Color::rgb(0.6, 0.6, 0.62),
+        }
+    }
+    fn tags(&self) -> &'static [&'static str] { &["Ingot", "Metal"] }
+    fn value(&self) -> f32 {
+        match self.metal {
+            Metal::Copper => self.amount * 8.0,
+            Metal::Iron   => self.amount * 6.0,
+        }
+    }
+}
+
+impl SpecificItem for Ingot {
+    type B = IngotBundle;
+    type M = f32;
+
+    fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
+    fn split(&mut self, amount: f32) -> Option<Self> {
+        if amount > self.amount {
+            return None;
+        }
+
+        self.amount -= amount;
+        Some(Ingot::new(self.metal, amount, self.id))
+    }
+
+    fn stack(&mut self, other: Self) -> Option<Self> {
+        if other.metal != self.metal {
+            return Some(other);
+        }
+
+        self.amount += other.amount;
+        None
+    }
+}
+
+anyify!(Ingot);
@@ -1,4 +1,5 @@
 use bevy::ecs::bundle::Bundle;
+use bevy::render::color::Color;
 use std::fmt::Debug;
 
 use crate::as_any::AsAny;
@@ -9,12 +10,62 @@ pub enum ItemWeight {
     Discrete(usize),
 }
 
+impl ItemWeight {
+    /// The amount as a plain number, regardless of whether it is continuous.
+    pub fn as_f32(&self) -> f32 {
+        match self {
+            ItemWeight::Continuous(amount) => *amount,
+            ItemWeight::Discrete(count) => *count as f32,
+        }
+    }
+}
+
+/// The amount type used by [SpecificItem::split]. Lets code that only knows
+/// amounts as plain numbers, like recipe costs, split items.
+pub trait SplitAmount {
+    fn from_f32(amount: f32) -> Self;
+}
+
+impl SplitAmount for f32 {
+    fn from_f32(amount: f32) -> Self {
+        amount
+    }
+}
+
+impl SplitAmount for usize {
+    fn from_f32(amount: f32) -> Self {
+        amount.ceil() as usize
+    }
+}
+
+/// What using an item held in hand does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemAction {
+    /// Breaks ore off a deposit.
+    Mine,
+    /// Places the building the item is packed up from.
+    Place,
+    Eat,
+    /// Fixes broken machines.
+    Repair,
+}
+
 pub trait SpecificItem:
     Item + AsAny + Clone + Copy + PartialEq + Debug + Send + Sync + 'static
 {
     type B: Bundle;
-    type M;
+    type M: SplitAmount;
+
+    /// Gives the item a new id, for when it lands among items that already
+    /// use its old one.
+    fn set_id(&mut self, id: usize);
     fn split(&mut self, amount: Self::M) -> Option<Self>;
+
+    /// Adds `other` onto this item if they are the same thing, or hands it
+    /// back if they are not. Items do not stack unless they say so.
+    fn stack(&mut self, other: Self) -> Option<Self> {
+        Some(other)
+    }
 }
 
 /// # Examples:
@@ -58,4 +109,58 @@ pub trait Item: 'static + Sync + Send + Debug + AsAny {
     fn type_description(&self) -> &'static str;
     fn amount(&self) -> ItemWeight;
     fn id(&self) -> usize;
+
+    /// Mass in kg, used for encumbrance. Continuous amounts are already a
+    /// mass, discrete items weigh 1 kg each unless they say otherwise.
+    fn mass(&self) -> f32 {
+        self.amount().as_f32()
+    }
+
+    /// Share of the item that is the wanted material, from 0 to 1, for items
+    /// that can be impure.
+    fn purity(&self) -> Option<f32> {
+        None
+    }
+
+    /// Sets how pure the item is. Items that cannot be impure ignore it.
+    fn set_purity(&mut self, _purity: f32) {}
+
+    /// Colour of the item's icon in the inventory.
+    fn icon_color(&self) -> Color {
+        Color::GRAY
+    }
+
+    /// Unit continuous amounts are measured in.
+    fn unit(&self) -> &'static str {
+        "kg"
+    }
+
+    /// Broad kinds the item belongs to, for players looking it over.
+    fn tags(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// What all of the item is worth, in credits.
+    fn value(&self) -> f32 {
+        0.0
+    }
+
+    /// How worn the item is, from 0 when broken to 1 when new, for items
+    /// that wear out.
+    fn condition(&self) -> Option<f32> {
+        None
+    }
+
+    /// Uses left before the item breaks, for items that wear out.
+    fn durability(&self) -> Option<u32> {
+        None
+    }
+
+    /// Sets the uses left. Items that do not wear out ignore it.
+    fn set_durability(&mut self, _durability: u32) {}
+
+    /// What using the item in hand does, if anything.
+    fn action(&self) -> Option<ItemAction> {
+        None
+    }
 }
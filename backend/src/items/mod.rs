@@ -1,4 +1,9 @@
+pub mod food;
+pub mod ingot;
 mod item;
 pub mod ore;
+pub mod registry;
+pub mod spare_part;
+pub mod tool;
 
 pub use item::*;
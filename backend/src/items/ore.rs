@@ -1,24 +1,37 @@
 use bevy::asset::Handle;
 use bevy::ecs::bundle::Bundle;
 use bevy::ecs::component::Component;
+use bevy::render::color::Color;
 use bevy::scene::Scene;
 use bevy::transform::components::Transform;
 
 use super::{Item, SpecificItem};
 use crate::anyify;
 use crate::items::ItemWeight;
-use bevy_xpbd_3d::components::RigidBody;
+use crate::physics::{PhysicsBundle, SurfaceMaterial};
+use bevy_xpbd_3d::prelude::Collider;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 
 #[derive(Bundle)]
 pub struct OreBundle<T: OreType> {
     pub ore: Ore<T>,
-    pub rigid_body: RigidBody,
+    pub physics: PhysicsBundle,
     pub model: Handle<Scene>,
     pub transform: Transform,
 }
 
+impl<T: OreType> OreBundle<T> {
+    pub fn new(ore: Ore<T>, model: Handle<Scene>, transform: Transform) -> Self {
+        Self {
+            ore,
+            physics: PhysicsBundle::item(Collider::cuboid(0.3, 0.3, 0.3), SurfaceMaterial::Stone),
+            model,
+            transform,
+        }
+    }
+}
+
 /// Denotes the type of ore the [Ore] struct is representing
 pub trait OreType: Debug + Send + Sync + PartialEq + Clone + Copy + PartialOrd + 'static {}
 
@@ -63,12 +76,21 @@ impl Item for Ore<CopperOre> {
     fn type_description(&self) -> &'static str { "A rock containing copper." }
     fn amount(&self) -> ItemWeight { self.amount() }
     fn id(&self) -> usize { self.id() }
+    fn purity(&self) -> Option<f32> { Some(self.purity) }
+    fn set_purity(&mut self, purity: f32) { self.purity = purity; }
+    fn icon_color(&self) -> Color { Color::rgb(0.72, 0.45, 0.2) }
+    fn tags(&self) -> &'static [&'static str] { &["Ore", "Smeltable"] }
+    fn value(&self) -> f32 { self.amount * self.purity * 3.0 }
 }
 
 impl SpecificItem for Ore<CopperOre> {
     type B = OreBundle<CopperOre>;
     type M = f32;
 
+    fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
     fn split(&mut self, amount: f32) -> Option<Self> {
         if amount > self.amount {
             return None;
@@ -92,16 +114,25 @@ impl OreType for IronOre {}
 
 #[rustfmt::skip]
 impl Item for Ore<IronOre> {
-    fn type_name(&self) -> &'static str { "Copper Ore" }
-    fn type_description(&self) -> &'static str { "A rock containing copper." }
+    fn type_name(&self) -> &'static str { "Iron Ore" }
+    fn type_description(&self) -> &'static str { "A rock containing iron." }
     fn amount(&self) -> ItemWeight { ItemWeight::Continuous(self.amount) }
     fn id(&self) -> usize { self.id() }
+    fn purity(&self) -> Option<f32> { Some(self.purity) }
+    fn set_purity(&mut self, purity: f32) { self.purity = purity; }
+    fn icon_color(&self) -> Color { Color::rgb(0.55, 0.35, 0.3) }
+    fn tags(&self) -> &'static [&'static str] { &["Ore", "Smeltable"] }
+    fn value(&self) -> f32 { self.amount * self.purity * 2.0 }
 }
 
 impl SpecificItem for Ore<IronOre> {
     type B = OreBundle<IronOre>;
     type M = f32;
 
+    fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
     fn split(&mut self, amount: f32) -> Option<Self> {
         if amount > self.amount {
             return None;
@@ -118,3 +149,20 @@ impl SpecificItem for Ore<IronOre> {
 }
 
 anyify!(Ore<IronOre>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Iron ore used to share copper ore's name, so recipes and research
+    /// costs could not tell the two apart.
+    #[test]
+    fn test_ores_have_their_own_names() {
+        let copper = Ore::<CopperOre>::new(1.0, 1.0, 0);
+        let iron = Ore::<IronOre>::new(1.0, 1.0, 0);
+
+        assert_eq!(copper.type_name(), "Copper Ore");
+        assert_eq!(iron.type_name(), "Iron Ore");
+        assert_eq!(iron.type_description(), "A rock containing iron.");
+    }
+}
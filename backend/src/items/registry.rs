@@ -0,0 +1,102 @@
+//! Makes items from their names, for things that only know items by name,
+//! like the outputs of recipes.
+
+use super::food::Food;
+use super::ingot::{Ingot, Metal};
+use super::ore::{CopperOre, IronOre, Ore};
+use super::spare_part::SparePart;
+use super::tool::{Tool, ToolKind};
+use super::SpecificItem;
+use crate::buildings::{BuildingItem, BuildingKind};
+use crate::fluids::fluid::{Fluid, Slurry, Water};
+use crate::iams::Inventory;
+
+/// Adds the item `make` builds from a free id, unless there is none of it.
+fn add<T: SpecificItem>(inventory: &mut Inventory, amount: f32, make: impl FnOnce(usize) -> T) {
+    if amount <= 0.0 {
+        return;
+    }
+    let id = inventory.next_id::<T>();
+    inventory.stack(make(id));
+}
+
+/// Adds `amount` of the item called `type_name` to `inventory`. Discrete
+/// items are rounded down to whole ones, and made ores come out pure.
+/// Returns false if no item is called `type_name`.
+pub fn add_named(inventory: &mut Inventory, type_name: &str, amount: f32) -> bool {
+    let count = amount.max(0.0).floor() as usize;
+    let whole = count as f32;
+
+    match type_name {
+        "Copper Ore" => add(inventory, amount, |id| {
+            Ore::<CopperOre>::new(amount, 1.0, id)
+        }),
+        "Iron Ore" => add(inventory, amount, |id| Ore::<IronOre>::new(amount, 1.0, id)),
+        "Copper Ingot" => add(inventory, amount, |id| {
+            Ingot::new(Metal::Copper, amount, id)
+        }),
+        "Iron Ingot" => add(inventory, amount, |id| Ingot::new(Metal::Iron, amount, id)),
+        "Spare Part" => add(inventory, whole, |id| SparePart::new(count, id)),
+        "Ration" => add(inventory, whole, |id| Food::new(count, id)),
+        "Water" => add(inventory, amount, |id| Fluid::<Water>::new(amount, id)),
+        "Slurry" => add(inventory, amount, |id| Fluid::<Slurry>::new(amount, id)),
+        _ => {
+            let tool = ToolKind::ALL
+                .into_iter()
+                .find(|kind| kind.name() == type_name);
+            let building = BuildingKind::ALL
+                .into_iter()
+                .find(|kind| kind.name() == type_name);
+            match (tool, building) {
+                (Some(kind), _) => add(inventory, whole, |id| Tool {
+                    count,
+                    ..Tool::new(kind, id)
+                }),
+                (_, Some(kind)) => add(inventory, whole, |id| BuildingItem::new(kind, count, id)),
+                _ => return false,
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_named() {
+        let mut inventory = Inventory::default();
+        assert!(add_named(&mut inventory, "Copper Ingot", 1.5));
+        assert!(add_named(&mut inventory, "Copper Ingot", 1.0));
+        assert!(add_named(&mut inventory, "Spare Part", 2.7));
+        assert!(add_named(&mut inventory, "Drill", 1.0));
+        assert!(add_named(&mut inventory, "Furnace", 1.0));
+        assert!(add_named(&mut inventory, "Water", 0.5));
+        assert!(add_named(&mut inventory, "Water", 0.25));
+
+        assert_eq!(inventory.query::<Ingot>().unwrap().len(), 1);
+        assert_eq!(inventory.amount_of("Copper Ingot"), 2.5);
+        assert_eq!(inventory.amount_of("Spare Part"), 2.0);
+        assert_eq!(inventory.amount_of("Drill"), 1.0);
+        assert_eq!(inventory.amount_of("Furnace"), 1.0);
+        assert_eq!(inventory.amount_of("Water"), 0.75);
+    }
+
+    #[test]
+    fn test_made_ore_is_pure() {
+        let mut inventory = Inventory::default();
+        add_named(&mut inventory, "Iron Ore", 0.9);
+        assert_eq!(inventory.get_all()[0].purity(), Some(1.0));
+    }
+
+    #[test]
+    fn test_nothing_to_add() {
+        let mut inventory = Inventory::default();
+        assert!(add_named(&mut inventory, "Spare Part", 0.5));
+        assert!(add_named(&mut inventory, "Iron Ore", 0.0));
+        assert!(inventory.is_empty());
+
+        assert!(!add_named(&mut inventory, "Unobtainium", 1.0));
+    }
+}
@@ -0,0 +1,80 @@
+use bevy::asset::Handle;
+use bevy::ecs::bundle::Bundle;
+use bevy::ecs::component::Component;
+use bevy::render::color::Color;
+use bevy::scene::Scene;
+use bevy::transform::components::Transform;
+use bevy_xpbd_3d::prelude::Collider;
+
+use super::{Item, ItemWeight, SpecificItem};
+use crate::anyify;
+use crate::physics::{PhysicsBundle, SurfaceMaterial};
+
+#[derive(Bundle)]
+pub struct SparePartBundle {
+    pub spare_part: SparePart,
+    pub physics: PhysicsBundle,
+    pub model: Handle<Scene>,
+    pub transform: Transform,
+}
+
+impl SparePartBundle {
+    pub fn new(spare_part: SparePart, model: Handle<Scene>, transform: Transform) -> Self {
+        Self {
+            spare_part,
+            physics: PhysicsBundle::item(Collider::cuboid(0.2, 0.2, 0.2), SurfaceMaterial::Metal),
+            model,
+            transform,
+        }
+    }
+}
+
+/// Consumed when repairing a failed machine.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Component)]
+pub struct SparePart {
+    pub count: usize,
+    pub id: usize,
+}
+
+impl SparePart {
+    pub fn new(count: usize, id: usize) -> Self {
+        SparePart { count, id }
+    }
+}
+
+#[rustfmt::skip]
+impl Item for SparePart {
+    fn type_name(&self) -> &'static str { "Spare Part" }
+    fn type_description(&self) -> &'static str { "Gears, seals and bearings for fixing machines." }
+    fn amount(&self) -> ItemWeight { ItemWeight::Discrete(self.count) }
+    fn id(&self) -> usize { self.id }
+    fn mass(&self) -> f32 { self.count as f32 * 2.0 }
+    fn icon_color(&self) -> Color { Color::rgb(0.6, 0.65, 0.7) }
+    fn tags(&self) -> &'static [&'static str] { &["Part"] }
+    fn value(&self) -> f32 { self.count as f32 * 10.0 }
+}
+
+impl SpecificItem for SparePart {
+    type B = SparePartBundle;
+    type M = usize;
+
+    fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
+    fn split(&mut self, count: usize) -> Option<Self> {
+        if count > self.count {
+            return None;
+        }
+
+        self.count -= count;
+        Some(SparePart::new(count, self.id))
+    }
+
+    fn stack(&mut self, other: Self) -> Option<Self> {
+        self.count += other.count;
+        None
+    }
+}
+
+anyify!(SparePart);
@@ -0,0 +1,385 @@
+use bevy::asset::Handle;
+use bevy::ecs::bundle::Bundle;
+use bevy::ecs::component::Component;
+use bevy::render::color::Color;
+use bevy::scene::Scene;
+use bevy::transform::components::Transform;
+use bevy_xpbd_3d::prelude::Collider;
+
+use super::{Item, ItemAction, ItemWeight, SpecificItem};
+use crate::anyify;
+use crate::iams::inventory::ItemKey;
+use crate::iams::Inventory;
+use crate::mining::OreKind;
+use crate::physics::{PhysicsBundle, SurfaceMaterial};
+use crate::recipes::Recipe;
+
+#[derive(Bundle)]
+pub struct ToolBundle {
+    pub tool: Tool,
+    pub physics: PhysicsBundle,
+    pub model: Handle<Scene>,
+    pub transform: Transform,
+}
+
+impl ToolBundle {
+    pub fn new(tool: Tool, model: Handle<Scene>, transform: Transform) -> Self {
+        Self {
+            tool,
+            physics: PhysicsBundle::item(Collider::cuboid(0.6, 0.1, 0.2), SurfaceMaterial::Metal),
+            model,
+            transform,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd)]
+pub enum ToolKind {
+    Pickaxe,
+    Drill,
+    Wrench,
+}
+
+impl ToolKind {
+    pub const ALL: [ToolKind; 3] = [ToolKind::Pickaxe, ToolKind::Drill, ToolKind::Wrench];
+
+    #[rustfmt::skip]
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToolKind::Pickaxe => "Pickaxe",
+            ToolKind::Drill   => "Drill",
+            ToolKind::Wrench  => "Wrench",
+        }
+    }
+
+    /// Hardest ore the tool can mine. See [OreKind::tier]. Tools that do
+    /// not mine are tier 0.
+    #[rustfmt::skip]
+    pub fn tier(&self) -> u8 {
+        match self {
+            ToolKind::Pickaxe => 1,
+            ToolKind::Drill   => 2,
+            ToolKind::Wrench  => 0,
+        }
+    }
+
+    /// How many times faster than [crate::mining::SWING_TIME] the tool
+    /// swings.
+    #[rustfmt::skip]
+    pub fn mining_speed(&self) -> f32 {
+        match self {
+            ToolKind::Pickaxe => 1.0,
+            ToolKind::Drill   => 2.5,
+            ToolKind::Wrench  => 0.0,
+        }
+    }
+
+    /// Uses a new tool lasts.
+    #[rustfmt::skip]
+    pub fn max_durability(&self) -> u32 {
+        match self {
+            ToolKind::Pickaxe => 120,
+            ToolKind::Drill   => 400,
+            ToolKind::Wrench  => 60,
+        }
+    }
+
+    pub fn can_mine(&self, ore: OreKind) -> bool {
+        self.tier() > 0 && self.tier() >= ore.tier()
+    }
+}
+
+/// Something held in hand to work with, worn down a little each use. Tools
+/// only stack while they are equally worn.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Component)]
+pub struct Tool {
+    pub kind: ToolKind,
+    pub count: usize,
+    /// Uses left before the tool breaks.
+    pub durability: u32,
+    pub id: usize,
+}
+
+impl Tool {
+    pub fn new(kind: ToolKind, id: usize) -> Self {
+        Tool {
+            kind,
+            count: 1,
+            durability: kind.max_durability(),
+            id,
+        }
+    }
+
+    /// A broken tool does nothing until it is repaired.
+    pub fn is_broken(&self) -> bool {
+        self.durability == 0
+    }
+
+    pub fn wear(&mut self) {
+        self.durability = self.durability.saturating_sub(1);
+    }
+}
+
+#[rustfmt::skip]
+impl Item for Tool {
+    fn type_name(&self) -> &'static str { self.kind.name() }
+    fn type_description(&self) -> &'static str {
+        match self.kind {
+            ToolKind::Pickaxe => "Breaks copper ore off deposits.",
+            ToolKind::Drill   => "Bores through any ore, and quickly.",
+            ToolKind::Wrench  => "Gets failed machines running again.",
+        }
+    }
+    fn amount(&self) -> ItemWeight { ItemWeight::Discrete(self.count) }
+    fn id(&self) -> usize { self.id }
+    fn mass(&self) -> f32 { self.count as f32 * 3.0 }
+    fn icon_color(&self) -> Color { Color::rgb(0.4, 0.4, 0.45) }
+    fn tags(&self) -> &'static [&'static str] { &["Tool"] }
+    fn value(&self) -> f32 { self.count as f32 * 20.0 * self.condition().unwrap_or(1.0) }
+    fn condition(&self) -> Option<f32> {
+        Some(self.durability as f32 / self.kind.max_durability() as f32)
+    }
+    fn durability(&self) -> Option<u32> { Some(self.durability) }
+    fn set_durability(&mut self, durability: u32) { self.durability = durability; }
+    fn action(&self) -> Option<ItemAction> {
+        match (self.kind, self.is_broken()) {
+            (_, true)             => None,
+            (ToolKind::Wrench, _) => Some(ItemAction::Repair),
+            _                     => Some(ItemAction::Mine),
+        }
+    }
+}
+
+impl SpecificItem for Tool {
+    type B = ToolBundle;
+    type M = usize;
+
+    fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
+    fn split(&mut self, count: usize) -> Option<Self> {
+        if count > self.count {
+            return None;
+        }
+
+        self.count -= count;
+        Some(Tool { count, ..*self })
+    }
+
+    fn stack(&mut self, other: Self) -> Option<Self> {
+        if other.kind != self.kind || other.durability != self.durability {
+            return Some(other);
+        }
+
+        self.count += other.count;
+        None
+    }
+}
+
+anyify!(Tool);
+
+#[derive(Debug, PartialEq)]
+pub enum ToolRepairError {
+    NoSuchTool,
+    /// The recipe is for repairing some other tool.
+    WrongRecipe,
+    NotWorn,
+    MissingInput {
+        item: String,
+        needed: f32,
+        available: f32,
+    },
+}
+
+/// The tool picked out by `key`, taken off its stack under a new id first so
+/// only that one tool is changed.
+fn take_off_stack(inventory: &mut Inventory, key: ItemKey) -> Option<&mut Tool> {
+    let id = inventory.next_id::<Tool>();
+    let tools = inventory.query_mut::<Tool>()?;
+    let index = tools.iter().position(|tool| ItemKey::of(tool) == key)?;
+
+    let others = tools[index].count.checked_sub(1)?;
+    if let Some(mut rest) = tools[index].split(others).filter(|rest| rest.count > 0) {
+        rest.id = id;
+        tools.push(rest);
+    }
+    tools.get_mut(index)
+}
+
+/// Wears the tool picked out by `key` down by one use. Returns whether it
+/// was there to wear.
+pub fn wear_tool(inventory: &mut Inventory, key: ItemKey) -> bool {
+    match take_off_stack(inventory, key) {
+        Some(tool) => {
+            tool.wear();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Brings the tool picked out by `key` back to full durability, paying with
+/// the inputs of `recipe` from `inventory`.
+pub fn repair_tool(
+    inventory: &mut Inventory,
+    key: ItemKey,
+    recipe: &Recipe,
+) -> Result<(), ToolRepairError> {
+    let tool = inventory
+        .get(key)
+        .and_then(|tool| tool.as_any().downcast_ref::<Tool>())
+        .ok_or(ToolRepairError::NoSuchTool)?;
+    if recipe.repairs.as_deref() != Some(tool.type_name()) {
+        return Err(ToolRepairError::WrongRecipe);
+    }
+    if tool.durability == tool.kind.max_durability() {
+        return Err(ToolRepairError::NotWorn);
+    }
+
+    for input in recipe.inputs.iter() {
+        let available = inventory.amount_of(&input.item);
+        if available < input.amount {
+            return Err(ToolRepairError::MissingInput {
+                item: input.item.clone(),
+                needed: input.amount,
+                available,
+            });
+        }
+    }
+    for input in recipe.inputs.iter() {
+        inventory.take_by_name(&input.item, input.amount);
+    }
+
+    if let Some(tool) = take_off_stack(inventory, key) {
+        tool.durability = tool.kind.max_durability();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::spare_part::SparePart;
+    use crate::recipes::HAND;
+    use crate::research::ItemCost;
+
+    fn repair_recipe(tool: &str, parts: f32) -> Recipe {
+        Recipe {
+            id: "repair".to_string(),
+            name: "Repair".to_string(),
+            machine: HAND.to_string(),
+            time: 1.0,
+            inputs: vec![ItemCost {
+                item: "Spare Part".to_string(),
+                amount: parts,
+            }],
+            outputs: vec![ItemCost {
+                item: tool.to_string(),
+                amount: 1.0,
+            }],
+            repairs: Some(tool.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_tiers_gate_ore() {
+        assert!(ToolKind::Pickaxe.can_mine(OreKind::Copper));
+        assert!(!ToolKind::Pickaxe.can_mine(OreKind::Iron));
+        assert!(ToolKind::Drill.can_mine(OreKind::Iron));
+        assert!(!ToolKind::Wrench.can_mine(OreKind::Copper));
+    }
+
+    #[test]
+    fn test_only_equally_worn_tools_stack() {
+        let mut inventory = Inventory::default();
+        inventory.stack(Tool::new(ToolKind::Pickaxe, 0));
+        inventory.stack(Tool::new(ToolKind::Pickaxe, 1));
+        assert_eq!(inventory.query::<Tool>().unwrap().len(), 1);
+
+        let mut worn = Tool::new(ToolKind::Pickaxe, 2);
+        worn.wear();
+        inventory.stack(worn);
+        inventory.stack(Tool::new(ToolKind::Drill, 3));
+        assert_eq!(inventory.query::<Tool>().unwrap().len(), 3);
+        assert_eq!(inventory.amount_of("Pickaxe"), 3.0);
+    }
+
+    #[test]
+    fn test_wear_takes_tool_off_stack() {
+        let mut inventory = Inventory::default();
+        inventory.add(Tool {
+            count: 3,
+            ..Tool::new(ToolKind::Pickaxe, 0)
+        });
+        let key = ItemKey::of(&Tool::new(ToolKind::Pickaxe, 0));
+
+        assert!(wear_tool(&mut inventory, key));
+        let tools = inventory.query::<Tool>().unwrap();
+        assert_eq!((tools[0].count, tools[0].durability), (1, 119));
+        assert_eq!(
+            (tools[1].count, tools[1].durability, tools[1].id),
+            (2, 120, 1)
+        );
+    }
+
+    #[test]
+    fn test_wear_empty_stack() {
+        let mut inventory = Inventory::default();
+        inventory.add(Tool {
+            count: 0,
+            ..Tool::new(ToolKind::Pickaxe, 0)
+        });
+        let key = ItemKey::of(&Tool::new(ToolKind::Pickaxe, 0));
+
+        assert!(!wear_tool(&mut inventory, key));
+    }
+
+    #[test]
+    fn test_broken_tools_do_nothing() {
+        let mut tool = Tool::new(ToolKind::Wrench, 0);
+        assert_eq!(tool.action(), Some(ItemAction::Repair));
+
+        tool.durability = 1;
+        tool.wear();
+        tool.wear();
+        assert!(tool.is_broken());
+        assert_eq!(tool.action(), None);
+        assert_eq!(tool.condition(), Some(0.0));
+    }
+
+    #[test]
+    fn test_repair_tool() {
+        let mut inventory = Inventory::default();
+        inventory.add(Tool {
+            durability: 0,
+            ..Tool::new(ToolKind::Pickaxe, 0)
+        });
+        inventory.add(SparePart::new(1, 0));
+        let key = ItemKey::of(&Tool::new(ToolKind::Pickaxe, 0));
+
+        assert_eq!(
+            repair_tool(&mut inventory, key, &repair_recipe("Drill", 1.0)),
+            Err(ToolRepairError::WrongRecipe)
+        );
+        assert_eq!(
+            repair_tool(&mut inventory, key, &repair_recipe("Pickaxe", 2.0)),
+            Err(ToolRepairError::MissingInput {
+                item: "Spare Part".to_string(),
+                needed: 2.0,
+                available: 1.0,
+            })
+        );
+
+        assert_eq!(
+            repair_tool(&mut inventory, key, &repair_recipe("Pickaxe", 1.0)),
+            Ok(())
+        );
+        assert_eq!(inventory.query::<Tool>().unwrap()[0].durability, 120);
+        assert_eq!(inventory.amount_of("Spare Part"), 0.0);
+        assert_eq!(
+            repair_tool(&mut inventory, key, &repair_recipe("Pickaxe", 1.0)),
+            Err(ToolRepairError::NotWorn)
+        );
+    }
+}
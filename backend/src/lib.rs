@@ -1,7 +1,16 @@
-#![feature(downcast_unchecked)]
-#![feature(test)]
-
 pub mod as_any;
+pub mod buildings;
+pub mod fluids;
+pub mod history;
 pub mod iams;
+pub mod input;
 pub mod items;
+pub mod machines;
+pub mod mining;
+pub mod physics;
 pub mod player;
+pub mod recipes;
+pub mod research;
+pub mod rng;
+pub mod save;
+pub mod settings;
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+use crate::rng::SeededRng;
+
+/// What happens to a machine when it breaks down.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum FailureEffect {
+    Stopped,
+    /// The machine keeps running at this fraction of its normal output.
+    ReducedOutput(f32),
+    /// Items fall out of the machine into the world.
+    Spilling,
+    Fire,
+}
+
+/// How long a type of machine lasts and what it takes to fix.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reliability {
+    /// Mean seconds between failures when running at full load on pure
+    /// inputs.
+    pub mtbf: f32,
+    /// Spare parts consumed by a repair.
+    pub repair_cost: usize,
+}
+
+impl Default for Reliability {
+    fn default() -> Self {
+        Self {
+            mtbf: 3600.0,
+            repair_cost: 1,
+        }
+    }
+}
+
+/// Wear gained per second of uptime. A machine at full load on pure inputs
+/// wears at 1.0, one running at no load still wears a quarter as fast and
+/// impure inputs wear up to twice as fast. Idle machines have no uptime, so
+/// they do not wear at all.
+pub fn wear_rate(load: f32, input_purity: f32) -> f32 {
+    (0.25 + 0.75 * load.clamp(0.0, 1.0)) * (2.0 - input_purity.clamp(0.0, 1.0))
+}
+
+/// Chance of failing while wear goes from `wear` to `wear + wear_gained`.
+///
+/// Failures follow a Weibull distribution with a shape of 2, so the odds of
+/// breaking down rise the longer a machine runs without being repaired.
+pub fn failure_chance(reliability: &Reliability, wear: f32, wear_gained: f32) -> f32 {
+    let scale = reliability.mtbf * std::f32::consts::FRAC_2_SQRT_PI;
+    let cumulative_hazard = |wear: f32| (wear / scale).powi(2);
+
+    1.0 - (cumulative_hazard(wear) - cumulative_hazard(wear + wear_gained)).exp()
+}
+
+/// Rolls against `chance` and picks what breaks when the roll fails.
+pub fn roll_failure(rng: &mut SeededRng, chance: f32) -> Option<FailureEffect> {
+    if rng.next_f32() >= chance {
+        return None;
+    }
+
+    let effect = match rng.next_f32() {
+        roll if roll < 0.4 => FailureEffect::Stopped,
+        roll if roll < 0.7 => FailureEffect::ReducedOutput(0.5),
+        roll if roll < 0.9 => FailureEffect::Spilling,
+        _ => FailureEffect::Fire,
+    };
+
+    Some(effect)
+}
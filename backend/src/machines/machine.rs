@@ -0,0 +1,246 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::failure::{failure_chance, roll_failure, wear_rate, FailureEffect, Reliability};
+use crate::iams::Inventory;
+use crate::items::spare_part::SparePart;
+use crate::items::SpecificItem;
+use crate::rng::SeededRng;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MachineState {
+    Idle,
+    Running,
+    Failed(FailureEffect),
+}
+
+impl MachineState {
+    /// The state as shown to players.
+    pub fn label(&self) -> String {
+        match self {
+            MachineState::Idle => "Idle".to_string(),
+            MachineState::Running => "Running".to_string(),
+            MachineState::Failed(FailureEffect::Stopped) => "Broken down".to_string(),
+            MachineState::Failed(FailureEffect::ReducedOutput(multiplier)) => {
+                format!("Faltering, at {:.0}% output", multiplier * 100.0)
+            }
+            MachineState::Failed(FailureEffect::Spilling) => "Spilling items".to_string(),
+            MachineState::Failed(FailureEffect::Fire) => "On fire".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairError {
+    NotBroken,
+    MissingSpareParts { needed: usize, available: usize },
+}
+
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Machine {
+    pub state: MachineState,
+    pub reliability: Reliability,
+    /// Fraction of rated capacity currently in use, from 0.0 to 1.0.
+    pub load: f32,
+    /// Purity of the inputs being processed. Dirty inputs wear machines faster.
+    pub input_purity: f32,
+    /// Accumulated wear since the last repair. See [wear_rate].
+    pub wear: f32,
+    /// Seconds spent running since the last repair.
+    pub uptime: f32,
+    /// Multiplier on the chance of failing, set by how well the machine was
+    /// last repaired.
+    pub failure_odds: f32,
+}
+
+impl Machine {
+    pub fn new(reliability: Reliability) -> Self {
+        Machine {
+            state: MachineState::Idle,
+            reliability,
+            load: 0.0,
+            input_purity: 1.0,
+            wear: 0.0,
+            uptime: 0.0,
+            failure_odds: 1.0,
+        }
+    }
+
+    /// Multiplier to apply to whatever the machine produces.
+    pub fn output_multiplier(&self) -> f32 {
+        match self.state {
+            MachineState::Running => 1.0,
+            MachineState::Failed(FailureEffect::ReducedOutput(multiplier)) => multiplier,
+            _ => 0.0,
+        }
+    }
+
+    /// Wear as a fraction of the mean wear between failures, from 0.0 to 1.0.
+    pub fn wear_fraction(&self) -> f32 {
+        (self.wear / self.reliability.mtbf).clamp(0.0, 1.0)
+    }
+
+    /// Adds wear for `delta_seconds` of operation and rolls for a failure.
+    /// Returns the failure if the machine broke down during this tick.
+    pub fn tick(&mut self, delta_seconds: f32, rng: &mut SeededRng) -> Option<FailureEffect> {
+        let running = matches!(
+            self.state,
+            MachineState::Running | MachineState::Failed(FailureEffect::ReducedOutput(_))
+        );
+        if !running {
+            return None;
+        }
+
+        let wear_gained = delta_seconds * wear_rate(self.load, self.input_purity);
+        let chance = failure_chance(&self.reliability, self.wear, wear_gained) * self.failure_odds;
+        self.wear += wear_gained;
+        self.uptime += delta_seconds;
+
+        // A machine that is already limping along can still break down fully,
+        // but not into another partial failure.
+        let effect = match roll_failure(rng, chance)? {
+            FailureEffect::ReducedOutput(_) if self.state != MachineState::Running => {
+                FailureEffect::Stopped
+            }
+            effect => effect,
+        };
+
+        self.state = MachineState::Failed(effect);
+        Some(effect)
+    }
+
+    /// Fixes the machine, taking [Reliability::repair_cost] [SparePart]s out
+    /// of `inventory`.
+    pub fn repair(&mut self, inventory: &mut Inventory) -> Result<(), RepairError> {
+        if !matches!(self.state, MachineState::Failed(_)) {
+            return Err(RepairError::NotBroken);
+        }
+
+        let needed = self.reliability.repair_cost;
+        let parts = inventory.query_mut::<SparePart>();
+        let available = parts
+            .as_ref()
+            .map_or(0, |parts| parts.iter().map(|part| part.count).sum());
+        if available < needed {
+            return Err(RepairError::MissingSpareParts { needed, available });
+        }
+
+        if let Some(parts) = parts {
+            let mut remaining = needed;
+            for part in parts.iter_mut() {
+                let taken = remaining.min(part.count);
+                part.split(taken);
+                remaining -= taken;
+            }
+            parts.retain(|part| part.count > 0);
+        }
+
+        self.state = MachineState::Idle;
+        self.wear = 0.0;
+        self.uptime = 0.0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn running_machine() -> Machine {
+        let mut machine = Machine::new(Reliability {
+            mtbf: 60.0,
+            repair_cost: 3,
+        });
+        machine.state = MachineState::Running;
+        machine.load = 1.0;
+        machine
+    }
+
+    fn seconds_until_failure(seed: u64) -> usize {
+        let mut machine = running_machine();
+        let mut rng = SeededRng::new(seed);
+        (1..)
+            .find(|_| machine.tick(1.0, &mut rng).is_some())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_failures_are_reproducible() {
+        assert_eq!(seconds_until_failure(42), seconds_until_failure(42));
+    }
+
+    #[test]
+    fn test_impure_inputs_wear_faster() {
+        let mut rng = SeededRng::new(0);
+        let mut clean = running_machine();
+        let mut dirty = running_machine();
+        dirty.input_purity = 0.2;
+
+        clean.tick(0.001, &mut rng);
+        dirty.tick(0.001, &mut rng);
+
+        assert!(dirty.wear > clean.wear);
+    }
+
+    #[test]
+    fn test_idle_machines_do_not_wear() {
+        let mut machine = running_machine();
+        machine.state = MachineState::Idle;
+
+        assert!(machine.tick(1000.0, &mut SeededRng::new(0)).is_none());
+        assert_eq!(machine.wear, 0.0);
+    }
+
+    #[test]
+    fn test_well_repaired_machines_fail_less() {
+        let mut machine = running_machine();
+        machine.failure_odds = 0.0;
+
+        let mut rng = SeededRng::new(42);
+        assert!((0..1000).all(|_| machine.tick(1.0, &mut rng).is_none()));
+    }
+
+    #[test]
+    fn test_repair_consumes_spare_parts() {
+        let mut inventory = Inventory::default();
+        inventory.add(SparePart::new(2, 0));
+        inventory.add(SparePart::new(2, 1));
+        let mut machine = running_machine();
+        machine.state = MachineState::Failed(FailureEffect::Fire);
+        machine.wear = 100.0;
+
+        assert_eq!(machine.repair(&mut inventory), Ok(()));
+        assert_eq!(machine.state, MachineState::Idle);
+        assert_eq!(machine.wear, 0.0);
+
+        let parts = inventory.query::<SparePart>().unwrap();
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].count, 1);
+    }
+
+    #[test]
+    fn test_repair_without_spare_parts() {
+        let mut inventory = Inventory::default();
+        inventory.add(SparePart::new(1, 0));
+        let mut machine = running_machine();
+        machine.state = MachineState::Failed(FailureEffect::Stopped);
+
+        assert_eq!(
+            machine.repair(&mut inventory),
+            Err(RepairError::MissingSpareParts {
+                needed: 3,
+                available: 1
+            })
+        );
+        assert_eq!(inventory.query::<SparePart>().unwrap()[0].count, 1);
+    }
+
+    #[test]
+    fn test_state_labels() {
+        assert_eq!(MachineState::Running.label(), "Running");
+        assert_eq!(
+            MachineState::Failed(FailureEffect::ReducedOutput(0.5)).label(),
+            "Faltering, at 50% output"
+        );
+    }
+}
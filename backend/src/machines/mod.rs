@@ -0,0 +1,330 @@
+//! Machines process items and, this being industrial-failure, break down. Wear
+//! builds up with uptime, load and input purity, and failures are rolled from
+//! a seeded [SeededRng] so they can be reproduced. Broken machines are fixed
+//! by spending [SparePart](crate::items::spare_part::SparePart)s.
+//!
+//! While set to a recipe, powered and loaded with its inputs, a machine runs
+//! through batches of it in its [MachineProcess].
+//!
+//! Spilling machines throw their contents out, which the frontend puts in
+//! the world, and burning ones lose them and hurt anyone standing close.
+
+pub mod failure;
+pub mod machine;
+pub mod panel;
+pub mod power;
+pub mod process;
+
+pub use failure::{FailureEffect, Reliability};
+pub use machine::{Machine, MachineState, RepairError};
+pub use panel::{MachinePanel, RecipeChoice};
+pub use power::Power;
+pub use process::MachineProcess;
+
+use bevy::prelude::*;
+
+use crate::buildings::Building;
+use crate::iams::Inventory;
+use crate::player::health::{DamageEvent, DamageType, Health};
+use crate::player::profile::PlayerLogic;
+use crate::player::skills::{ExperienceGained, Skill, SMELTING_EXPERIENCE};
+use crate::recipes::RecipeBook;
+use crate::rng::SeededRng;
+
+/// How close in m to a burning machine players get burnt.
+pub const FIRE_RADIUS: f32 = 3.0;
+/// Burn damage per second to anyone within [FIRE_RADIUS] of a fire.
+pub const FIRE_DAMAGE: f32 = 8.0;
+
+/// Sent when a machine breaks down, so the effect (spilled items, fire) can be
+/// shown in the world.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct MachineFailed {
+    pub machine: Entity,
+    pub effect: FailureEffect,
+}
+
+/// Sent with everything a spilling machine threw out, for the frontend to
+/// put in the world.
+#[derive(Event, Debug)]
+pub struct MachineSpilled {
+    pub machine: Entity,
+    pub items: Inventory,
+}
+
+#[derive(Resource, Debug)]
+pub struct FailureRng(pub SeededRng);
+
+#[derive(Default)]
+pub struct MachinePlugin {
+    pub seed: u64,
+}
+
+impl Plugin for MachinePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FailureRng(SeededRng::new(self.seed)))
+            .add_event::<MachineFailed>()
+            .add_event::<MachineSpilled>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    run_machines,
+                    wear_machines.after(run_machines),
+                    apply_failure_effects.after(wear_machines),
+                    burn_near_fires,
+                ),
+            );
+    }
+}
+
+/// Works machines through the recipe they are set to. Machines missing
+/// inputs or power stand idle, unless they have broken down. Skilled
+/// operators get more out of each batch, and learn from every one finished.
+pub fn run_machines(
+    time: Res<Time>,
+    recipes: Option<Res<RecipeBook>>,
+    mut machines: Query<(&Building, &mut Machine, &mut MachineProcess, &Power)>,
+    players: Query<&PlayerLogic>,
+    mut experience: EventWriter<ExperienceGained>,
+) {
+    for (building, mut machine, mut process, power) in machines.iter_mut() {
+        let recipe = recipes
+            .as_deref()
+            .zip(building.config.recipe.as_deref())
+            .and_then(|(recipes, id)| recipes.get(id));
+        let ready = power.is_powered() && recipe.is_some_and(|recipe| process.has_inputs(recipe));
+
+        if !matches!(machine.state, MachineState::Failed(_)) {
+            machine.state = match ready {
+                true => MachineState::Running,
+                false => MachineState::Idle,
+            };
+        }
+        machine.load = match ready {
+            true => 1.0,
+            false => 0.0,
+        };
+        if let Some(recipe) = recipe {
+            machine.input_purity = process.input_purity(recipe);
+        }
+
+        if !power.is_powered() {
+            continue;
+        }
+        let operator = process.operator.and_then(|player| players.get(player).ok());
+        let skill = operator.map_or(1.0, |player| {
+            player.skills.yield_multiplier(Skill::Refining)
+        });
+        let multiplier = machine.output_multiplier() * skill;
+        let finished = process.tick(recipe, time.delta_seconds(), multiplier);
+
+        if let (true, Some(player), Some(recipe)) = (finished, process.operator, recipe) {
+            let smelted: f32 = recipe.inputs.iter().map(|input| input.amount).sum();
+            experience.send(ExperienceGained {
+                player,
+                skill: Skill::Refining,
+                amount: SMELTING_EXPERIENCE * smelted.round() as u32,
+            });
+        }
+    }
+}
+
+pub fn wear_machines(
+    time: Res<Time>,
+    mut rng: ResMut<FailureRng>,
+    mut machines: Query<(Entity, &mut Machine)>,
+    mut failures: EventWriter<MachineFailed>,
+) {
+    for (entity, mut machine) in machines.iter_mut() {
+        if let Some(effect) = machine.tick(time.delta_seconds(), &mut rng.0) {
+            failures.send(MachineFailed {
+                machine: entity,
+                effect,
+            });
+        }
+    }
+}
+
+/// Empties spilling machines out into the world and burns up what was in
+/// machines that caught fire.
+pub fn apply_failure_effects(
+    mut failures: EventReader<MachineFailed>,
+    mut machines: Query<&mut MachineProcess>,
+    mut spills: EventWriter<MachineSpilled>,
+) {
+    for failure in failures.read() {
+        let Ok(mut process) = machines.get_mut(failure.machine) else {
+            continue;
+        };
+
+        match failure.effect {
+            FailureEffect::Spilling => {
+                let items = process.spill();
+                if !items.is_empty() {
+                    spills.send(MachineSpilled {
+                        machine: failure.machine,
+                        items,
+                    });
+                }
+            }
+            FailureEffect::Fire => {
+                process.spill();
+            }
+            FailureEffect::Stopped | FailureEffect::ReducedOutput(_) => {}
+        }
+    }
+}
+
+/// Burns everyone within [FIRE_RADIUS] of a machine on fire, until it is
+/// repaired.
+pub fn burn_near_fires(
+    time: Res<Time>,
+    machines: Query<(&Machine, &Transform)>,
+    targets: Query<(Entity, &Transform), With<Health>>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    let fires = machines
+        .iter()
+        .filter(|(machine, _)| machine.state == MachineState::Failed(FailureEffect::Fire));
+    for (_, fire) in fires {
+        for (target, transform) in targets.iter() {
+            if transform.translation.distance(fire.translation) <= FIRE_RADIUS {
+                damage.send(DamageEvent {
+                    target,
+                    amount: FIRE_DAMAGE * time.delta_seconds(),
+                    damage_type: DamageType::Burn,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::buildings::{BuildingConfig, BuildingKind, Rotation};
+    use crate::items::ore::{IronOre, Ore};
+    use crate::items::spare_part::SparePart;
+    use crate::player::profile::PlayerId;
+    use crate::recipes::recipe_book::testing::recipes;
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Events<MachineFailed>>();
+        world.init_resource::<Events<MachineSpilled>>();
+        world.init_resource::<Events<DamageEvent>>();
+        world.init_resource::<Events<ExperienceGained>>();
+        world
+    }
+
+    fn advance(world: &mut World, seconds: u64) {
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_secs(seconds));
+        world.insert_resource(time);
+    }
+
+    fn loaded_machine(world: &mut World) -> Entity {
+        let mut process = MachineProcess::default();
+        process.input.add(Ore::<IronOre>::new(2.0, 0.5, 0));
+        process.output.add(SparePart::new(3, 0));
+        world.spawn(process).id()
+    }
+
+    fn fail(world: &mut World, machine: Entity, effect: FailureEffect) {
+        world.send_event(MachineFailed { machine, effect });
+        world.run_system_once(apply_failure_effects);
+    }
+
+    #[test]
+    fn test_spilling_throws_contents_out() {
+        let mut world = world();
+        let machine = loaded_machine(&mut world);
+        fail(&mut world, machine, FailureEffect::Spilling);
+
+        let process = world.get::<MachineProcess>(machine).unwrap();
+        assert!(process.input.is_empty() && process.output.is_empty());
+
+        let spills = world.resource::<Events<MachineSpilled>>();
+        let mut reader = spills.get_reader();
+        let spill = reader.read(spills).next().unwrap();
+        assert_eq!(spill.machine, machine);
+        assert_eq!(spill.items.amount_of("Iron Ore"), 2.0);
+        assert_eq!(spill.items.amount_of("Spare Part"), 3.0);
+    }
+
+    #[test]
+    fn test_fire_burns_contents_and_people_nearby() {
+        let mut world = world();
+        let machine = loaded_machine(&mut world);
+        fail(&mut world, machine, FailureEffect::Fire);
+        let process = world.get::<MachineProcess>(machine).unwrap();
+        assert!(process.input.is_empty() && process.output.is_empty());
+        assert!(world.resource::<Events<MachineSpilled>>().is_empty());
+
+        let mut burning = Machine::new(Reliability::default());
+        burning.state = MachineState::Failed(FailureEffect::Fire);
+        world
+            .entity_mut(machine)
+            .insert((burning, Transform::default()));
+        let near = world
+            .spawn((Health::default(), Transform::from_xyz(1.0, 0.0, 0.0)))
+            .id();
+        world.spawn((Health::default(), Transform::from_xyz(10.0, 0.0, 0.0)));
+
+        advance(&mut world, 1);
+        world.run_system_once(burn_near_fires);
+
+        let damage = world.resource::<Events<DamageEvent>>();
+        let burns: Vec<_> = damage.get_reader().read(damage).copied().collect();
+        assert_eq!(
+            burns,
+            [DamageEvent {
+                target: near,
+                amount: FIRE_DAMAGE,
+                damage_type: DamageType::Burn,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_finished_batches_train_refining() {
+        let mut world = world();
+        world.insert_resource(recipes());
+        let player = world.spawn(PlayerLogic::new(PlayerId(1), "Smelter")).id();
+
+        let mut process = MachineProcess::default();
+        process.input.add(Ore::<IronOre>::new(2.0, 1.0, 0));
+        process.operator = Some(player);
+        world.spawn((
+            Building {
+                kind: BuildingKind::Furnace,
+                rotation: Rotation::North,
+                config: BuildingConfig {
+                    recipe: Some("iron_ingot".to_string()),
+                    filter: None,
+                },
+            },
+            Machine::new(Reliability::default()),
+            process,
+            Power::new(15.0),
+        ));
+
+        advance(&mut world, 5);
+        world.run_system_once(run_machines);
+
+        let gained = world.resource::<Events<ExperienceGained>>();
+        let gained: Vec<_> = gained.get_reader().read(gained).copied().collect();
+        assert_eq!(
+            gained,
+            [ExperienceGained {
+                player,
+                skill: Skill::Refining,
+                amount: 2 * SMELTING_EXPERIENCE,
+            }]
+        );
+    }
+}
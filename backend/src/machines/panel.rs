@@ -0,0 +1,181 @@
+//! What the panel of a machine shows, worked out from its components and the
+//! recipe book so every kind of machine gets the same panel. Progress and
+//! wear change every tick, so they are read straight off the machine instead.
+
+use super::{Machine, MachineProcess, Power};
+use crate::buildings::Building;
+use crate::iams::view::ItemView;
+use crate::iams::Inventory;
+use crate::player::profile::PlayerLogic;
+use crate::recipes::{Recipe, RecipeBook};
+
+/// A recipe the machine can be set to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecipeChoice {
+    pub id: String,
+    pub name: String,
+    pub selected: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MachinePanel {
+    pub title: &'static str,
+    pub state: String,
+    pub power: String,
+    pub recipes: Vec<RecipeChoice>,
+    /// What a batch of the selected recipe takes, against what is loaded.
+    pub needs: Vec<String>,
+    /// What a batch of the selected recipe makes.
+    pub makes: Vec<String>,
+    pub input: Vec<ItemView>,
+    pub output: Vec<ItemView>,
+}
+
+impl MachinePanel {
+    /// Only offers the recipes `profile` has unlocked.
+    pub fn new(
+        building: &Building,
+        machine: &Machine,
+        process: &MachineProcess,
+        power: &Power,
+        recipes: &RecipeBook,
+        profile: &PlayerLogic,
+    ) -> Self {
+        let selected = building.config.recipe.as_deref();
+        let recipe = selected.and_then(|id| recipes.get(id));
+        Self {
+            title: building.kind.name(),
+            state: machine.state.label(),
+            power: power.label(),
+            recipes: recipes
+                .for_machine(building.kind.id())
+                .filter(|recipe| profile.has_recipe(&recipe.id))
+                .map(|recipe| RecipeChoice {
+                    id: recipe.id.clone(),
+                    name: recipe.name.clone(),
+                    selected: Some(recipe.id.as_str()) == selected,
+                })
+                .collect(),
+            needs: recipe.map_or(Vec::new(), |recipe| needs(recipe, &process.input)),
+            makes: recipe.map_or(Vec::new(), makes),
+            input: items(&process.input),
+            output: items(&process.output),
+        }
+    }
+}
+
+fn needs(recipe: &Recipe, input: &Inventory) -> Vec<String> {
+    recipe
+        .inputs
+        .iter()
+        .map(|cost| {
+            let have = input.amount_of(&cost.item);
+            format!("{}: {have:.1} / {:.1}", cost.item, cost.amount)
+        })
+        .collect()
+}
+
+fn makes(recipe: &Recipe) -> Vec<String> {
+    recipe
+        .outputs
+        .iter()
+        .map(|output| format!("{}: {:.1}", output.item, output.amount))
+        .collect()
+}
+
+fn items(inventory: &Inventory) -> Vec<ItemView> {
+    inventory.get_all().into_iter().map(ItemView::new).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buildings::{BuildingConfig, BuildingKind, Rotation};
+    use crate::items::ore::{IronOre, Ore};
+    use crate::machines::Reliability;
+    use crate::player::profile::PlayerId;
+    use crate::recipes::recipe_book::testing::recipes;
+
+    fn furnace(recipe: Option<&str>) -> Building {
+        Building {
+            kind: BuildingKind::Furnace,
+            rotation: Rotation::North,
+            config: BuildingConfig {
+                recipe: recipe.map(str::to_string),
+                filter: None,
+            },
+        }
+    }
+
+    /// A player who has researched smelting.
+    fn smelter() -> PlayerLogic {
+        let mut profile = PlayerLogic::new(PlayerId(1), "Smelter");
+        profile.unlock_recipe("copper_ingot");
+        profile.unlock_recipe("iron_ingot");
+        profile
+    }
+
+    fn panel(building: &Building, process: &MachineProcess) -> MachinePanel {
+        MachinePanel::new(
+            building,
+            &Machine::new(Reliability::default()),
+            process,
+            &Power::new(building.kind.power_draw()),
+            &recipes(),
+            &smelter(),
+        )
+    }
+
+    #[test]
+    fn test_recipe_choices() {
+        let panel = panel(&furnace(Some("iron_ingot")), &MachineProcess::default());
+
+        assert_eq!(panel.title, "Furnace");
+        assert_eq!(panel.state, "Idle");
+        assert_eq!(panel.power, "Powered, 15 kW");
+        let choices: Vec<_> = panel
+            .recipes
+            .iter()
+            .map(|choice| (choice.name.as_str(), choice.selected))
+            .collect();
+        assert_eq!(choices, [("Copper Ingot", false), ("Iron Ingot", true)]);
+    }
+
+    #[test]
+    fn test_locked_recipes_are_hidden() {
+        let mut profile = PlayerLogic::new(PlayerId(1), "Novice");
+        profile.unlock_recipe("iron_ingot");
+        let panel = MachinePanel::new(
+            &furnace(None),
+            &Machine::new(Reliability::default()),
+            &MachineProcess::default(),
+            &Power::new(BuildingKind::Furnace.power_draw()),
+            &recipes(),
+            &profile,
+        );
+
+        let names: Vec<_> = panel.recipes.iter().map(|choice| &choice.name).collect();
+        assert_eq!(names, ["Iron Ingot"]);
+    }
+
+    #[test]
+    fn test_needs_against_loaded_inputs() {
+        let mut process = MachineProcess::default();
+        process.input.add(Ore::<IronOre>::new(1.5, 1.0, 0));
+        let panel = panel(&furnace(Some("iron_ingot")), &process);
+
+        assert_eq!(panel.needs, ["Iron Ore: 1.5 / 2.0"]);
+        assert_eq!(panel.makes, ["Iron Ingot: 1.0"]);
+        assert_eq!(panel.input.len(), 1);
+        assert!(panel.output.is_empty());
+    }
+
+    #[test]
+    fn test_no_recipe_selected() {
+        let panel = panel(&furnace(None), &MachineProcess::default());
+
+        assert!(panel.recipes.iter().all(|choice| !choice.selected));
+        assert!(panel.needs.is_empty());
+        assert!(panel.makes.is_empty());
+    }
+}
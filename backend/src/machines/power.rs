@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+
+/// Electricity a machine needs to run. Machines without enough of it stand
+/// still, keeping how far they got.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Power {
+    /// kW drawn while running.
+    pub draw: f32,
+    /// Fraction of the draw being supplied, from 0.0 to 1.0.
+    pub supplied: f32,
+}
+
+impl Power {
+    /// Fully supplied, as there is nothing generating power yet.
+    pub fn new(draw: f32) -> Self {
+        Power {
+            draw,
+            supplied: 1.0,
+        }
+    }
+
+    pub fn is_powered(&self) -> bool {
+        self.draw <= 0.0 || self.supplied >= 1.0
+    }
+
+    /// The power status as shown to players.
+    pub fn label(&self) -> String {
+        if self.draw <= 0.0 {
+            return "No power needed".to_string();
+        }
+        match self.is_powered() {
+            true => format!("Powered, {:.0} kW", self.draw),
+            false => format!(
+                "Underpowered, {:.0} of {:.0} kW",
+                self.draw * self.supplied,
+                self.draw
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_power_labels() {
+        assert_eq!(Power::new(0.0).label(), "No power needed");
+        assert_eq!(Power::new(15.0).label(), "Powered, 15 kW");
+
+        let power = Power {
+            draw: 15.0,
+            supplied: 0.4,
+        };
+        assert!(!power.is_powered());
+        assert_eq!(power.label(), "Underpowered, 6 of 15 kW");
+    }
+}
@@ -0,0 +1,206 @@
+use bevy::prelude::*;
+
+use crate::iams::inventory::ItemKey;
+use crate::iams::Inventory;
+use crate::items::registry::add_named;
+use crate::recipes::Recipe;
+
+/// What a machine is working on: the items loaded into it, what it has made
+/// so far and how far through the current batch it is.
+#[derive(Component, Debug, Default)]
+pub struct MachineProcess {
+    pub input: Inventory,
+    pub output: Inventory,
+    /// Id of the recipe [MachineProcess::progress] is towards.
+    pub recipe: Option<String>,
+    /// How far through the current batch, from 0.0 to 1.0.
+    pub progress: f32,
+    /// The player who last loaded the machine, who earns the
+    /// [Skill::Refining](crate::player::skills::Skill::Refining) experience
+    /// for its batches.
+    pub operator: Option<Entity>,
+}
+
+impl MachineProcess {
+    /// Whether the input holds enough for a batch of `recipe`.
+    pub fn has_inputs(&self, recipe: &Recipe) -> bool {
+        recipe
+            .inputs
+            .iter()
+            .all(|input| self.input.amount_of(&input.item) >= input.amount)
+    }
+
+    /// Average purity of the loaded items `recipe` takes, weighted by amount.
+    /// Items without a purity count as pure.
+    pub fn input_purity(&self, recipe: &Recipe) -> f32 {
+        let (amount, pure) = self
+            .input
+            .get_all()
+            .into_iter()
+            .filter(|item| recipe.uses(item.type_name()))
+            .fold((0.0, 0.0), |(amount, pure), item| {
+                let item_amount = item.amount().as_f32();
+                let purity = item.purity().unwrap_or(1.0);
+                (amount + item_amount, pure + item_amount * purity)
+            });
+
+        match amount > 0.0 {
+            true => pure / amount,
+            false => 1.0,
+        }
+    }
+
+    /// Works on `recipe` for `delta_seconds`. A finished batch takes its
+    /// inputs and puts its outputs, scaled by `output_multiplier`, in the
+    /// output. Switching recipes or running out of inputs loses the batch.
+    /// Returns whether a batch finished.
+    pub fn tick(
+        &mut self,
+        recipe: Option<&Recipe>,
+        delta_seconds: f32,
+        output_multiplier: f32,
+    ) -> bool {
+        let id = recipe.map(|recipe| recipe.id.clone());
+        if self.recipe != id {
+            self.recipe = id;
+            self.progress = 0.0;
+        }
+
+        let Some(recipe) = recipe.filter(|recipe| self.has_inputs(recipe)) else {
+            self.progress = 0.0;
+            return false;
+        };
+        if output_multiplier <= 0.0 {
+            return false;
+        }
+
+        self.progress += delta_seconds / recipe.time;
+        if self.progress < 1.0 {
+            return false;
+        }
+
+        self.progress = 0.0;
+        for input in &recipe.inputs {
+            self.input.take_by_name(&input.item, input.amount);
+        }
+        for output in &recipe.outputs {
+            let amount = output.amount * output_multiplier;
+            if !add_named(&mut self.output, &output.item, amount) {
+                log::warn!("Recipe {} makes unknown item {}", recipe.id, output.item);
+            }
+        }
+        true
+    }
+
+    /// Empties the machine, handing back everything loaded into it or made
+    /// by it. The batch in progress is lost.
+    pub fn spill(&mut self) -> Inventory {
+        let mut spilled = std::mem::take(&mut self.input);
+        let made: Vec<_> = self.output.get_all().into_iter().map(ItemKey::of).collect();
+        for key in made {
+            self.output.move_item(key, None, &mut spilled);
+        }
+        self.progress = 0.0;
+        spilled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::ore::{IronOre, Ore};
+    use crate::research::ItemCost;
+
+    fn iron_ingot() -> Recipe {
+        Recipe {
+            id: "iron_ingot".to_string(),
+            name: "Iron Ingot".to_string(),
+            machine: "furnace".to_string(),
+            time: 4.0,
+            inputs: vec![ItemCost {
+                item: "Iron Ore".to_string(),
+                amount: 2.0,
+            }],
+            outputs: vec![ItemCost {
+                item: "Iron Ingot".to_string(),
+                amount: 1.0,
+            }],
+            repairs: None,
+        }
+    }
+
+    fn loaded(ore: f32, purity: f32) -> MachineProcess {
+        let mut process = MachineProcess::default();
+        process.input.add(Ore::<IronOre>::new(ore, purity, 0));
+        process
+    }
+
+    #[test]
+    fn test_batch_takes_inputs_and_makes_outputs() {
+        let recipe = iron_ingot();
+        let mut process = loaded(3.0, 1.0);
+
+        assert!(!process.tick(Some(&recipe), 3.0, 1.0));
+        assert_eq!(process.progress, 0.75);
+        assert!(process.tick(Some(&recipe), 1.0, 1.0));
+
+        assert_eq!(process.progress, 0.0);
+        assert_eq!(process.input.amount_of("Iron Ore"), 1.0);
+        assert_eq!(process.output.amount_of("Iron Ingot"), 1.0);
+    }
+
+    #[test]
+    fn test_reduced_output() {
+        let recipe = iron_ingot();
+        let mut process = loaded(2.0, 1.0);
+
+        assert!(process.tick(Some(&recipe), 4.0, 0.5));
+        assert_eq!(process.output.amount_of("Iron Ingot"), 0.5);
+    }
+
+    #[test]
+    fn test_missing_inputs_lose_the_batch() {
+        let recipe = iron_ingot();
+        let mut process = loaded(1.0, 1.0);
+        process.recipe = Some(recipe.id.clone());
+        process.progress = 0.5;
+
+        assert!(!process.tick(Some(&recipe), 1.0, 1.0));
+        assert_eq!(process.progress, 0.0);
+        assert!(process.output.is_empty());
+    }
+
+    #[test]
+    fn test_switching_recipes_restarts() {
+        let recipe = iron_ingot();
+        let mut process = loaded(2.0, 1.0);
+        process.tick(Some(&recipe), 2.0, 1.0);
+
+        let mut other = iron_ingot();
+        other.id = "other".to_string();
+        process.tick(Some(&other), 1.0, 1.0);
+
+        assert_eq!(process.recipe.as_deref(), Some("other"));
+        assert_eq!(process.progress, 0.25);
+    }
+
+    #[test]
+    fn test_stopped_machines_keep_progress() {
+        let recipe = iron_ingot();
+        let mut process = loaded(2.0, 1.0);
+        process.tick(Some(&recipe), 2.0, 1.0);
+
+        assert!(!process.tick(Some(&recipe), 2.0, 0.0));
+        assert_eq!(process.progress, 0.5);
+    }
+
+    #[test]
+    fn test_input_purity() {
+        let recipe = iron_ingot();
+        let mut process = loaded(1.0, 0.2);
+        process.input.add(Ore::<IronOre>::new(3.0, 1.0, 1));
+
+        assert!((process.input_purity(&recipe) - 0.8).abs() < 1e-6);
+        assert_eq!(MachineProcess::default().input_purity(&recipe), 1.0);
+    }
+}
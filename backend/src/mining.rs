@@ -0,0 +1,170 @@
+//! Ore deposits in the world, broken up with a pickaxe a swing at a time.
+
+use bevy::prelude::*;
+
+use crate::iams::Inventory;
+use crate::items::ore::{CopperOre, IronOre, Ore};
+
+/// Seconds one swing of a pickaxe takes.
+pub const SWING_TIME: f32 = 1.0;
+
+/// Ore broken off by one swing, in kg.
+pub const SWING_YIELD: f32 = 0.5;
+
+/// Stamina spent by one swing. Too tired to pay it, a swing breaks nothing
+/// off.
+pub const SWING_STAMINA: f32 = 5.0;
+
+/// How close the player has to be to a deposit to mine it.
+pub const MINING_REACH: f32 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OreKind {
+    Copper,
+    Iron,
+}
+
+impl OreKind {
+    #[rustfmt::skip]
+    pub fn name(&self) -> &'static str {
+        match self {
+            OreKind::Copper => "Copper Ore",
+            OreKind::Iron   => "Iron Ore",
+        }
+    }
+
+    /// How good a tool has to be to mine the ore. See [ToolKind::tier].
+    ///
+    /// [ToolKind::tier]: crate::items::tool::ToolKind::tier
+    #[rustfmt::skip]
+    pub fn tier(&self) -> u8 {
+        match self {
+            OreKind::Copper => 1,
+            OreKind::Iron   => 2,
+        }
+    }
+
+    /// Puts `amount` kg of this ore into `inventory`.
+    pub fn give(&self, amount: f32, purity: f32, inventory: &mut Inventory) {
+        match self {
+            OreKind::Copper => {
+                let id = inventory.next_id::<Ore<CopperOre>>();
+                inventory.add(Ore::<CopperOre>::new(amount, purity, id));
+            }
+            OreKind::Iron => {
+                let id = inventory.next_id::<Ore<IronOre>>();
+                inventory.add(Ore::<IronOre>::new(amount, purity, id));
+            }
+        }
+    }
+}
+
+/// A vein of ore in the world, used up as it is mined.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct OreDeposit {
+    pub kind: OreKind,
+    /// Purity of the ore before the miner's skill is counted.
+    pub purity: f32,
+    /// Ore left, in kg.
+    pub remaining: f32,
+}
+
+impl OreDeposit {
+    pub fn new(kind: OreKind, purity: f32, remaining: f32) -> Self {
+        Self {
+            kind,
+            purity,
+            remaining,
+        }
+    }
+
+    /// Breaks up to `amount` kg off the deposit. Returns how much came off.
+    pub fn mine(&mut self, amount: f32) -> f32 {
+        let mined = amount.min(self.remaining).max(0.0);
+        self.remaining -= mined;
+        mined
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining <= 0.0
+    }
+}
+
+/// How far through a swing at a deposit a miner is.
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct MiningProgress {
+    pub target: Option<Entity>,
+    /// From 0 to 1 through the current swing.
+    pub progress: f32,
+}
+
+impl MiningProgress {
+    /// Keeps swinging at `target`, `speed` times as fast as normal. Looking
+    /// away or stopping starts the swing over. Returns whether a swing
+    /// landed.
+    pub fn tick(&mut self, target: Option<Entity>, delta_seconds: f32, speed: f32) -> bool {
+        if target.is_none() || target != self.target {
+            self.target = target;
+            self.progress = 0.0;
+            return false;
+        }
+
+        self.progress += delta_seconds * speed / SWING_TIME;
+        if self.progress < 1.0 {
+            return false;
+        }
+        self.progress -= 1.0;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mine_until_exhausted() {
+        let mut deposit = OreDeposit::new(OreKind::Iron, 0.6, 1.2);
+
+        assert_eq!(deposit.mine(0.5), 0.5);
+        assert_eq!(deposit.mine(0.5), 0.5);
+        assert!((deposit.mine(0.5) - 0.2).abs() < 1e-6);
+        assert!(deposit.is_exhausted());
+        assert_eq!(deposit.mine(0.5), 0.0);
+    }
+
+    #[test]
+    fn test_give_adds_new_items() {
+        let mut inventory = Inventory::default();
+        OreKind::Copper.give(0.5, 0.7, &mut inventory);
+        OreKind::Copper.give(0.5, 0.8, &mut inventory);
+
+        assert_eq!(inventory.amount_of("Copper Ore"), 1.0);
+        assert_eq!(inventory.get_all().len(), 2);
+    }
+
+    #[test]
+    fn test_swings_land_after_swing_time() {
+        let target = Some(Entity::from_raw(1));
+        let mut progress = MiningProgress::default();
+
+        // The first frame only picks the target.
+        assert!(!progress.tick(target, 0.5, 1.0));
+        assert!(!progress.tick(target, 0.5, 1.0));
+        assert!(progress.tick(target, 0.5, 1.0));
+
+        assert!(!progress.tick(target, 0.25, 2.0));
+        assert!(progress.tick(target, 0.25, 2.0));
+    }
+
+    #[test]
+    fn test_looking_away_restarts_swing() {
+        let mut progress = MiningProgress::default();
+        progress.tick(Some(Entity::from_raw(1)), 0.0, 1.0);
+        progress.tick(Some(Entity::from_raw(1)), 0.9, 1.0);
+
+        assert!(!progress.tick(Some(Entity::from_raw(2)), 0.9, 1.0));
+        assert_eq!(progress.progress, 0.0);
+        assert!(!progress.tick(None, 0.9, 1.0));
+    }
+}
@@ -0,0 +1,325 @@
+//! Collision layers and surface materials shared by everything with a
+//! collider.
+
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+pub struct PhysicsMaterialPlugin;
+
+impl Plugin for PhysicsMaterialPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            apply_surface_materials.before(PhysicsSet::Prepare),
+        );
+    }
+}
+
+/// What a collider is, deciding what it collides with and which spatial
+/// queries see it. Colliders spawned without [CollisionLayers] still count as
+/// world.
+#[derive(PhysicsLayer, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    World,
+    Player,
+    /// Items lying loose in the world.
+    Item,
+    Building,
+    /// Sensors that notice what passes through them without blocking it.
+    Trigger,
+    /// Items riding a conveyor. They only touch the belt and the ground, so
+    /// they cannot jam against each other or the player.
+    ConveyorItem,
+}
+
+impl Layer {
+    pub const ALL: [Layer; 6] = [
+        Layer::World,
+        Layer::Player,
+        Layer::Item,
+        Layer::Building,
+        Layer::Trigger,
+        Layer::ConveyorItem,
+    ];
+
+    /// The layers this one collides with. Collisions need both sides to agree,
+    /// so the table is symmetric.
+    #[rustfmt::skip]
+    pub fn filters(self) -> &'static [Layer] {
+        use Layer::*;
+        match self {
+            World        => &[Player, Item, Building, ConveyorItem],
+            Player       => &[World, Building, Trigger],
+            Item         => &[World, Item, Building, Trigger],
+            Building     => &[World, Player, Item, ConveyorItem],
+            Trigger      => &[Player, Item],
+            ConveyorItem => &[World, Building],
+        }
+    }
+
+    /// What to give a collider on this layer.
+    pub fn collision_layers(self) -> CollisionLayers {
+        CollisionLayers::new([self], mask(self.filters()))
+    }
+
+    /// The layers that block movement on this layer, for spatial queries.
+    pub fn solid(self) -> LayerMask {
+        solid_filters(&self.collision_layers())
+    }
+
+    /// The layers a new collider on this layer may not overlap, for spatial
+    /// queries: whatever blocks it and others of its own kind.
+    pub fn occupied(self) -> LayerMask {
+        LayerMask(self.solid().0 | self.to_bits())
+    }
+}
+
+/// The layers that block movement for a collider with `layers`, for spatial
+/// queries. Triggers never block anything.
+pub fn solid_filters(layers: &CollisionLayers) -> LayerMask {
+    LayerMask(layers.filters.0 & !Layer::Trigger.to_bits())
+}
+
+fn mask(layers: &[Layer]) -> LayerMask {
+    LayerMask(layers.iter().fold(0, |bits, layer| bits | layer.to_bits()))
+}
+
+/// Everything a collider needs to take part in physics, with layers and
+/// material filled in for the kind of thing it is.
+#[derive(Bundle)]
+pub struct PhysicsBundle {
+    pub rigid_body: RigidBody,
+    pub collider: Collider,
+    pub layers: CollisionLayers,
+    pub material: SurfaceMaterial,
+}
+
+impl PhysicsBundle {
+    /// Something that never moves, like the ground or a building.
+    pub fn fixed(layer: Layer, collider: Collider, material: SurfaceMaterial) -> Self {
+        Self {
+            rigid_body: RigidBody::Static,
+            collider,
+            layers: layer.collision_layers(),
+            material,
+        }
+    }
+
+    /// An item lying loose in the world, free to tumble and slide.
+    pub fn item(collider: Collider, material: SurfaceMaterial) -> Self {
+        Self {
+            rigid_body: RigidBody::Dynamic,
+            collider,
+            layers: Layer::Item.collision_layers(),
+            material,
+        }
+    }
+
+    /// An item carried along by a conveyor belt.
+    pub fn conveyor_item(collider: Collider, material: SurfaceMaterial) -> Self {
+        Self {
+            rigid_body: RigidBody::Dynamic,
+            collider,
+            layers: Layer::ConveyorItem.collision_layers(),
+            material,
+        }
+    }
+
+    /// A sensor that notices what passes through it without blocking it.
+    pub fn trigger(collider: Collider) -> (Self, Sensor) {
+        let trigger = Self {
+            rigid_body: RigidBody::Static,
+            collider,
+            layers: Layer::Trigger.collision_layers(),
+            material: SurfaceMaterial::default(),
+        };
+        (trigger, Sensor)
+    }
+
+    /// A player moved by their character controller.
+    pub fn player(collider: Collider) -> Self {
+        Self {
+            rigid_body: RigidBody::Kinematic,
+            collider,
+            layers: Layer::Player.collision_layers(),
+            material: SurfaceMaterial::default(),
+        }
+    }
+}
+
+/// What a surface is made of, for footsteps and how things slide on it.
+/// Surfaces without one are dirt.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SurfaceMaterial {
+    #[default]
+    Dirt,
+    Grass,
+    Stone,
+    Metal,
+    Wood,
+}
+
+impl SurfaceMaterial {
+    /// Touching surfaces use the lower friction of the two, so things slide
+    /// easily on metal whatever they are made of.
+    #[rustfmt::skip]
+    pub fn friction(self) -> Friction {
+        let coefficient = match self {
+            SurfaceMaterial::Dirt  => 0.7,
+            SurfaceMaterial::Grass => 0.6,
+            SurfaceMaterial::Stone => 0.6,
+            SurfaceMaterial::Metal => 0.2,
+            SurfaceMaterial::Wood  => 0.5,
+        };
+        Friction::new(coefficient).with_combine_rule(CoefficientCombine::Min)
+    }
+
+    #[rustfmt::skip]
+    pub fn restitution(self) -> Restitution {
+        match self {
+            SurfaceMaterial::Dirt  => Restitution::new(0.05),
+            SurfaceMaterial::Grass => Restitution::new(0.1),
+            SurfaceMaterial::Stone => Restitution::new(0.2),
+            SurfaceMaterial::Metal => Restitution::new(0.3),
+            SurfaceMaterial::Wood  => Restitution::new(0.25),
+        }
+    }
+}
+
+/// Gives colliders the friction and restitution of their [SurfaceMaterial].
+pub fn apply_surface_materials(
+    mut commands: Commands,
+    surfaces: Query<(Entity, &SurfaceMaterial), Changed<SurfaceMaterial>>,
+) {
+    for (entity, material) in surfaces.iter() {
+        commands
+            .entity(entity)
+            .insert((material.friction(), material.restitution()));
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use std::time::Duration;
+
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+
+    /// A headless app with physics, stepping 1/60 s per update.
+    pub fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            HierarchyPlugin,
+            AssetPlugin::default(),
+            bevy::scene::ScenePlugin,
+            PhysicsPlugins::default(),
+            PhysicsMaterialPlugin,
+        ))
+        .init_asset::<Mesh>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            1.0 / 60.0,
+        )));
+        app
+    }
+
+    pub fn spawn_box(app: &mut App, center: Vec3, size: Vec3, rotation: Quat) -> Entity {
+        app.world
+            .spawn((
+                RigidBody::Static,
+                Collider::cuboid(size.x, size.y, size.z),
+                TransformBundle::from_transform(
+                    Transform::from_translation(center).with_rotation(rotation),
+                ),
+            ))
+            .id()
+    }
+
+    pub fn run(app: &mut App, frames: usize) {
+        for _ in 0..frames {
+            app.update();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::{app, run, spawn_box};
+    use super::*;
+
+    #[test]
+    fn test_layers_are_symmetric() {
+        for layer in Layer::ALL {
+            for other in layer.filters() {
+                assert!(other.filters().contains(&layer), "{layer:?} and {other:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_triggers_are_not_solid() {
+        let solid = Layer::Player.solid();
+        assert_eq!(solid.0 & Layer::Trigger.to_bits(), 0);
+        assert_ne!(solid.0 & Layer::World.to_bits(), 0);
+    }
+
+    #[test]
+    fn test_material_sets_friction() {
+        let mut app = app();
+        let surface = app.world.spawn(SurfaceMaterial::Metal).id();
+        app.update();
+        assert_eq!(
+            app.world.get::<Friction>(surface),
+            Some(&SurfaceMaterial::Metal.friction())
+        );
+
+        app.world.entity_mut(surface).insert(SurfaceMaterial::Dirt);
+        app.update();
+        assert_eq!(
+            app.world.get::<Friction>(surface),
+            Some(&SurfaceMaterial::Dirt.friction())
+        );
+    }
+
+    /// How far a chunk of ore slides down a 20° ramp of the given material.
+    fn slide_distance(ramp: SurfaceMaterial) -> f32 {
+        let mut app = app();
+        let angle = 20f32.to_radians();
+        let slope = Quat::from_rotation_z(angle);
+        let ramp_entity = spawn_box(&mut app, Vec3::ZERO, Vec3::new(20.0, 1.0, 4.0), slope);
+        app.world
+            .entity_mut(ramp_entity)
+            .insert((Layer::World.collision_layers(), ramp));
+
+        let start = slope * Vec3::new(0.0, 0.5 + 0.15 + 0.01, 0.0);
+        let ore = app
+            .world
+            .spawn((
+                RigidBody::Dynamic,
+                Collider::cuboid(0.3, 0.3, 0.3),
+                Layer::Item.collision_layers(),
+                SurfaceMaterial::Stone,
+                TransformBundle::from_transform(
+                    Transform::from_translation(start).with_rotation(slope),
+                ),
+            ))
+            .id();
+        run(&mut app, 120);
+
+        app.world
+            .get::<Transform>(ore)
+            .unwrap()
+            .translation
+            .distance(start)
+    }
+
+    #[test]
+    fn test_ore_slides_on_metal_but_not_dirt() {
+        let metal = slide_distance(SurfaceMaterial::Metal);
+        let dirt = slide_distance(SurfaceMaterial::Dirt);
+        assert!(metal > 1.0, "{metal}");
+        assert!(dirt < 0.2, "{dirt}");
+    }
+}
@@ -0,0 +1,682 @@
+//! Kinematic character controller. Characters are moved by casting their
+//! collider through the world and sliding along whatever they hit, rather
+//! than being pushed around by the physics solver.
+
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::physics::{solid_filters, SurfaceMaterial};
+
+use super::floor::Grounded;
+
+/// How many times a move may be deflected by what it hits in one frame.
+const MAX_SLIDES: usize = 4;
+/// How far below a character the ground may be for it to count as standing.
+const GROUND_CHECK: f32 = 0.05;
+/// How far beside a contact the surface is probed for its slope.
+const PROBE: f32 = 0.05;
+
+pub struct CharacterControllerPlugin;
+
+impl Plugin for CharacterControllerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, move_characters);
+    }
+}
+
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct CharacterController {
+    /// Steepest slope in radians that can be walked up.
+    pub max_slope: f32,
+    /// Tallest ledge in m walked onto without jumping, such as a stair or belt.
+    pub step_height: f32,
+    /// How far in m the character is pulled down to stay on the ground when
+    /// walking down slopes and stairs.
+    pub snap_distance: f32,
+    /// Seconds after walking off a ledge that jumping still works.
+    pub coyote_time: f32,
+    /// Seconds a jump pressed in the air is remembered, so it happens on
+    /// landing.
+    pub jump_buffer: f32,
+    pub gravity: f32,
+    /// How quickly the character turns towards the wanted movement in the air,
+    /// per second.
+    pub air_control: f32,
+    /// Gap in m kept between the collider and the world, so casts do not start
+    /// inside what they are touching.
+    pub skin: f32,
+}
+
+impl Default for CharacterController {
+    fn default() -> Self {
+        Self {
+            max_slope: 45f32.to_radians(),
+            step_height: 0.3,
+            snap_distance: 0.3,
+            coyote_time: 0.1,
+            jump_buffer: 0.2,
+            gravity: 9.8,
+            air_control: 2.0,
+            skin: 0.02,
+        }
+    }
+}
+
+/// What the character wants to do this frame.
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct CharacterInput {
+    /// Horizontal velocity in m/s.
+    pub movement: Vec3,
+    /// Upwards velocity of a jump pressed this frame. Taken by the controller.
+    pub jump: Option<f32>,
+}
+
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct CharacterState {
+    pub velocity: Vec3,
+    /// Vertical velocity the character hit the ground with, on the frame it
+    /// landed.
+    pub landed: Option<f32>,
+    /// Seconds since the character last stood on the ground.
+    airborne_time: f32,
+    /// Velocity of a jump waiting to happen and the seconds it waits for.
+    buffered_jump: Option<(f32, f32)>,
+}
+
+impl Default for CharacterState {
+    fn default() -> Self {
+        Self {
+            velocity: Vec3::ZERO,
+            landed: None,
+            // A character spawned in the air has no coyote time to use.
+            airborne_time: f32::INFINITY,
+            buffered_jump: None,
+        }
+    }
+}
+
+/// Holds a character where it is, with gravity and momentum paused, for
+/// example while the camera flies free of the player.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq)]
+pub struct Frozen;
+
+impl CharacterState {
+    /// Stops the character, for example after teleporting it.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// The collider of a character and how to cast it through the world.
+struct Body<'a, 'w, 's> {
+    query: &'a SpatialQuery<'w, 's>,
+    shape: &'a Collider,
+    rotation: Quat,
+    filter: SpatialQueryFilter,
+    skin: f32,
+}
+
+struct Hit {
+    entity: Entity,
+    distance: f32,
+    point: Vec3,
+    normal: Vec3,
+}
+
+impl Body<'_, '_, '_> {
+    /// How far the body can move from `origin` along `motion` before touching
+    /// something, and where it touches.
+    fn cast(&self, origin: Vec3, motion: Vec3) -> Option<Hit> {
+        let direction = Direction3d::new(motion).ok()?;
+        let hit = self.query.cast_shape(
+            self.shape,
+            origin,
+            self.rotation,
+            direction,
+            motion.length() + self.skin,
+            true,
+            self.filter.clone(),
+        )?;
+        Some(Hit {
+            entity: hit.entity,
+            distance: (hit.time_of_impact - self.skin).max(0.0),
+            point: hit.point1,
+            normal: hit.normal1,
+        })
+    }
+
+    /// Normal of the surface just beside `point`, towards `toward`. The
+    /// collider touches the corner of a ledge with its rounded bottom, so the
+    /// normal of the hit itself is tilted even when the ledge is flat.
+    fn surface_normal(&self, point: Vec3, toward: Vec3) -> Option<Vec3> {
+        let toward = Vec3::new(toward.x, 0.0, toward.z).normalize_or_zero();
+        let origin = point + toward * PROBE + Vec3::Y * PROBE * 2.0;
+        self.query
+            .cast_ray(
+                origin,
+                Direction3d::NEG_Y,
+                PROBE * 4.0,
+                true,
+                self.filter.clone(),
+            )
+            .map(|hit| hit.normal)
+    }
+
+    /// Moves along `motion`, sliding along whatever is hit. With `walls` set,
+    /// surfaces steeper than `min_floor_y` are treated as upright so they
+    /// cannot be walked up. Returns whether such a wall was hit.
+    fn slide(&self, position: &mut Vec3, mut motion: Vec3, min_floor_y: f32, walls: bool) -> bool {
+        let mut blocked = false;
+        for _ in 0..MAX_SLIDES {
+            let Some(hit) = self.cast(*position, motion) else {
+                *position += motion;
+                break;
+            };
+
+            let direction = motion.normalize();
+            *position += direction * hit.distance;
+            let remaining = direction * (motion.length() - hit.distance);
+
+            let steep = walls && hit.normal.y < min_floor_y;
+            blocked |= steep;
+            let normal = match steep {
+                true => Vec3::new(hit.normal.x, 0.0, hit.normal.z).normalize_or_zero(),
+                false => hit.normal,
+            };
+            motion = remaining - normal * remaining.dot(normal);
+        }
+        blocked
+    }
+
+    /// Where the body ends up stepping over a ledge: lifted, moved along
+    /// `motion` and put back down. None if there is nothing to stand on.
+    fn step(&self, start: Vec3, motion: Vec3, height: f32, min_floor_y: f32) -> Option<Vec3> {
+        let rise = self
+            .cast(start, Vec3::Y * height)
+            .map_or(height, |hit| hit.distance);
+        let mut position = start + Vec3::Y * rise;
+        self.slide(&mut position, motion, min_floor_y, true);
+
+        let hit = self.cast(position, Vec3::NEG_Y * rise)?;
+        let normal = self.surface_normal(hit.point, motion)?;
+        match normal.y >= min_floor_y {
+            true => Some(position - Vec3::Y * hit.distance),
+            false => None,
+        }
+    }
+
+    /// The ground within `distance` below, if it is flat enough to stand on,
+    /// with the normal of its surface. Past the edge of a ledge there is no
+    /// ground, so the character falls off rather than balancing on the corner.
+    fn ground(&self, position: Vec3, distance: f32, min_floor_y: f32) -> Option<Hit> {
+        let hit = self.cast(position, Vec3::NEG_Y * distance)?;
+        let normal = self.surface_normal(hit.point, position - hit.point)?;
+        match normal.y >= min_floor_y {
+            true => Some(Hit { normal, ..hit }),
+            false => None,
+        }
+    }
+}
+
+/// Moves a character at `position` for `dt` seconds. Returns the ground it
+/// ends up standing on.
+fn step_character(
+    body: &Body,
+    position: &mut Vec3,
+    controller: &CharacterController,
+    input: &mut CharacterInput,
+    state: &mut CharacterState,
+    was_grounded: bool,
+    dt: f32,
+) -> Option<Hit> {
+    let min_floor_y = controller.max_slope.cos();
+    state.landed = None;
+    state.airborne_time = match was_grounded {
+        true => 0.0,
+        false => state.airborne_time + dt,
+    };
+
+    if let Some(velocity) = input.jump.take() {
+        state.buffered_jump = Some((velocity, controller.jump_buffer));
+    }
+    let can_jump = was_grounded || state.airborne_time <= controller.coyote_time;
+    let mut jumped = false;
+    state.buffered_jump = match state.buffered_jump {
+        Some((velocity, _)) if can_jump => {
+            state.velocity.y = velocity;
+            // Leaves no coyote time for a second jump.
+            state.airborne_time = f32::INFINITY;
+            jumped = true;
+            None
+        }
+        Some((velocity, time)) if time > dt => Some((velocity, time - dt)),
+        _ => None,
+    };
+
+    let wanted = Vec3::new(input.movement.x, 0.0, input.movement.z);
+    let horizontal = match was_grounded {
+        true => wanted,
+        false => {
+            let current = Vec3::new(state.velocity.x, 0.0, state.velocity.z);
+            current.lerp(wanted, 1.0 - (-controller.air_control * dt).exp())
+        }
+    };
+    state.velocity = Vec3::new(horizontal.x, state.velocity.y, horizontal.z);
+    if !was_grounded {
+        state.velocity.y -= controller.gravity * dt;
+    }
+
+    let start = *position;
+    let blocked = body.slide(position, horizontal * dt, min_floor_y, was_grounded);
+    if blocked && was_grounded && !jumped {
+        let moved = (*position - start).xz().length();
+        let stepped = body.step(start, horizontal * dt, controller.step_height, min_floor_y);
+        if let Some(stepped) = stepped.filter(|stepped| (*stepped - start).xz().length() > moved) {
+            *position = stepped;
+        }
+    }
+
+    let before = *position;
+    body.slide(
+        position,
+        Vec3::Y * state.velocity.y * dt,
+        min_floor_y,
+        false,
+    );
+    // Bumping a ceiling stops the rise.
+    if state.velocity.y > 0.0 && position.y - before.y < state.velocity.y * dt * 0.5 {
+        state.velocity.y = 0.0;
+    }
+
+    let snap = match was_grounded && state.velocity.y <= 0.0 {
+        true => controller.snap_distance,
+        false => GROUND_CHECK,
+    };
+    let ground = match state.velocity.y > 0.0 {
+        true => None,
+        false => body.ground(*position, snap, min_floor_y),
+    };
+
+    if let Some(hit) = &ground {
+        position.y -= hit.distance;
+        if !was_grounded {
+            state.landed = Some(state.velocity.y);
+        }
+        state.velocity.y = 0.0;
+    }
+    ground
+}
+
+/// What [move_characters] reads and writes for each character.
+type CharacterData<'a> = (
+    Entity,
+    &'a mut Transform,
+    &'a Collider,
+    Option<&'a CollisionLayers>,
+    &'a CharacterController,
+    &'a mut CharacterInput,
+    &'a mut CharacterState,
+    Option<&'a mut Grounded>,
+);
+
+/// Moves every character and keeps its [Grounded] up to date, which is only
+/// inserted when it lands and changed when what it stands on does.
+pub fn move_characters(
+    mut commands: Commands,
+    time: Res<Time>,
+    spatial_query: SpatialQuery,
+    mut characters: Query<CharacterData, Without<Frozen>>,
+    materials: Query<&SurfaceMaterial>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut transform, collider, layers, controller, mut input, mut state, grounded) in
+        characters.iter_mut()
+    {
+        let body = Body {
+            query: &spatial_query,
+            shape: collider,
+            rotation: transform.rotation,
+            filter: SpatialQueryFilter::from_mask(layers.map_or(LayerMask::ALL, solid_filters))
+                .with_excluded_entities([entity]),
+            skin: controller.skin,
+        };
+        let ground = step_character(
+            &body,
+            &mut transform.translation,
+            controller,
+            &mut input,
+            &mut state,
+            grounded.is_some(),
+            dt,
+        );
+
+        let floor = ground.map(|hit| Grounded {
+            floor: hit.entity,
+            normal: hit.normal,
+            material: materials.get(hit.entity).copied().unwrap_or_default(),
+            // Snapping to the ground leaves only the skin between them.
+            distance: controller.skin,
+        });
+        match (floor, grounded) {
+            (Some(floor), Some(mut grounded)) => {
+                grounded.set_if_neq(floor);
+            }
+            (Some(floor), None) => {
+                commands.entity(entity).insert(floor);
+            }
+            (None, Some(_)) => {
+                commands.entity(entity).remove::<Grounded>();
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::testing::{run, spawn_box};
+
+    /// Half the height of the test character's capsule.
+    const HALF_HEIGHT: f32 = 0.9;
+
+    fn app() -> App {
+        let mut app = crate::physics::testing::app();
+        app.add_plugins(CharacterControllerPlugin);
+
+        // The floor, with its top at y = 0.
+        spawn_box(
+            &mut app,
+            Vec3::new(0.0, -0.5, 0.0),
+            Vec3::new(40.0, 1.0, 40.0),
+            Quat::IDENTITY,
+        );
+        app
+    }
+
+    /// A ramp rising along +x from x = 1 at the given angle.
+    fn spawn_ramp(app: &mut App, angle: f32) {
+        let length = 10.0;
+        let center = Vec3::new(
+            1.0 + angle.cos() * length / 2.0,
+            angle.sin() * length / 2.0,
+            0.0,
+        );
+        spawn_box(
+            app,
+            center,
+            Vec3::new(length, 0.1, 4.0),
+            Quat::from_rotation_z(angle),
+        );
+    }
+
+    fn spawn_character(app: &mut App, position: Vec3) -> Entity {
+        app.world
+            .spawn((
+                RigidBody::Kinematic,
+                Collider::capsule(1.0, 0.4),
+                TransformBundle::from_transform(Transform::from_translation(position)),
+                CharacterController::default(),
+                CharacterInput::default(),
+                CharacterState::default(),
+            ))
+            .id()
+    }
+
+    fn position(app: &App, character: Entity) -> Vec3 {
+        app.world.get::<Transform>(character).unwrap().translation
+    }
+
+    fn state(app: &App, character: Entity) -> CharacterState {
+        app.world.get::<CharacterState>(character).unwrap().clone()
+    }
+
+    fn grounded(app: &App, character: Entity) -> bool {
+        app.world.get::<Grounded>(character).is_some()
+    }
+
+    fn input(app: &mut App, character: Entity) -> Mut<'_, CharacterInput> {
+        app.world.get_mut::<CharacterInput>(character).unwrap()
+    }
+
+    /// Lets the character settle onto the floor.
+    fn settled(position: Vec3) -> (App, Entity) {
+        let mut app = app();
+        let character = spawn_character(&mut app, position);
+        run(&mut app, 30);
+        assert!(grounded(&app, character));
+        (app, character)
+    }
+
+    #[test]
+    fn test_falls_onto_floor() {
+        let mut app = app();
+        let character = spawn_character(&mut app, Vec3::new(0.0, 3.0, 0.0));
+        run(&mut app, 120);
+
+        assert!(grounded(&app, character));
+        assert_eq!(state(&app, character).velocity.y, 0.0);
+        assert!((position(&app, character).y - HALF_HEIGHT).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_reports_what_it_stands_on() {
+        let mut app = app();
+        let slope = Quat::from_rotation_z(0.3);
+        let floor = spawn_box(
+            &mut app,
+            Vec3::new(20.0, 5.0, 0.0),
+            Vec3::new(10.0, 1.0, 10.0),
+            slope,
+        );
+        app.world.entity_mut(floor).insert(SurfaceMaterial::Metal);
+        let character = spawn_character(&mut app, Vec3::new(20.0, 7.0, 0.0));
+        run(&mut app, 60);
+
+        let grounded = app.world.get::<Grounded>(character).unwrap();
+        assert_eq!(grounded.floor, floor);
+        assert_eq!(grounded.material, SurfaceMaterial::Metal);
+        assert!(grounded.normal.dot(slope * Vec3::Y) > 0.99);
+    }
+
+    #[test]
+    fn test_frozen_characters_hang_in_the_air() {
+        let mut app = app();
+        let character = spawn_character(&mut app, Vec3::new(0.0, 3.0, 0.0));
+        app.world.entity_mut(character).insert(Frozen);
+        input(&mut app, character).movement = Vec3::X;
+        run(&mut app, 30);
+
+        assert_eq!(position(&app, character), Vec3::new(0.0, 3.0, 0.0));
+        assert_eq!(state(&app, character).velocity, Vec3::ZERO);
+    }
+
+    #[test]
+    fn test_reports_landing_velocity() {
+        let mut app = app();
+        let character = spawn_character(&mut app, Vec3::new(0.0, 3.0, 0.0));
+
+        let mut landed = None;
+        for _ in 0..120 {
+            app.update();
+            landed = landed.or(state(&app, character).landed);
+        }
+        assert!(landed.unwrap() < -5.0);
+    }
+
+    #[test]
+    fn test_slides_along_wall() {
+        let (mut app, character) = settled(Vec3::new(0.0, HALF_HEIGHT, 0.0));
+        // A wall along z, just in front of the character.
+        spawn_box(
+            &mut app,
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(0.2, 2.0, 20.0),
+            Quat::IDENTITY,
+        );
+        run(&mut app, 2);
+
+        input(&mut app, character).movement = Vec3::new(3.0, 0.0, 3.0).normalize() * 3.0;
+        run(&mut app, 60);
+
+        let position = position(&app, character);
+        assert!(position.x < 0.9 - 0.4 + 0.05);
+        assert!(position.z > 1.0);
+    }
+
+    #[test]
+    fn test_walks_up_gentle_slope() {
+        let mut app = app();
+        spawn_ramp(&mut app, 20f32.to_radians());
+        let character = spawn_character(&mut app, Vec3::new(-1.0, HALF_HEIGHT, 0.0));
+        run(&mut app, 30);
+
+        input(&mut app, character).movement = Vec3::X * 3.0;
+        run(&mut app, 90);
+
+        assert!(position(&app, character).y > HALF_HEIGHT + 0.5);
+        assert!(grounded(&app, character));
+    }
+
+    #[test]
+    fn test_blocked_by_steep_slope() {
+        let mut app = app();
+        spawn_ramp(&mut app, 60f32.to_radians());
+        let character = spawn_character(&mut app, Vec3::new(-1.0, HALF_HEIGHT, 0.0));
+        run(&mut app, 30);
+
+        input(&mut app, character).movement = Vec3::X * 3.0;
+        run(&mut app, 90);
+
+        let position = position(&app, character);
+        assert!(position.x < 1.5);
+        assert!(position.y < HALF_HEIGHT + 0.5);
+    }
+
+    #[test]
+    fn test_steps_up_low_ledge() {
+        let (mut app, character) = settled(Vec3::new(0.0, HALF_HEIGHT, 0.0));
+        spawn_box(
+            &mut app,
+            Vec3::new(2.0, 0.1, 0.0),
+            Vec3::new(2.0, 0.2, 4.0),
+            Quat::IDENTITY,
+        );
+        run(&mut app, 2);
+
+        input(&mut app, character).movement = Vec3::X * 3.0;
+        run(&mut app, 45);
+
+        let position = position(&app, character);
+        assert!(position.x > 1.5);
+        assert!((position.y - (HALF_HEIGHT + 0.2)).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_blocked_by_high_ledge() {
+        let (mut app, character) = settled(Vec3::new(0.0, HALF_HEIGHT, 0.0));
+        spawn_box(
+            &mut app,
+            Vec3::new(2.0, 0.3, 0.0),
+            Vec3::new(2.0, 0.6, 4.0),
+            Quat::IDENTITY,
+        );
+        run(&mut app, 2);
+
+        input(&mut app, character).movement = Vec3::X * 3.0;
+        run(&mut app, 45);
+
+        let position = position(&app, character);
+        assert!(position.x < 1.0 - 0.4 + 0.05);
+        assert!(position.y < HALF_HEIGHT + 0.1);
+    }
+
+    #[test]
+    fn test_stays_grounded_walking_down_slope() {
+        let mut app = app();
+        let angle = 20f32.to_radians();
+        spawn_ramp(&mut app, angle);
+        let surface = 3.0 * angle.tan() + 0.05 / angle.cos();
+        let character = spawn_character(&mut app, Vec3::new(4.0, surface + 1.2, 0.0));
+        run(&mut app, 30);
+        assert!(grounded(&app, character));
+
+        let start = position(&app, character);
+        input(&mut app, character).movement = Vec3::NEG_X * 3.0;
+        for _ in 0..45 {
+            app.update();
+            assert!(grounded(&app, character));
+        }
+        assert!(position(&app, character).y < start.y - 0.5);
+    }
+
+    /// Walks off a 2 m high platform ending at x = 1 and returns the frame
+    /// after leaving it.
+    fn walk_off_ledge() -> (App, Entity) {
+        let mut app = app();
+        spawn_box(
+            &mut app,
+            Vec3::new(-2.0, 1.0, 0.0),
+            Vec3::new(6.0, 2.0, 4.0),
+            Quat::IDENTITY,
+        );
+        let character = spawn_character(&mut app, Vec3::new(0.0, 2.0 + HALF_HEIGHT, 0.0));
+        run(&mut app, 30);
+        assert!(grounded(&app, character));
+
+        input(&mut app, character).movement = Vec3::X * 3.0;
+        while grounded(&app, character) {
+            app.update();
+        }
+        (app, character)
+    }
+
+    #[test]
+    fn test_coyote_time() {
+        let (mut app, character) = walk_off_ledge();
+        app.update();
+
+        input(&mut app, character).jump = Some(4.9);
+        app.update();
+        assert!(state(&app, character).velocity.y > 0.0);
+    }
+
+    #[test]
+    fn test_no_jump_after_coyote_time() {
+        let (mut app, character) = walk_off_ledge();
+        run(&mut app, 15);
+
+        input(&mut app, character).jump = Some(4.9);
+        app.update();
+        assert!(state(&app, character).velocity.y < 0.0);
+    }
+
+    #[test]
+    fn test_jump_buffered_until_landing() {
+        let mut app = app();
+        let character = spawn_character(&mut app, Vec3::new(0.0, HALF_HEIGHT + 0.05, 0.0));
+        app.update();
+        assert!(!grounded(&app, character));
+
+        input(&mut app, character).jump = Some(4.9);
+        let mut jumped = false;
+        for _ in 0..20 {
+            app.update();
+            jumped |= state(&app, character).velocity.y > 0.0;
+        }
+        assert!(jumped);
+    }
+
+    #[test]
+    fn test_jump_not_buffered_forever() {
+        let mut app = app();
+        let character = spawn_character(&mut app, Vec3::new(0.0, HALF_HEIGHT + 2.0, 0.0));
+        app.update();
+
+        input(&mut app, character).jump = Some(4.9);
+        for _ in 0..60 {
+            app.update();
+            assert!(state(&app, character).velocity.y <= 0.0);
+        }
+    }
+}
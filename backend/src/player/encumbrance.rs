@@ -0,0 +1,71 @@
+/// How weighed down a player is by their inventory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Encumbrance {
+    Light,
+    Burdened,
+    Overloaded,
+}
+
+impl Encumbrance {
+    /// Fraction of carry capacity at which the player becomes
+    /// [Encumbrance::Burdened].
+    pub const BURDENED: f32 = 0.5;
+    /// Fraction of carry capacity at which the player becomes
+    /// [Encumbrance::Overloaded].
+    pub const OVERLOADED: f32 = 1.0;
+
+    pub fn from_mass(mass: f32, carry_capacity: f32) -> Self {
+        match mass / carry_capacity {
+            ratio if ratio >= Self::OVERLOADED => Encumbrance::Overloaded,
+            ratio if ratio >= Self::BURDENED => Encumbrance::Burdened,
+            _ => Encumbrance::Light,
+        }
+    }
+
+    #[rustfmt::skip]
+    pub fn speed_multiplier(&self) -> f32 {
+        match self {
+            Encumbrance::Light      => 1.0,
+            Encumbrance::Burdened   => 0.75,
+            Encumbrance::Overloaded => 0.4,
+        }
+    }
+
+    #[rustfmt::skip]
+    pub fn jump_multiplier(&self) -> f32 {
+        match self {
+            Encumbrance::Light      => 1.0,
+            Encumbrance::Burdened   => 0.8,
+            Encumbrance::Overloaded => 0.5,
+        }
+    }
+
+    /// Overloaded players are too heavy to sprint.
+    pub fn can_sprint(&self) -> bool {
+        *self != Encumbrance::Overloaded
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Encumbrance::Light => "Light",
+            Encumbrance::Burdened => "Burdened",
+            Encumbrance::Overloaded => "Overloaded",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thresholds() {
+        assert_eq!(Encumbrance::from_mass(0.0, 100.0), Encumbrance::Light);
+        assert_eq!(Encumbrance::from_mass(49.9, 100.0), Encumbrance::Light);
+        assert_eq!(Encumbrance::from_mass(50.0, 100.0), Encumbrance::Burdened);
+        assert_eq!(
+            Encumbrance::from_mass(100.0, 100.0),
+            Encumbrance::Overloaded
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Detects what a player is standing on by casting a sphere the size of
+//! their feet down from them.
+
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::physics::{solid_filters, Layer, SurfaceMaterial};
+
+use super::controller::{move_characters, CharacterController};
+
+pub struct FloorPlugin;
+
+impl Plugin for FloorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, detect_floor.after(move_characters));
+    }
+}
+
+/// Looks for the floor below an entity standing on its origin. Only colliders
+/// on layers solid to the entity's [CollisionLayers] count, or just
+/// [Layer::World] without them, so its own collider is never mistaken for the
+/// floor. Characters do not need one, their controller keeps [Grounded] up to
+/// date.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct FloorDetector {
+    /// Radius of the feet, in m.
+    pub radius: f32,
+    /// How far below the feet the floor may be while still standing on it.
+    pub max_distance: f32,
+}
+
+impl Default for FloorDetector {
+    fn default() -> Self {
+        Self {
+            radius: 0.35,
+            max_distance: 0.1,
+        }
+    }
+}
+
+/// Present while a [FloorDetector] finds floor below it, or a character stands
+/// on something.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Grounded {
+    pub floor: Entity,
+    pub normal: Vec3,
+    pub material: SurfaceMaterial,
+    /// Gap between the feet and the floor, in m.
+    pub distance: f32,
+}
+
+/// What [detect_floor] reads and writes for each detector.
+type DetectorData<'a> = (
+    Entity,
+    &'a Transform,
+    &'a FloorDetector,
+    Option<&'a CollisionLayers>,
+    Option<&'a mut Grounded>,
+);
+
+pub fn detect_floor(
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    mut detectors: Query<DetectorData, Without<CharacterController>>,
+    materials: Query<&SurfaceMaterial>,
+) {
+    for (entity, transform, detector, layers, grounded) in detectors.iter_mut() {
+        let mask = layers.map_or(Layer::World.into(), solid_filters);
+        let feet = Collider::sphere(detector.radius);
+        let hit = spatial_query.cast_shape(
+            &feet,
+            transform.translation + Vec3::Y * detector.radius,
+            Quat::IDENTITY,
+            Direction3d::NEG_Y,
+            detector.max_distance,
+            true,
+            SpatialQueryFilter::from_mask(mask),
+        );
+
+        let floor = hit.map(|hit| Grounded {
+            floor: hit.entity,
+            normal: hit.normal1,
+            material: materials.get(hit.entity).copied().unwrap_or_default(),
+            distance: hit.time_of_impact,
+        });
+        match (floor, grounded) {
+            (Some(floor), Some(mut grounded)) => {
+                grounded.set_if_neq(floor);
+            }
+            (Some(floor), None) => {
+                commands.entity(entity).insert(floor);
+            }
+            (None, Some(_)) => {
+                commands.entity(entity).remove::<Grounded>();
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::testing::{run, spawn_box};
+
+    fn app() -> App {
+        let mut app = crate::physics::testing::app();
+        app.add_plugins(FloorPlugin);
+        app
+    }
+
+    /// A player standing on its origin with a capsule collider around it,
+    /// which the detector has to ignore.
+    fn spawn_player(app: &mut App, feet: Vec3) -> Entity {
+        app.world
+            .spawn((
+                RigidBody::Kinematic,
+                Collider::compound(vec![(
+                    Vec3::Y * 1.0,
+                    Quat::IDENTITY,
+                    Collider::capsule(1.2, 0.4),
+                )]),
+                Layer::Player.collision_layers(),
+                TransformBundle::from_transform(Transform::from_translation(feet)),
+                FloorDetector::default(),
+            ))
+            .id()
+    }
+
+    fn grounded(app: &App, player: Entity) -> Option<Grounded> {
+        app.world.get::<Grounded>(player).cloned()
+    }
+
+    #[test]
+    fn test_detects_floor() {
+        let mut app = app();
+        let floor = spawn_box(
+            &mut app,
+            Vec3::new(0.0, -0.5, 0.0),
+            Vec3::new(10.0, 1.0, 10.0),
+            Quat::IDENTITY,
+        );
+        let player = spawn_player(&mut app, Vec3::new(0.0, 0.05, 0.0));
+        run(&mut app, 3);
+
+        let grounded = grounded(&app, player).unwrap();
+        assert_eq!(grounded.floor, floor);
+        assert_eq!(grounded.material, SurfaceMaterial::Dirt);
+        assert!((grounded.distance - 0.05).abs() < 0.01);
+        assert!(grounded.normal.dot(Vec3::Y) > 0.99);
+    }
+
+    #[test]
+    fn test_reports_material_and_slope() {
+        let mut app = app();
+        let slope = Quat::from_rotation_z(0.3);
+        let floor = spawn_box(
+            &mut app,
+            Vec3::new(0.0, -0.5, 0.0),
+            Vec3::new(10.0, 1.0, 10.0),
+            slope,
+        );
+        app.world.entity_mut(floor).insert(SurfaceMaterial::Metal);
+        let player = spawn_player(&mut app, Vec3::new(0.0, 0.05, 0.0));
+        run(&mut app, 3);
+
+        let grounded = grounded(&app, player).unwrap();
+        assert_eq!(grounded.material, SurfaceMaterial::Metal);
+        assert!(grounded.normal.dot(slope * Vec3::Y) > 0.99);
+    }
+
+    #[test]
+    fn test_ignores_own_collider() {
+        let mut app = app();
+        let player = spawn_player(&mut app, Vec3::new(0.0, 5.0, 0.0));
+        run(&mut app, 3);
+
+        assert_eq!(grounded(&app, player), None);
+    }
+
+    #[test]
+    fn test_stops_being_grounded() {
+        let mut app = app();
+        spawn_box(
+            &mut app,
+            Vec3::new(0.0, -0.5, 0.0),
+            Vec3::new(10.0, 1.0, 10.0),
+            Quat::IDENTITY,
+        );
+        let player = spawn_player(&mut app, Vec3::new(0.0, 0.02, 0.0));
+        run(&mut app, 3);
+        assert!(grounded(&app, player).is_some());
+
+        app.world
+            .get_mut::<Transform>(player)
+            .unwrap()
+            .translation
+            .y = 1.0;
+        run(&mut app, 3);
+        assert_eq!(grounded(&app, player), None);
+    }
+}
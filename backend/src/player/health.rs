@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+
+/// Landing slower than this, in m/s, does no damage.
+pub const SAFE_FALL_SPEED: f32 = 8.0;
+/// Damage per m/s of landing speed above [SAFE_FALL_SPEED].
+pub const FALL_DAMAGE_PER_SPEED: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DamageType {
+    Fall,
+    Burn,
+    Crush,
+    Electrical,
+}
+
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Health {
+    pub max: f32,
+    pub current: f32,
+    /// Health regained per second once regeneration kicks in.
+    pub regeneration: f32,
+    /// Seconds after taking damage before regeneration starts.
+    pub regeneration_delay: f32,
+    since_damage: f32,
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self::new(100.0)
+    }
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Health {
+            max,
+            current: max,
+            regeneration: 1.0,
+            regeneration_delay: 5.0,
+            since_damage: 0.0,
+        }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.0
+    }
+
+    /// Returns true if this damage killed the entity.
+    pub fn damage(&mut self, amount: f32) -> bool {
+        if self.is_dead() {
+            return false;
+        }
+
+        self.current = (self.current - amount).max(0.0);
+        self.since_damage = 0.0;
+        self.is_dead()
+    }
+
+    pub fn heal(&mut self, amount: f32) {
+        if !self.is_dead() {
+            self.current = (self.current + amount).min(self.max);
+        }
+    }
+
+    pub fn regenerate(&mut self, delta_seconds: f32) {
+        self.since_damage += delta_seconds;
+        if self.since_damage >= self.regeneration_delay {
+            self.heal(self.regeneration * delta_seconds);
+        }
+    }
+
+    /// Brings a dead entity back at full health.
+    pub fn revive(&mut self) {
+        self.current = self.max;
+        self.since_damage = 0.0;
+    }
+}
+
+/// Damage taken from landing at `vertical_velocity`. Only downward velocity
+/// (negative y) hurts.
+pub fn fall_damage(vertical_velocity: f32) -> Option<f32> {
+    let speed = -vertical_velocity;
+    match speed > SAFE_FALL_SPEED {
+        true => Some((speed - SAFE_FALL_SPEED) * FALL_DAMAGE_PER_SPEED),
+        false => None,
+    }
+}
+
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub amount: f32,
+    pub damage_type: DamageType,
+}
+
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct DeathEvent {
+    pub entity: Entity,
+    pub cause: DamageType,
+}
+
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageEvent>()
+            .add_event::<DeathEvent>()
+            .add_systems(Update, (apply_damage, regenerate_health));
+    }
+}
+
+pub fn apply_damage(
+    mut damage_events: EventReader<DamageEvent>,
+    mut deaths: EventWriter<DeathEvent>,
+    mut health: Query<&mut Health>,
+) {
+    for event in damage_events.read() {
+        let Ok(mut health) = health.get_mut(event.target) else {
+            continue;
+        };
+
+        if health.damage(event.amount) {
+            deaths.send(DeathEvent {
+                entity: event.target,
+                cause: event.damage_type,
+            });
+        }
+    }
+}
+
+pub fn regenerate_health(time: Res<Time>, mut health: Query<&mut Health>) {
+    for mut health in health.iter_mut() {
+        health.regenerate(time.delta_seconds());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_damage_and_death() {
+        let mut health = Health::new(50.0);
+        assert!(!health.damage(20.0));
+        assert_eq!(health.current, 30.0);
+        assert!(health.damage(40.0));
+        assert_eq!(health.current, 0.0);
+        // Already dead, so this does not count as another death.
+        assert!(!health.damage(10.0));
+    }
+
+    #[test]
+    fn test_regeneration_waits_for_delay() {
+        let mut health = Health::new(100.0);
+        health.damage(50.0);
+
+        health.regenerate(health.regeneration_delay / 2.0);
+        assert_eq!(health.current, 50.0);
+
+        health.regenerate(health.regeneration_delay);
+        assert!(health.current > 50.0);
+    }
+
+    #[test]
+    fn test_dead_do_not_regenerate() {
+        let mut health = Health::new(10.0);
+        health.damage(10.0);
+        health.regenerate(100.0);
+        assert!(health.is_dead());
+
+        health.revive();
+        assert_eq!(health.current, 10.0);
+    }
+
+    #[test]
+    fn test_fall_damage() {
+        assert_eq!(fall_damage(-SAFE_FALL_SPEED), None);
+        assert_eq!(fall_damage(20.0), None);
+        assert_eq!(
+            fall_damage(-SAFE_FALL_SPEED - 2.0),
+            Some(2.0 * FALL_DAMAGE_PER_SPEED)
+        );
+    }
+}
@@ -0,0 +1,162 @@
+//! Quick slots holding references to items in the player's inventory.
+
+use bevy::prelude::*;
+
+use crate::iams::inventory::ItemKey;
+use crate::iams::Inventory;
+use crate::items::tool::Tool;
+use crate::items::{Item, ItemAction};
+
+pub const HOTBAR_SLOTS: usize = 9;
+
+/// Each slot points at an item still in the inventory. Items are never held
+/// by the hotbar itself, so dropping or using up an item empties its slot.
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct Hotbar {
+    pub slots: [Option<ItemKey>; HOTBAR_SLOTS],
+    /// The slot whose item is in hand.
+    pub selected: usize,
+}
+
+impl Hotbar {
+    /// Puts `key` in `slot`, taking it out of any other slot it was in.
+    pub fn assign(&mut self, slot: usize, key: ItemKey) {
+        if slot >= HOTBAR_SLOTS {
+            return;
+        }
+        for other in self.slots.iter_mut() {
+            if *other == Some(key) {
+                *other = None;
+            }
+        }
+        self.slots[slot] = Some(key);
+    }
+
+    pub fn clear(&mut self, slot: usize) {
+        if let Some(slot) = self.slots.get_mut(slot) {
+            *slot = None;
+        }
+    }
+
+    pub fn select(&mut self, slot: usize) {
+        if slot < HOTBAR_SLOTS {
+            self.selected = slot;
+        }
+    }
+
+    /// Moves the selection `steps` slots along, wrapping round at the ends.
+    pub fn scroll(&mut self, steps: i32) {
+        let slots = HOTBAR_SLOTS as i32;
+        self.selected = (self.selected as i32 + steps).rem_euclid(slots) as usize;
+    }
+
+    /// The item in hand, if the selected slot holds one still in `inventory`.
+    pub fn held<'a>(&self, inventory: &'a Inventory) -> Option<&'a dyn Item> {
+        inventory.get(self.slots[self.selected]?)
+    }
+
+    /// The item in hand, if it is a tool.
+    pub fn held_tool<'a>(&self, inventory: &'a Inventory) -> Option<&'a Tool> {
+        self.held(inventory)?.as_any().downcast_ref()
+    }
+
+    /// What using the item in hand does.
+    pub fn held_action(&self, inventory: &Inventory) -> Option<ItemAction> {
+        self.held(inventory)?.action()
+    }
+
+    /// Empties slots whose item is no longer in `inventory`.
+    pub fn forget_missing(&mut self, inventory: &Inventory) {
+        for slot in self.slots.iter_mut() {
+            if slot.is_some_and(|key| !inventory.contains(key)) {
+                *slot = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buildings::{BuildingItem, BuildingKind};
+    use crate::items::spare_part::SparePart;
+    use crate::items::tool::ToolKind;
+
+    fn key(id: usize) -> ItemKey {
+        ItemKey {
+            type_name: "Spare Part",
+            id,
+        }
+    }
+
+    #[test]
+    fn test_assign_moves_between_slots() {
+        let mut hotbar = Hotbar::default();
+        hotbar.assign(0, key(1));
+        hotbar.assign(3, key(1));
+
+        assert_eq!(hotbar.slots[0], None);
+        assert_eq!(hotbar.slots[3], Some(key(1)));
+
+        hotbar.assign(HOTBAR_SLOTS, key(2));
+        assert!(!hotbar.slots.contains(&Some(key(2))));
+    }
+
+    #[test]
+    fn test_scroll_wraps() {
+        let mut hotbar = Hotbar::default();
+        hotbar.scroll(-1);
+        assert_eq!(hotbar.selected, HOTBAR_SLOTS - 1);
+        hotbar.scroll(2);
+        assert_eq!(hotbar.selected, 1);
+
+        hotbar.select(4);
+        assert_eq!(hotbar.selected, 4);
+        hotbar.select(HOTBAR_SLOTS);
+        assert_eq!(hotbar.selected, 4);
+    }
+
+    #[test]
+    fn test_held_action() {
+        let mut inventory = Inventory::default();
+        inventory.add(SparePart::new(1, 1));
+        inventory.add(Tool::new(ToolKind::Pickaxe, 0));
+        inventory.add(BuildingItem::new(BuildingKind::Furnace, 1, 0));
+
+        let mut hotbar = Hotbar::default();
+        assert_eq!(hotbar.held_action(&inventory), None);
+
+        hotbar.assign(0, key(1));
+        assert!(hotbar.held(&inventory).is_some());
+        assert_eq!(hotbar.held_action(&inventory), None);
+
+        hotbar.assign(1, ItemKey::of(&Tool::new(ToolKind::Pickaxe, 0)));
+        hotbar.assign(
+            2,
+            ItemKey::of(&BuildingItem::new(BuildingKind::Furnace, 1, 0)),
+        );
+        hotbar.select(1);
+        assert_eq!(hotbar.held_action(&inventory), Some(ItemAction::Mine));
+        assert_eq!(
+            hotbar.held_tool(&inventory).unwrap().kind,
+            ToolKind::Pickaxe
+        );
+        hotbar.select(2);
+        assert_eq!(hotbar.held_action(&inventory), Some(ItemAction::Place));
+        assert!(hotbar.held_tool(&inventory).is_none());
+    }
+
+    #[test]
+    fn test_forget_missing() {
+        let mut inventory = Inventory::default();
+        inventory.add(SparePart::new(1, 1));
+
+        let mut hotbar = Hotbar::default();
+        hotbar.assign(0, key(1));
+        hotbar.assign(1, key(2));
+        hotbar.forget_missing(&inventory);
+
+        assert_eq!(hotbar.slots[0], Some(key(1)));
+        assert_eq!(hotbar.slots[1], None);
+    }
+}
@@ -1,2 +1,8 @@
-mod health;
-pub mod player;
+pub mod controller;
+pub mod encumbrance;
+pub mod floor;
+pub mod health;
+pub mod hotbar;
+pub mod profile;
+pub mod skills;
+pub mod stamina;
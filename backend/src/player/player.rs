@@ -1,7 +0,0 @@
-use bevy::prelude::*;
-
-#[derive(Component)]
-pub struct PlayerLogic {
-    pub name: String,
-    pub age: u32,
-}
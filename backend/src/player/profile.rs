@@ -0,0 +1,148 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::skills::{grant_experience, ExperienceGained, Skills};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PlayerId(pub u64);
+
+/// Running totals of what a player has done.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerStats {
+    /// In kg.
+    pub ore_mined: f32,
+    pub items_crafted: u64,
+    pub machines_repaired: u64,
+    pub deaths: u64,
+}
+
+#[derive(Debug)]
+pub enum ProfileError {
+    Io(std::io::Error),
+    Serialize(ron::Error),
+    Deserialize(ron::error::SpannedError),
+}
+
+/// A player's profile. Holds everything about a player that is not tied to
+/// rendering or the world, so a headless server can manage players without
+/// the frontend.
+#[derive(Component, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerLogic {
+    pub id: PlayerId,
+    pub name: String,
+    pub stats: PlayerStats,
+    pub skills: Skills,
+    pub unlocked_recipes: BTreeSet<String>,
+    #[serde(default)]
+    pub unlocked_machines: BTreeSet<String>,
+    /// Ids of finished [Technology](crate::research::Technology)s.
+    #[serde(default)]
+    pub researched: BTreeSet<String>,
+    /// Total seconds played.
+    pub playtime: f64,
+}
+
+impl PlayerLogic {
+    pub fn new(id: PlayerId, name: impl Into<String>) -> Self {
+        PlayerLogic {
+            id,
+            name: name.into(),
+            stats: PlayerStats::default(),
+            skills: Skills::default(),
+            unlocked_recipes: BTreeSet::new(),
+            unlocked_machines: BTreeSet::new(),
+            researched: BTreeSet::new(),
+            playtime: 0.0,
+        }
+    }
+
+    pub fn unlock_recipe(&mut self, recipe: impl Into<String>) -> bool {
+        self.unlocked_recipes.insert(recipe.into())
+    }
+
+    pub fn has_recipe(&self, recipe: &str) -> bool {
+        self.unlocked_recipes.contains(recipe)
+    }
+
+    pub fn to_ron(&self) -> Result<String, ProfileError> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(ProfileError::Serialize)
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, ProfileError> {
+        ron::from_str(text).map_err(ProfileError::Deserialize)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ProfileError> {
+        std::fs::write(path, self.to_ron()?).map_err(ProfileError::Io)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ProfileError> {
+        let text = std::fs::read_to_string(path).map_err(ProfileError::Io)?;
+        Self::from_ron(&text)
+    }
+}
+
+/// Hands out [PlayerId]s that are unique for this world.
+#[derive(Resource, Debug, Default)]
+pub struct PlayerIds {
+    next: u64,
+}
+
+impl PlayerIds {
+    pub fn next_id(&mut self) -> PlayerId {
+        self.next += 1;
+        PlayerId(self.next)
+    }
+
+    /// Makes sure ids handed out later do not clash with a loaded profile.
+    pub fn reserve(&mut self, id: PlayerId) {
+        self.next = self.next.max(id.0);
+    }
+}
+
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerIds>()
+            .add_event::<ExperienceGained>()
+            .add_systems(Update, (track_playtime, grant_experience));
+    }
+}
+
+pub fn track_playtime(time: Res<Time>, mut players: Query<&mut PlayerLogic>) {
+    for mut player in players.iter_mut() {
+        player.playtime += time.delta_seconds_f64();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ron_round_trip() {
+        let mut player = PlayerLogic::new(PlayerId(7), "gman");
+        player.stats.deaths = 3;
+        player.unlock_recipe("iron_plate");
+        player.playtime = 12.5;
+
+        let text = player.to_ron().unwrap();
+        assert_eq!(PlayerLogic::from_ron(&text).unwrap(), player);
+    }
+
+    #[test]
+    fn test_ids_are_unique() {
+        let mut ids = PlayerIds::default();
+        let first = ids.next_id();
+        ids.reserve(PlayerId(10));
+        let second = ids.next_id();
+
+        assert_ne!(first, second);
+        assert_eq!(second, PlayerId(11));
+    }
+}
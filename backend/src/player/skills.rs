@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::profile::PlayerLogic;
+
+pub const MAX_LEVEL: u32 = 20;
+
+/// Experience granted per kg of ore mined.
+pub const MINING_EXPERIENCE: u32 = 2;
+/// Experience granted per kg of ore smelted.
+pub const SMELTING_EXPERIENCE: u32 = 1;
+/// Experience granted per machine repaired.
+pub const REPAIR_EXPERIENCE: u32 = 25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Skill {
+    Mining,
+    Refining,
+    Engineering,
+}
+
+/// Total experience needed to reach `level`.
+pub fn experience_for_level(level: u32) -> u32 {
+    100 * level * level
+}
+
+/// Experience a player has earned in each [Skill].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Skills {
+    experience: BTreeMap<Skill, u32>,
+}
+
+impl Skills {
+    pub fn experience(&self, skill: Skill) -> u32 {
+        self.experience.get(&skill).copied().unwrap_or(0)
+    }
+
+    /// Returns the new level if this experience caused a level up.
+    pub fn add_experience(&mut self, skill: Skill, amount: u32) -> Option<u32> {
+        let before = self.level(skill);
+        let experience = self.experience.entry(skill).or_insert(0);
+        *experience = experience.saturating_add(amount);
+
+        let after = self.level(skill);
+        (after > before).then_some(after)
+    }
+
+    pub fn level(&self, skill: Skill) -> u32 {
+        let experience = self.experience(skill);
+        (1..=MAX_LEVEL)
+            .take_while(|level| experience_for_level(*level) <= experience)
+            .last()
+            .unwrap_or(0)
+    }
+
+    /// Multiplier on what an action yields, +2% per level.
+    pub fn yield_multiplier(&self, skill: Skill) -> f32 {
+        1.0 + 0.02 * self.level(skill) as f32
+    }
+
+    /// Multiplier on how fast an action is done, +2.5% per level.
+    pub fn speed_multiplier(&self, skill: Skill) -> f32 {
+        1.0 + 0.025 * self.level(skill) as f32
+    }
+
+    /// Multiplier on the odds of something going wrong, -3% per level.
+    pub fn failure_odds_multiplier(&self, skill: Skill) -> f32 {
+        1.0 - 0.03 * self.level(skill) as f32
+    }
+
+    /// Purity of freshly mined ore, improved by [Skill::Mining].
+    pub fn mined_purity(&self, purity: f32) -> f32 {
+        (purity * self.yield_multiplier(Skill::Mining)).min(1.0)
+    }
+}
+
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct ExperienceGained {
+    pub player: Entity,
+    pub skill: Skill,
+    pub amount: u32,
+}
+
+pub fn grant_experience(
+    mut gained: EventReader<ExperienceGained>,
+    mut players: Query<&mut PlayerLogic>,
+) {
+    for event in gained.read() {
+        let Ok(mut player) = players.get_mut(event.player) else {
+            continue;
+        };
+
+        if let Some(level) = player.skills.add_experience(event.skill, event.amount) {
+            log::info!("{} reached {:?} level {}", player.name, event.skill, level);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels() {
+        let mut skills = Skills::default();
+        assert_eq!(skills.level(Skill::Mining), 0);
+
+        assert_eq!(skills.add_experience(Skill::Mining, 99), None);
+        assert_eq!(skills.add_experience(Skill::Mining, 1), Some(1));
+        assert_eq!(skills.add_experience(Skill::Mining, 300), Some(2));
+        assert_eq!(skills.level(Skill::Refining), 0);
+
+        skills.add_experience(Skill::Mining, u32::MAX);
+        assert_eq!(skills.level(Skill::Mining), MAX_LEVEL);
+    }
+
+    #[test]
+    fn test_modifiers() {
+        let mut skills = Skills::default();
+        assert_eq!(skills.yield_multiplier(Skill::Mining), 1.0);
+        assert_eq!(skills.failure_odds_multiplier(Skill::Engineering), 1.0);
+
+        skills.add_experience(Skill::Mining, experience_for_level(MAX_LEVEL));
+        skills.add_experience(Skill::Engineering, experience_for_level(10));
+        assert!(skills.speed_multiplier(Skill::Mining) > 1.0);
+        assert!(skills.failure_odds_multiplier(Skill::Engineering) < 1.0);
+        assert_eq!(skills.mined_purity(0.9), 1.0);
+        assert!(skills.mined_purity(0.5) > 0.5);
+    }
+}
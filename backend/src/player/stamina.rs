@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Stamina {
+    pub max: f32,
+    pub current: f32,
+    /// Stamina spent per second of sprinting.
+    pub sprint_cost: f32,
+    /// Stamina regained per second while resting.
+    pub regeneration: f32,
+    /// Set when stamina runs out. Sprinting is blocked until stamina is back
+    /// above [Stamina::RECOVERED].
+    pub exhausted: bool,
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Self {
+            max: 100.0,
+            current: 100.0,
+            sprint_cost: 15.0,
+            regeneration: 10.0,
+            exhausted: false,
+        }
+    }
+}
+
+impl Stamina {
+    /// Fraction of [Stamina::max] needed to stop being exhausted.
+    pub const RECOVERED: f32 = 0.25;
+
+    /// Spends stamina for a one-off action such as a pickaxe swing. Returns
+    /// false, spending nothing, if there is not enough.
+    pub fn spend(&mut self, amount: f32) -> bool {
+        if self.exhausted || self.current < amount {
+            return false;
+        }
+
+        self.current -= amount;
+        self.exhausted = self.current <= 0.0;
+        true
+    }
+
+    /// Drains stamina while `sprinting` and regenerates it otherwise. Returns
+    /// whether the player is actually able to sprint this tick.
+    pub fn tick(&mut self, delta_seconds: f32, sprinting: bool) -> bool {
+        if sprinting && !self.exhausted {
+            self.current = (self.current - self.sprint_cost * delta_seconds).max(0.0);
+            self.exhausted = self.current <= 0.0;
+            return !self.exhausted;
+        }
+
+        self.current = (self.current + self.regeneration * delta_seconds).min(self.max);
+        if self.current >= self.max * Self::RECOVERED {
+            self.exhausted = false;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sprinting_drains_until_exhausted() {
+        let mut stamina = Stamina::default();
+        let seconds = stamina.max / stamina.sprint_cost;
+
+        assert!(stamina.tick(seconds / 2.0, true));
+        assert!(!stamina.tick(seconds, true));
+        assert!(stamina.exhausted);
+        assert_eq!(stamina.current, 0.0);
+    }
+
+    #[test]
+    fn test_exhaustion_recovers() {
+        let mut stamina = Stamina {
+            current: 0.0,
+            exhausted: true,
+            ..Default::default()
+        };
+
+        stamina.tick(0.1, false);
+        assert!(!stamina.tick(0.1, true));
+        assert!(!stamina.spend(1.0));
+
+        stamina.tick(stamina.max / stamina.regeneration, false);
+        assert!(!stamina.exhausted);
+        assert!(stamina.spend(1.0));
+    }
+}
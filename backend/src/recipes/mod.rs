@@ -0,0 +1,6 @@
+//! What machines make out of what. Recipes are defined in data (see
+//! [RecipeBook::from_ron]) and unlocked by research.
+
+pub mod recipe_book;
+
+pub use recipe_book::{Recipe, RecipeBook, RecipeError, HAND};
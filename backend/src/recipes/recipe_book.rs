@@ -0,0 +1,232 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::research::ItemCost;
+
+/// Machine id of recipes the player makes by hand.
+pub const HAND: &str = "hand";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recipe {
+    pub id: String,
+    pub name: String,
+    /// Id of the machine that makes it, as unlocked by research.
+    pub machine: String,
+    /// Seconds one batch takes.
+    pub time: f32,
+    #[serde(default)]
+    pub inputs: Vec<ItemCost>,
+    pub outputs: Vec<ItemCost>,
+    /// Name of the tool the recipe brings back to full durability. The tool
+    /// is kept rather than a new one made, so it is not among the inputs.
+    #[serde(default)]
+    pub repairs: Option<String>,
+}
+
+impl Recipe {
+    pub fn uses(&self, item: &str) -> bool {
+        self.inputs.iter().any(|input| input.item == item)
+    }
+
+    pub fn produces(&self, item: &str) -> bool {
+        self.outputs.iter().any(|output| output.item == item)
+    }
+}
+
+/// Problems with a recipe book definition, found when it is loaded.
+#[derive(Debug, PartialEq)]
+pub enum RecipeError {
+    Parse(ron::error::SpannedError),
+    Duplicate(String),
+    /// A recipe that makes nothing.
+    NoOutputs(String),
+    /// A recipe that takes no time, or less.
+    NoTime(String),
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct RecipeBook {
+    recipes: BTreeMap<String, Recipe>,
+}
+
+impl RecipeBook {
+    pub fn new(recipes: Vec<Recipe>) -> Result<Self, RecipeError> {
+        let mut book = RecipeBook::default();
+        for recipe in recipes {
+            if recipe.outputs.is_empty() {
+                return Err(RecipeError::NoOutputs(recipe.id));
+            }
+            if recipe.time <= 0.0 {
+                return Err(RecipeError::NoTime(recipe.id));
+            }
+            if book.recipes.contains_key(&recipe.id) {
+                return Err(RecipeError::Duplicate(recipe.id));
+            }
+            book.recipes.insert(recipe.id.clone(), recipe);
+        }
+
+        Ok(book)
+    }
+
+    /// Loads a list of [Recipe]s written in RON.
+    pub fn from_ron(text: &str) -> Result<Self, RecipeError> {
+        let recipes = ron::from_str(text).map_err(RecipeError::Parse)?;
+        Self::new(recipes)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Recipe> {
+        self.recipes.get(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Recipe> {
+        self.recipes.values()
+    }
+
+    /// Recipes taking the item called `item` as an input.
+    pub fn used_in<'a>(&'a self, item: &'a str) -> impl Iterator<Item = &'a Recipe> {
+        self.iter().filter(move |recipe| recipe.uses(item))
+    }
+
+    /// Recipes making the item called `item`. Repairs only give back the
+    /// tool they were handed, so they do not count.
+    pub fn produced_by<'a>(&'a self, item: &'a str) -> impl Iterator<Item = &'a Recipe> {
+        self.iter()
+            .filter(move |recipe| recipe.repairs.is_none() && recipe.produces(item))
+    }
+
+    /// The recipe repairing tools called `tool`.
+    pub fn repairing(&self, tool: &str) -> Option<&Recipe> {
+        self.iter()
+            .find(|recipe| recipe.repairs.as_deref() == Some(tool))
+    }
+
+    /// Recipes the machine with id `machine` can make.
+    pub fn for_machine<'a>(&'a self, machine: &'a str) -> impl Iterator<Item = &'a Recipe> {
+        self.iter().filter(move |recipe| recipe.machine == machine)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+
+    /// Smelting both ores and washing iron ore, for tests that need recipes.
+    pub const RECIPES: &str = r#"[
+        (
+            id: "copper_ingot",
+            name: "Copper Ingot",
+            machine: "furnace",
+            time: 4.0,
+            inputs: [(item: "Copper Ore", amount: 2.0)],
+            outputs: [(item: "Copper Ingot", amount: 1.0)],
+        ),
+        (
+            id: "iron_ingot",
+            name: "Iron Ingot",
+            machine: "furnace",
+            time: 4.0,
+            inputs: [(item: "Iron Ore", amount: 2.0)],
+            outputs: [(item: "Iron Ingot", amount: 1.0)],
+        ),
+        (
+            id: "washed_iron_ore",
+            name: "Washed Iron Ore",
+            machine: "ore_washer",
+            time: 2.0,
+            inputs: [(item: "Iron Ore", amount: 1.0), (item: "Water", amount: 0.5)],
+            outputs: [(item: "Iron Ore", amount: 0.9)],
+        ),
+    ]"#;
+
+    pub fn recipes() -> RecipeBook {
+        RecipeBook::from_ron(RECIPES).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::recipes;
+    use super::*;
+
+    fn ids<'a>(recipes: impl Iterator<Item = &'a Recipe>) -> Vec<&'a str> {
+        recipes.map(|recipe| recipe.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_from_ron() {
+        let book = recipes();
+        assert_eq!(book.get("iron_ingot").unwrap().inputs[0].item, "Iron Ore");
+    }
+
+    #[test]
+    fn test_lookups() {
+        let book = recipes();
+
+        assert_eq!(
+            ids(book.used_in("Iron Ore")),
+            ["iron_ingot", "washed_iron_ore"]
+        );
+        assert_eq!(ids(book.produced_by("Iron Ore")), ["washed_iron_ore"]);
+        assert_eq!(ids(book.used_in("Iron Ingot")), Vec::<&str>::new());
+        assert_eq!(
+            ids(book.for_machine("furnace")),
+            ["copper_ingot", "iron_ingot"]
+        );
+    }
+
+    #[test]
+    fn test_repairs_do_not_produce() {
+        let book = RecipeBook::from_ron(
+            r#"[
+                (
+                    id: "repair_drill",
+                    name: "Repair Drill",
+                    machine: "hand",
+                    time: 4.0,
+                    inputs: [(item: "Spare Part", amount: 3.0)],
+                    outputs: [(item: "Drill", amount: 1.0)],
+                    repairs: Some("Drill"),
+                ),
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(ids(book.produced_by("Drill")), Vec::<&str>::new());
+        assert_eq!(book.repairing("Drill").unwrap().id, "repair_drill");
+    }
+
+    #[test]
+    fn test_rejects_bad_recipes() {
+        let recipe = Recipe {
+            id: "nothing".to_string(),
+            name: "Nothing".to_string(),
+            machine: "furnace".to_string(),
+            time: 1.0,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            repairs: None,
+        };
+        assert_eq!(
+            RecipeBook::new(vec![recipe.clone()]).unwrap_err(),
+            RecipeError::NoOutputs("nothing".to_string())
+        );
+
+        let mut recipe = recipe;
+        recipe.outputs.push(ItemCost {
+            item: "Iron Ingot".to_string(),
+            amount: 1.0,
+        });
+        assert_eq!(
+            RecipeBook::new(vec![recipe.clone(), recipe.clone()]).unwrap_err(),
+            RecipeError::Duplicate("nothing".to_string())
+        );
+
+        recipe.time = 0.0;
+        assert_eq!(
+            RecipeBook::new(vec![recipe]).unwrap_err(),
+            RecipeError::NoTime("nothing".to_string())
+        );
+    }
+}
@@ -0,0 +1,7 @@
+//! Research unlocks recipes and machines. Technologies are defined in data
+//! (see [TechTree::from_ron]) and are paid for with items from an
+//! [Inventory](crate::iams::Inventory), either the player's or a lab's.
+
+pub mod tech_tree;
+
+pub use tech_tree::{ItemCost, ResearchError, TechTree, TechTreeError, Technology};
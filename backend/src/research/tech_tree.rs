@@ -0,0 +1,417 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::buildings::BuildingKind;
+use crate::iams::Inventory;
+use crate::player::profile::PlayerLogic;
+use crate::recipes::RecipeBook;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemCost {
+    /// [Item::type_name](crate::items::Item::type_name) of the item to pay
+    /// with.
+    pub item: String,
+    pub amount: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Technology {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub prerequisites: Vec<String>,
+    #[serde(default)]
+    pub cost: Vec<ItemCost>,
+    #[serde(default)]
+    pub recipes: Vec<String>,
+    #[serde(default)]
+    pub machines: Vec<String>,
+}
+
+/// Problems with a tech tree definition, found when it is loaded.
+#[derive(Debug, PartialEq)]
+pub enum TechTreeError {
+    Parse(ron::error::SpannedError),
+    Duplicate(String),
+    DanglingPrerequisite {
+        technology: String,
+        prerequisite: String,
+    },
+    /// The ids of the technologies forming the cycle, in order.
+    Cycle(Vec<String>),
+    /// A recipe id that is not in the [RecipeBook].
+    UnknownRecipe {
+        technology: String,
+        recipe: String,
+    },
+    /// A machine id that is no [BuildingKind::id].
+    UnknownMachine {
+        technology: String,
+        machine: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResearchError {
+    UnknownTechnology(String),
+    AlreadyResearched,
+    MissingPrerequisite(String),
+    MissingItems {
+        item: String,
+        needed: f32,
+        available: f32,
+    },
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct TechTree {
+    technologies: BTreeMap<String, Technology>,
+}
+
+impl TechTree {
+    /// Checks that every prerequisite, recipe and machine the technologies
+    /// name exists, and that no technology depends on itself.
+    pub fn new(technologies: Vec<Technology>, recipes: &RecipeBook) -> Result<Self, TechTreeError> {
+        let mut tree = TechTree::default();
+        for technology in technologies {
+            if tree.technologies.contains_key(&technology.id) {
+                return Err(TechTreeError::Duplicate(technology.id));
+            }
+            tree.technologies.insert(technology.id.clone(), technology);
+        }
+
+        tree.validate(recipes)?;
+        Ok(tree)
+    }
+
+    /// Loads a list of [Technology]s written in RON.
+    pub fn from_ron(text: &str, recipes: &RecipeBook) -> Result<Self, TechTreeError> {
+        let technologies = ron::from_str(text).map_err(TechTreeError::Parse)?;
+        Self::new(technologies, recipes)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Technology> {
+        self.technologies.get(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Technology> {
+        self.technologies.values()
+    }
+
+    /// Technologies the player has not researched yet but has all the
+    /// prerequisites for.
+    pub fn available<'a>(
+        &'a self,
+        player: &'a PlayerLogic,
+    ) -> impl Iterator<Item = &'a Technology> {
+        self.iter().filter(|technology| {
+            !player.researched.contains(&technology.id)
+                && technology
+                    .prerequisites
+                    .iter()
+                    .all(|prerequisite| player.researched.contains(prerequisite))
+        })
+    }
+
+    pub fn can_research(
+        &self,
+        id: &str,
+        player: &PlayerLogic,
+        inventory: &Inventory,
+    ) -> Result<&Technology, ResearchError> {
+        let technology = self
+            .get(id)
+            .ok_or_else(|| ResearchError::UnknownTechnology(id.to_string()))?;
+
+        if player.researched.contains(id) {
+            return Err(ResearchError::AlreadyResearched);
+        }
+
+        if let Some(missing) = technology
+            .prerequisites
+            .iter()
+            .find(|prerequisite| !player.researched.contains(*prerequisite))
+        {
+            return Err(ResearchError::MissingPrerequisite(missing.clone()));
+        }
+
+        for cost in technology.cost.iter() {
+            let available = inventory.amount_of(&cost.item);
+            if available < cost.amount {
+                return Err(ResearchError::MissingItems {
+                    item: cost.item.clone(),
+                    needed: cost.amount,
+                    available,
+                });
+            }
+        }
+
+        Ok(technology)
+    }
+
+    /// Whether the player may build `machine`. Machines no technology unlocks
+    /// can be built from the start.
+    pub fn can_build(&self, machine: &str, player: &PlayerLogic) -> bool {
+        player.unlocked_machines.contains(machine)
+            || !self
+                .iter()
+                .any(|technology| technology.machines.iter().any(|id| id == machine))
+    }
+
+    /// Pays for a technology out of `inventory`, the player's or a lab's, and
+    /// unlocks its recipes and machines for the player.
+    pub fn research(
+        &self,
+        id: &str,
+        player: &mut PlayerLogic,
+        inventory: &mut Inventory,
+    ) -> Result<(), ResearchError> {
+        let technology = self.can_research(id, player, inventory)?;
+
+        for cost in technology.cost.iter() {
+            inventory.take_by_name(&cost.item, cost.amount);
+        }
+
+        player.researched.insert(technology.id.clone());
+        player
+            .unlocked_recipes
+            .extend(technology.recipes.iter().cloned());
+        player
+            .unlocked_machines
+            .extend(technology.machines.iter().cloned());
+
+        Ok(())
+    }
+
+    fn validate(&self, recipes: &RecipeBook) -> Result<(), TechTreeError> {
+        for technology in self.technologies.values() {
+            if let Some(prerequisite) = technology
+                .prerequisites
+                .iter()
+                .find(|prerequisite| !self.technologies.contains_key(*prerequisite))
+            {
+                return Err(TechTreeError::DanglingPrerequisite {
+                    technology: technology.id.clone(),
+                    prerequisite: prerequisite.clone(),
+                });
+            }
+            if let Some(recipe) = technology
+                .recipes
+                .iter()
+                .find(|recipe| recipes.get(recipe).is_none())
+            {
+                return Err(TechTreeError::UnknownRecipe {
+                    technology: technology.id.clone(),
+                    recipe: recipe.clone(),
+                });
+            }
+            if let Some(machine) = technology
+                .machines
+                .iter()
+                .find(|machine| BuildingKind::ALL.iter().all(|kind| kind.id() != *machine))
+            {
+                return Err(TechTreeError::UnknownMachine {
+                    technology: technology.id.clone(),
+                    machine: machine.clone(),
+                });
+            }
+        }
+
+        let mut finished = Vec::new();
+        for id in self.technologies.keys() {
+            self.find_cycle(id, &mut Vec::new(), &mut finished)?;
+        }
+
+        Ok(())
+    }
+
+    /// Depth first search through prerequisites. `path` holds the chain of
+    /// technologies currently being visited, so reaching one of them again
+    /// means there is a cycle.
+    fn find_cycle<'a>(
+        &'a self,
+        id: &'a String,
+        path: &mut Vec<&'a String>,
+        finished: &mut Vec<&'a String>,
+    ) -> Result<(), TechTreeError> {
+        if finished.contains(&id) {
+            return Ok(());
+        }
+
+        if let Some(start) = path.iter().position(|visited| *visited == id) {
+            let cycle = path[start..].iter().map(|id| id.to_string()).collect();
+            return Err(TechTreeError::Cycle(cycle));
+        }
+
+        path.push(id);
+        for prerequisite in self.technologies[id].prerequisites.iter() {
+            self.find_cycle(prerequisite, path, finished)?;
+        }
+        path.pop();
+
+        finished.push(id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::ore::{IronOre, Ore};
+    use crate::player::profile::PlayerId;
+    use crate::recipes::recipe_book::testing::recipes;
+
+    fn technology(id: &str, prerequisites: &[&str]) -> Technology {
+        Technology {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            prerequisites: prerequisites.iter().map(|id| id.to_string()).collect(),
+            cost: Vec::new(),
+            recipes: Vec::new(),
+            machines: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_from_ron() {
+        let tree = TechTree::from_ron(
+            r#"[
+                (id: "smelting", name: "Smelting", description: "Melt ore."),
+                (
+                    id: "washing",
+                    name: "Ore Washing",
+                    description: "Clean ore with water.",
+                    prerequisites: ["smelting"],
+                    cost: [(item: "Iron Ore", amount: 5.0)],
+                    machines: ["ore_washer"],
+                ),
+            ]"#,
+            &recipes(),
+        )
+        .unwrap();
+
+        assert_eq!(tree.get("washing").unwrap().prerequisites, ["smelting"]);
+    }
+
+    #[test]
+    fn test_rejects_dangling_prerequisites() {
+        let result = TechTree::new(vec![technology("washing", &["smelting"])], &recipes());
+        assert_eq!(
+            result.unwrap_err(),
+            TechTreeError::DanglingPrerequisite {
+                technology: "washing".to_string(),
+                prerequisite: "smelting".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_rejects_cycles() {
+        let technologies = vec![
+            technology("a", &[]),
+            technology("b", &["a", "d"]),
+            technology("c", &["b"]),
+            technology("d", &["c"]),
+        ];
+        let result = TechTree::new(technologies, &recipes());
+        assert!(matches!(result, Err(TechTreeError::Cycle(cycle)) if cycle.len() == 3));
+
+        let result = TechTree::new(vec![technology("a", &["a"])], &recipes());
+        assert!(matches!(result, Err(TechTreeError::Cycle(_))));
+    }
+
+    #[test]
+    fn test_rejects_unknown_unlocks() {
+        let mut washing = technology("washing", &[]);
+        washing.recipes.push("washed_gold_ore".to_string());
+        assert_eq!(
+            TechTree::new(vec![washing.clone()], &recipes()).unwrap_err(),
+            TechTreeError::UnknownRecipe {
+                technology: "washing".to_string(),
+                recipe: "washed_gold_ore".to_string(),
+            }
+        );
+
+        washing.recipes = vec!["washed_iron_ore".to_string()];
+        washing.machines.push("gold_washer".to_string());
+        assert_eq!(
+            TechTree::new(vec![washing], &recipes()).unwrap_err(),
+            TechTreeError::UnknownMachine {
+                technology: "washing".to_string(),
+                machine: "gold_washer".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_rejects_duplicates() {
+        let result = TechTree::new(vec![technology("a", &[]), technology("a", &[])], &recipes());
+        assert_eq!(
+            result.unwrap_err(),
+            TechTreeError::Duplicate("a".to_string())
+        );
+    }
+
+    #[test]
+    fn test_research() {
+        let mut washing = technology("washing", &["smelting"]);
+        washing.cost.push(ItemCost {
+            item: "Iron Ore".to_string(),
+            amount: 5.0,
+        });
+        washing.recipes.push("washed_iron_ore".to_string());
+        let tree = TechTree::new(vec![technology("smelting", &[]), washing], &recipes()).unwrap();
+
+        let mut player = PlayerLogic::new(PlayerId(0), "gman");
+        let mut inventory = Inventory::default();
+        inventory.add(Ore::<IronOre>::new(3.0, 1.0, 0));
+
+        assert_eq!(
+            tree.research("washing", &mut player, &mut inventory),
+            Err(ResearchError::MissingPrerequisite("smelting".to_string()))
+        );
+        assert_eq!(
+            tree.research("smelting", &mut player, &mut inventory),
+            Ok(())
+        );
+        assert_eq!(
+            tree.research("washing", &mut player, &mut inventory),
+            Err(ResearchError::MissingItems {
+                item: "Iron Ore".to_string(),
+                needed: 5.0,
+                available: 3.0,
+            })
+        );
+
+        inventory.add(Ore::<IronOre>::new(3.0, 1.0, 1));
+        assert_eq!(
+            tree.research("washing", &mut player, &mut inventory),
+            Ok(())
+        );
+        assert_eq!(inventory.amount_of("Iron Ore"), 1.0);
+        assert!(player.has_recipe("washed_iron_ore"));
+        assert_eq!(
+            tree.research("washing", &mut player, &mut inventory),
+            Err(ResearchError::AlreadyResearched)
+        );
+    }
+
+    #[test]
+    fn test_can_build() {
+        let mut smelting = technology("smelting", &[]);
+        smelting.machines.push("furnace".to_string());
+        let tree = TechTree::new(vec![smelting], &recipes()).unwrap();
+
+        let mut player = PlayerLogic::new(PlayerId(0), "gman");
+        assert!(!tree.can_build("furnace", &player));
+        assert!(tree.can_build("lab", &player));
+
+        tree.research("smelting", &mut player, &mut Inventory::default())
+            .unwrap();
+        assert!(tree.can_build("furnace", &player));
+    }
+}
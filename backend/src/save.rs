@@ -0,0 +1,333 @@
+//! Saved games, written as ron like the settings. A save holds the player's
+//! profile, where they stood, what they carried and the buildings they
+//! placed, with what their machines were working on. Items are kept by name
+//! and amount and made again with [add_named], then given back how pure or
+//! worn they were.
+
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::buildings::{Building, BuildingConfig, BuildingKind, Placement, Rotation};
+use crate::iams::inventory::ItemKey;
+use crate::iams::Inventory;
+use crate::items::registry::add_named;
+use crate::items::Item;
+use crate::machines::{Machine, MachineProcess, MachineState};
+use crate::player::profile::PlayerLogic;
+
+pub const SAVE_FILE: &str = "save.ron";
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    Serialize(ron::Error),
+    Deserialize(ron::error::SpannedError),
+}
+
+/// An item by name and amount, with how pure or worn it was.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedItem {
+    pub item: String,
+    pub amount: f32,
+    #[serde(default)]
+    pub purity: Option<f32>,
+    #[serde(default)]
+    pub durability: Option<u32>,
+}
+
+impl SavedItem {
+    pub fn new(item: &dyn Item) -> Self {
+        Self {
+            item: item.type_name().to_string(),
+            amount: item.amount().as_f32(),
+            purity: item.purity(),
+            durability: item.durability(),
+        }
+    }
+
+    /// Gives the items [add_named] just made in `made` the saved purity and
+    /// durability.
+    fn restore_state(&self, made: &mut Inventory) {
+        for item in made.get_all_mut() {
+            if let Some(purity) = self.purity {
+                item.set_purity(purity);
+            }
+            if let Some(durability) = self.durability {
+                item.set_durability(durability);
+            }
+        }
+    }
+}
+
+fn save_items(inventory: &Inventory) -> Vec<SavedItem> {
+    inventory
+        .get_all()
+        .into_iter()
+        .map(SavedItem::new)
+        .collect()
+}
+
+/// Makes the saved items again. Items no longer in the game are left out.
+fn restore_items(saved: &[SavedItem]) -> Inventory {
+    let mut inventory = Inventory::default();
+    for saved in saved {
+        let mut made = Inventory::default();
+        if !add_named(&mut made, &saved.item, saved.amount) {
+            log::warn!("Dropping unknown saved item {}", saved.item);
+            continue;
+        }
+        saved.restore_state(&mut made);
+
+        let keys: Vec<_> = made.get_all().into_iter().map(ItemKey::of).collect();
+        for key in keys {
+            made.move_item(key, None, &mut inventory);
+        }
+    }
+    inventory
+}
+
+/// How worn a machine was and the batch it was working on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedMachine {
+    pub state: MachineState,
+    pub wear: f32,
+    pub uptime: f32,
+    pub failure_odds: f32,
+    #[serde(default)]
+    pub input: Vec<SavedItem>,
+    #[serde(default)]
+    pub output: Vec<SavedItem>,
+    #[serde(default)]
+    pub recipe: Option<String>,
+    #[serde(default)]
+    pub progress: f32,
+}
+
+impl SavedMachine {
+    pub fn new(machine: &Machine, process: &MachineProcess) -> Self {
+        Self {
+            state: machine.state,
+            wear: machine.wear,
+            uptime: machine.uptime,
+            failure_odds: machine.failure_odds,
+            input: save_items(&process.input),
+            output: save_items(&process.output),
+            recipe: process.recipe.clone(),
+            progress: process.progress,
+        }
+    }
+
+    /// Puts the saved wear and batch back onto a newly built machine.
+    pub fn restore(&self, machine: &mut Machine, process: &mut MachineProcess) {
+        machine.state = self.state;
+        machine.wear = self.wear;
+        machine.uptime = self.uptime;
+        machine.failure_odds = self.failure_odds;
+        process.input = restore_items(&self.input);
+        process.output = restore_items(&self.output);
+        process.recipe = self.recipe.clone();
+        process.progress = self.progress;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedBuilding {
+    pub kind: BuildingKind,
+    pub translation: [f32; 3],
+    pub rotation: Rotation,
+    #[serde(default)]
+    pub config: BuildingConfig,
+    #[serde(default)]
+    pub machine: Option<SavedMachine>,
+}
+
+impl SavedBuilding {
+    pub fn new(building: &Building, translation: Vec3) -> Self {
+        Self {
+            kind: building.kind,
+            translation: translation.to_array(),
+            rotation: building.rotation,
+            config: building.config.clone(),
+            machine: None,
+        }
+    }
+
+    pub fn with_machine(mut self, machine: &Machine, process: &MachineProcess) -> Self {
+        self.machine = Some(SavedMachine::new(machine, process));
+        self
+    }
+
+    pub fn placement(&self) -> Placement {
+        Placement {
+            kind: self.kind,
+            translation: Vec3::from_array(self.translation),
+            rotation: self.rotation,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub profile: PlayerLogic,
+    pub position: [f32; 3],
+    #[serde(default)]
+    pub inventory: Vec<SavedItem>,
+    #[serde(default)]
+    pub buildings: Vec<SavedBuilding>,
+}
+
+impl SaveGame {
+    pub fn new(
+        profile: &PlayerLogic,
+        position: Vec3,
+        inventory: &Inventory,
+        buildings: Vec<SavedBuilding>,
+    ) -> Self {
+        Self {
+            profile: profile.clone(),
+            position: position.to_array(),
+            inventory: save_items(inventory),
+            buildings,
+        }
+    }
+
+    /// Makes the saved items again. Items no longer in the game are left out.
+    pub fn restore_inventory(&self) -> Inventory {
+        restore_items(&self.inventory)
+    }
+
+    pub fn to_ron(&self) -> Result<String, SaveError> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(SaveError::Serialize)
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, SaveError> {
+        ron::from_str(text).map_err(SaveError::Deserialize)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SaveError> {
+        std::fs::write(path, self.to_ron()?).map_err(SaveError::Io)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SaveError> {
+        let text = std::fs::read_to_string(path).map_err(SaveError::Io)?;
+        Self::from_ron(&text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::ore::{IronOre, Ore};
+    use crate::items::spare_part::SparePart;
+    use crate::items::tool::{Tool, ToolKind};
+    use crate::machines::{FailureEffect, Reliability};
+    use crate::player::profile::PlayerId;
+
+    fn sample() -> SaveGame {
+        let mut inventory = Inventory::default();
+        inventory.add(Ore::<IronOre>::new(1.5, 0.5, 0));
+        inventory.add(SparePart::new(3, 0));
+        inventory.add(Tool {
+            durability: 7,
+            ..Tool::new(ToolKind::Drill, 0)
+        });
+
+        let mut furnace = Building {
+            kind: BuildingKind::Furnace,
+            rotation: Rotation::East,
+            config: BuildingConfig::default(),
+        };
+        furnace.config.recipe = Some("iron_ingot".to_string());
+
+        let mut machine = Machine::new(Reliability::default());
+        machine.state = MachineState::Failed(FailureEffect::ReducedOutput(0.5));
+        machine.wear = 12.0;
+        let mut process = MachineProcess::default();
+        process.input.add(Ore::<IronOre>::new(2.0, 0.25, 0));
+        process.recipe = Some("iron_ingot".to_string());
+        process.progress = 0.4;
+
+        SaveGame::new(
+            &PlayerLogic::new(PlayerId(1), "gman"),
+            Vec3::new(1.0, 2.0, 3.0),
+            &inventory,
+            vec![SavedBuilding::new(&furnace, Vec3::new(4.0, 1.0, 0.0))
+                .with_machine(&machine, &process)],
+        )
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let save = sample();
+        let text = save.to_ron().unwrap();
+        assert_eq!(SaveGame::from_ron(&text).unwrap(), save);
+    }
+
+    #[test]
+    fn test_restore_inventory() {
+        let mut save = sample();
+        save.inventory.push(SavedItem {
+            item: "Unobtainium".to_string(),
+            amount: 1.0,
+            purity: None,
+            durability: None,
+        });
+        let inventory = save.restore_inventory();
+
+        assert_eq!(inventory.amount_of("Iron Ore"), 1.5);
+        assert_eq!(inventory.amount_of("Spare Part"), 3.0);
+        assert_eq!(inventory.get_all().len(), 3);
+    }
+
+    #[test]
+    fn test_round_trip_keeps_purity_and_durability() {
+        let text = sample().to_ron().unwrap();
+        let inventory = SaveGame::from_ron(&text).unwrap().restore_inventory();
+
+        let ore = inventory.query::<Ore<IronOre>>().unwrap();
+        assert_eq!(ore[0].purity, 0.5);
+        let tools = inventory.query::<Tool>().unwrap();
+        assert_eq!(tools[0].durability, 7);
+    }
+
+    #[test]
+    fn test_restore_machine() {
+        let saved = sample().buildings[0].machine.clone().unwrap();
+        let mut machine = Machine::new(Reliability::default());
+        let mut process = MachineProcess::default();
+        saved.restore(&mut machine, &mut process);
+
+        assert_eq!(
+            machine.state,
+            MachineState::Failed(FailureEffect::ReducedOutput(0.5))
+        );
+        assert_eq!(machine.wear, 12.0);
+        assert_eq!(
+            process.input.query::<Ore<IronOre>>().unwrap()[0].purity,
+            0.25
+        );
+        assert_eq!(process.recipe.as_deref(), Some("iron_ingot"));
+        assert_eq!(process.progress, 0.4);
+    }
+
+    #[test]
+    fn test_reads_items_without_state() {
+        let saved: SavedItem = ron::from_str(r#"(item: "Iron Ore", amount: 1.5)"#).unwrap();
+        let inventory = restore_items(&[saved]);
+
+        assert_eq!(inventory.query::<Ore<IronOre>>().unwrap()[0].purity, 1.0);
+        assert_eq!(inventory.amount_of("Iron Ore"), 1.5);
+    }
+
+    #[test]
+    fn test_building_placement() {
+        let placement = sample().buildings[0].placement();
+
+        assert_eq!(placement.kind, BuildingKind::Furnace);
+        assert_eq!(placement.translation, Vec3::new(4.0, 1.0, 0.0));
+        assert_eq!(placement.rotation, Rotation::East);
+    }
+}
@@ -0,0 +1,481 @@
+//! Player settings, kept in a ron file next to the game so they survive
+//! restarts. Every section falls back to its defaults when missing, so old
+//! settings files keep loading as sections are added.
+
+use std::f32::consts::FRAC_PI_2;
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::input::InputBindings;
+
+pub const SETTINGS_FILE: &str = "settings.ron";
+
+#[derive(Debug)]
+pub enum SettingsError {
+    Io(std::io::Error),
+    Serialize(ron::Error),
+    Deserialize(ron::error::SpannedError),
+}
+
+/// How the mouse turns the camera. Pitch is in radians, positive looking down.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraSettings {
+    pub min_pitch: f32,
+    pub max_pitch: f32,
+    /// Radians turned per pixel of horizontal mouse movement.
+    pub sensitivity_x: f32,
+    /// Radians pitched per pixel of vertical mouse movement.
+    pub sensitivity_y: f32,
+    pub invert_y: bool,
+    /// Seconds the camera takes to catch up with most of the mouse movement.
+    /// Zero turns smoothing off.
+    pub smoothing: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            min_pitch: -1.0,
+            max_pitch: 1.0,
+            sensitivity_x: 0.001,
+            sensitivity_y: 0.00067,
+            invert_y: false,
+            smoothing: 0.0,
+        }
+    }
+}
+
+impl CameraSettings {
+    /// Turns mouse movement in pixels into yaw and pitch in radians. Moving
+    /// the mouse right turns right, so yaw is negative.
+    pub fn look_delta(&self, mouse: Vec2) -> Vec2 {
+        let pitch = match self.invert_y {
+            true => -mouse.y,
+            false => mouse.y,
+        };
+        Vec2::new(-mouse.x * self.sensitivity_x, pitch * self.sensitivity_y)
+    }
+
+    /// Keeps the pitch within the limits, which are themselves kept short of
+    /// straight up and down so the camera never flips over.
+    pub fn clamp_pitch(&self, pitch: f32) -> f32 {
+        let limit = FRAC_PI_2 - 0.01;
+        let min = self.min_pitch.min(self.max_pitch).clamp(-limit, limit);
+        let max = self.max_pitch.max(self.min_pitch).clamp(-limit, limit);
+        pitch.clamp(min, max)
+    }
+
+    /// Share of the outstanding mouse movement to apply after `dt` seconds.
+    /// Applying it twice over half the time gives the same result, so
+    /// smoothing feels the same at any frame rate.
+    pub fn smoothing_factor(&self, dt: f32) -> f32 {
+        match self.smoothing > 0.0 {
+            true => 1.0 - (-dt / self.smoothing).exp(),
+            false => 1.0,
+        }
+    }
+}
+
+/// How the game is drawn.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphicsSettings {
+    pub vsync: bool,
+    pub shadows: bool,
+    /// Vertical field of view in degrees.
+    pub fov: f32,
+    /// Samples per pixel for anti-aliasing. 1 turns it off.
+    pub msaa_samples: u32,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            vsync: true,
+            shadows: true,
+            fov: 45.0,
+            msaa_samples: 4,
+        }
+    }
+}
+
+impl GraphicsSettings {
+    pub const MIN_FOV: f32 = 30.0;
+    pub const MAX_FOV: f32 = 110.0;
+
+    pub fn msaa(&self) -> Msaa {
+        match self.msaa_samples {
+            0 | 1 => Msaa::Off,
+            2 => Msaa::Sample2,
+            3 | 4 => Msaa::Sample4,
+            _ => Msaa::Sample8,
+        }
+    }
+}
+
+/// Volumes from 0.0 to 1.0. Music and effects are scaled by the master
+/// volume.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub music: f32,
+    pub effects: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: 0.7,
+            effects: 1.0,
+        }
+    }
+}
+
+impl AudioSettings {
+    pub fn music_volume(&self) -> f32 {
+        self.master * self.music
+    }
+
+    pub fn effects_volume(&self) -> f32 {
+        self.master * self.effects
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub camera: CameraSettings,
+    pub controls: InputBindings,
+    pub graphics: GraphicsSettings,
+    pub audio: AudioSettings,
+}
+
+impl Settings {
+    pub fn to_ron(&self) -> Result<String, SettingsError> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(SettingsError::Serialize)
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, SettingsError> {
+        let mut settings: Settings = ron::from_str(text).map_err(SettingsError::Deserialize)?;
+        settings.controls.fill_defaults();
+        Ok(settings)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SettingsError> {
+        std::fs::write(path, self.to_ron()?).map_err(SettingsError::Io)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SettingsError> {
+        let text = std::fs::read_to_string(path).map_err(SettingsError::Io)?;
+        Self::from_ron(&text)
+    }
+}
+
+/// A setting changed a step at a time from the settings menu. Key bindings
+/// have a menu of their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    Vsync,
+    Shadows,
+    Fov,
+    Msaa,
+    Sensitivity,
+    InvertY,
+    Smoothing,
+    MasterVolume,
+    MusicVolume,
+    EffectsVolume,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 10] = [
+        Setting::Vsync,
+        Setting::Shadows,
+        Setting::Fov,
+        Setting::Msaa,
+        Setting::Sensitivity,
+        Setting::InvertY,
+        Setting::Smoothing,
+        Setting::MasterVolume,
+        Setting::MusicVolume,
+        Setting::EffectsVolume,
+    ];
+
+    #[rustfmt::skip]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Setting::Vsync         => "V-sync",
+            Setting::Shadows       => "Shadows",
+            Setting::Fov           => "Field of view",
+            Setting::Msaa          => "Anti-aliasing",
+            Setting::Sensitivity   => "Mouse sensitivity",
+            Setting::InvertY       => "Invert mouse",
+            Setting::Smoothing     => "Camera smoothing",
+            Setting::MasterVolume  => "Master volume",
+            Setting::MusicVolume   => "Music volume",
+            Setting::EffectsVolume => "Effects volume",
+        }
+    }
+
+    /// The current value as shown in the menu.
+    pub fn label(&self, settings: &Settings) -> String {
+        let on_off = |on: bool| match on {
+            true => "On".to_string(),
+            false => "Off".to_string(),
+        };
+        let percent = |volume: f32| format!("{:.0}%", volume * 100.0);
+
+        match self {
+            Setting::Vsync => on_off(settings.graphics.vsync),
+            Setting::Shadows => on_off(settings.graphics.shadows),
+            Setting::Fov => format!("{:.0}°", settings.graphics.fov),
+            Setting::Msaa => match settings.graphics.msaa() {
+                Msaa::Off => "Off".to_string(),
+                msaa => format!("{}x", msaa.samples()),
+            },
+            Setting::Sensitivity => {
+                let default = CameraSettings::default().sensitivity_x;
+                percent(settings.camera.sensitivity_x / default)
+            }
+            Setting::InvertY => on_off(settings.camera.invert_y),
+            Setting::Smoothing => format!("{:.2} s", settings.camera.smoothing),
+            Setting::MasterVolume => percent(settings.audio.master),
+            Setting::MusicVolume => percent(settings.audio.music),
+            Setting::EffectsVolume => percent(settings.audio.effects),
+        }
+    }
+
+    /// Moves the setting one step up or down. Switches flip either way, and
+    /// values stop at the ends of their range.
+    pub fn step(&self, settings: &mut Settings, up: bool) {
+        let sign = match up {
+            true => 1.0,
+            false => -1.0,
+        };
+        let volume = |volume: &mut f32| *volume = (*volume + sign * 0.1).clamp(0.0, 1.0);
+
+        match self {
+            Setting::Vsync => settings.graphics.vsync = !settings.graphics.vsync,
+            Setting::Shadows => settings.graphics.shadows = !settings.graphics.shadows,
+            Setting::Fov => {
+                let fov = settings.graphics.fov + sign * 5.0;
+                settings.graphics.fov =
+                    fov.clamp(GraphicsSettings::MIN_FOV, GraphicsSettings::MAX_FOV);
+            }
+            Setting::Msaa => {
+                let samples = settings.graphics.msaa().samples();
+                settings.graphics.msaa_samples = match up {
+                    true => (samples * 2).min(8),
+                    false => (samples / 2).max(1),
+                };
+            }
+            Setting::Sensitivity => {
+                // Both axes are scaled together to keep their ratio.
+                let camera = &mut settings.camera;
+                let default = CameraSettings::default().sensitivity_x;
+                let scale = camera.sensitivity_x / default;
+                let new_scale = (scale + sign * 0.1).clamp(0.1, 5.0);
+                camera.sensitivity_x *= new_scale / scale;
+                camera.sensitivity_y *= new_scale / scale;
+            }
+            Setting::InvertY => settings.camera.invert_y = !settings.camera.invert_y,
+            Setting::Smoothing => {
+                let smoothing = settings.camera.smoothing + sign * 0.05;
+                settings.camera.smoothing = smoothing.clamp(0.0, 0.5);
+            }
+            Setting::MasterVolume => volume(&mut settings.audio.master),
+            Setting::MusicVolume => volume(&mut settings.audio.music),
+            Setting::EffectsVolume => volume(&mut settings.audio.effects),
+        }
+    }
+}
+
+/// Where the settings are saved, as given to the [SettingsPlugin].
+#[derive(Resource, Debug, Clone)]
+pub struct SettingsFile(pub PathBuf);
+
+/// Loads the settings file and inserts each section as a resource. Defaults
+/// are used when the file is missing or broken. Whenever a section changes,
+/// the file is written again.
+pub struct SettingsPlugin {
+    pub path: PathBuf,
+}
+
+impl Default for SettingsPlugin {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from(SETTINGS_FILE),
+        }
+    }
+}
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = Settings::load(&self.path).unwrap_or_else(|err| {
+            log::info!("Using default settings: {err:?}");
+            Settings::default()
+        });
+
+        app.insert_resource(settings.camera)
+            .insert_resource(settings.controls)
+            .insert_resource(settings.graphics)
+            .insert_resource(settings.audio)
+            .insert_resource(SettingsFile(self.path.clone()))
+            .add_systems(Last, save_changed_settings);
+    }
+}
+
+pub fn save_changed_settings(
+    file: Res<SettingsFile>,
+    camera: Res<CameraSettings>,
+    controls: Res<InputBindings>,
+    graphics: Res<GraphicsSettings>,
+    audio: Res<AudioSettings>,
+) {
+    // Everything counts as changed when first inserted, which is not worth
+    // writing back.
+    let changed =
+        camera.is_changed() || controls.is_changed() || graphics.is_changed() || audio.is_changed();
+    if !changed || file.is_added() {
+        return;
+    }
+
+    let settings = Settings {
+        camera: camera.clone(),
+        controls: controls.clone(),
+        graphics: graphics.clone(),
+        audio: audio.clone(),
+    };
+    if let Err(err) = settings.save(&file.0) {
+        log::warn!("Could not save settings: {err:?}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{Action, Binding};
+
+    #[test]
+    fn test_clamp_pitch() {
+        let settings = CameraSettings::default();
+        assert_eq!(settings.clamp_pitch(0.5), 0.5);
+        assert_eq!(settings.clamp_pitch(2.0), 1.0);
+        assert_eq!(settings.clamp_pitch(-2.0), -1.0);
+    }
+
+    #[test]
+    fn test_clamp_pitch_never_flips() {
+        let settings = CameraSettings {
+            min_pitch: -10.0,
+            max_pitch: 10.0,
+            ..Default::default()
+        };
+        assert!(settings.clamp_pitch(3.0) < FRAC_PI_2);
+        assert!(settings.clamp_pitch(-3.0) > -FRAC_PI_2);
+    }
+
+    #[test]
+    fn test_clamp_pitch_swapped_limits() {
+        let settings = CameraSettings {
+            min_pitch: 0.5,
+            max_pitch: -0.5,
+            ..Default::default()
+        };
+        assert_eq!(settings.clamp_pitch(1.0), 0.5);
+        assert_eq!(settings.clamp_pitch(-1.0), -0.5);
+    }
+
+    #[test]
+    fn test_invert_y() {
+        let mut settings = CameraSettings::default();
+        let normal = settings.look_delta(Vec2::new(10.0, 10.0));
+        settings.invert_y = true;
+        let inverted = settings.look_delta(Vec2::new(10.0, 10.0));
+
+        assert_eq!(normal.x, inverted.x);
+        assert_eq!(normal.y, -inverted.y);
+    }
+
+    #[test]
+    fn test_smoothing_is_frame_rate_independent() {
+        let settings = CameraSettings {
+            smoothing: 0.1,
+            ..Default::default()
+        };
+
+        let one_frame = 1.0 - settings.smoothing_factor(1.0 / 30.0);
+        let two_frames = (1.0 - settings.smoothing_factor(1.0 / 60.0)).powi(2);
+        assert!((one_frame - two_frames).abs() < 1e-6);
+
+        let off = CameraSettings::default();
+        assert_eq!(off.smoothing_factor(1.0 / 60.0), 1.0);
+    }
+
+    #[test]
+    fn test_missing_sections_use_defaults() {
+        let settings = Settings::from_ron("(camera: (invert_y: true))").unwrap();
+        assert!(settings.camera.invert_y);
+        assert_eq!(settings.camera.max_pitch, 1.0);
+        assert_eq!(Settings::from_ron("()").unwrap(), Settings::default());
+    }
+
+    #[test]
+    fn test_steps_stay_in_range() {
+        let mut settings = Settings::default();
+        for _ in 0..20 {
+            Setting::MasterVolume.step(&mut settings, true);
+            Setting::Fov.step(&mut settings, false);
+            Setting::Msaa.step(&mut settings, true);
+        }
+
+        assert_eq!(settings.audio.master, 1.0);
+        assert_eq!(settings.graphics.fov, GraphicsSettings::MIN_FOV);
+        assert_eq!(settings.graphics.msaa_samples, 8);
+        assert_eq!(Setting::Msaa.label(&settings), "8x");
+    }
+
+    #[test]
+    fn test_switches_flip() {
+        let mut settings = Settings::default();
+        Setting::InvertY.step(&mut settings, false);
+        assert!(settings.camera.invert_y);
+        assert_eq!(Setting::InvertY.label(&settings), "On");
+
+        Setting::Msaa.step(&mut settings, false);
+        Setting::Msaa.step(&mut settings, false);
+        Setting::Msaa.step(&mut settings, false);
+        assert_eq!(Setting::Msaa.label(&settings), "Off");
+    }
+
+    #[test]
+    fn test_sensitivity_keeps_axis_ratio() {
+        let mut settings = Settings::default();
+        let ratio = settings.camera.sensitivity_y / settings.camera.sensitivity_x;
+        Setting::Sensitivity.step(&mut settings, true);
+
+        assert_eq!(Setting::Sensitivity.label(&settings), "110%");
+        let new_ratio = settings.camera.sensitivity_y / settings.camera.sensitivity_x;
+        assert!((ratio - new_ratio).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut settings = Settings::default();
+        settings.camera.invert_y = true;
+        settings.graphics.fov = 90.0;
+        settings.audio.music = 0.3;
+        settings
+            .controls
+            .bind(Action::Jump, Binding::Key(KeyCode::KeyJ));
+
+        let text = settings.to_ron().unwrap();
+        assert_eq!(Settings::from_ron(&text).unwrap(), settings);
+    }
+}
@@ -0,0 +1,266 @@
+use backend::buildings::{
+    Blueprint, BuildEdits, Building, BuildingConfig, PlaceBuilding, Placement, GRID_SIZE,
+};
+use backend::history::{Batch, EditCommand};
+use backend::input::{Action, ActionState};
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use super::edit::{edit_buildings, BuildEdit};
+use super::placement::{aim, BuildMode, GhostAssets};
+use crate::player::Player;
+
+const BLUEPRINT_DIR: &str = "blueprints";
+/// Blueprints shared as text are pasted into this file to be loaded.
+const SHARED_BLUEPRINT: &str = "blueprints/shared.ron";
+
+/// State of the copy and paste tool. [Action::Copy] marks two corners of the
+/// area to copy, [Action::Paste] starts and stops pasting. While pasting, the
+/// blueprint is shown as ghosts and [Action::Place] builds what they show.
+#[derive(Resource, Debug, Default)]
+pub struct BlueprintTool {
+    corner: Option<Vec3>,
+    pub blueprint: Option<Blueprint>,
+    pub pasting: bool,
+    /// Where the ghosts currently show the blueprint going.
+    preview: Vec<(Placement, BuildingConfig)>,
+}
+
+/// Puts a blueprint on the clipboard as a line of text, for sharing.
+fn export_blueprint(blueprint: &Blueprint) -> Result<(), String> {
+    let text = blueprint.to_text().map_err(|err| format!("{err:?}"))?;
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .map_err(|err| format!("{err:?}"))
+}
+
+/// Reads a blueprint shared as text off the clipboard, if there is one.
+fn import_blueprint() -> Option<Blueprint> {
+    let text = arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .ok()?;
+    Blueprint::from_text(&text).ok()
+}
+
+/// One translucent building of the blueprint being pasted.
+#[derive(Component, Debug)]
+pub struct BlueprintGhost {
+    index: usize,
+    valid: bool,
+}
+
+pub fn copy_blueprint(
+    actions: Res<ActionState>,
+    spatial_query: SpatialQuery,
+    mut tool: ResMut<BlueprintTool>,
+    player: Query<Entity, With<Player>>,
+    camera: Query<&Transform, With<Camera3d>>,
+    buildings: Query<(&Transform, &Building), Without<Camera3d>>,
+) {
+    if !actions.just_pressed(Action::Copy) {
+        return;
+    }
+
+    let Some((_, point)) = aim(&spatial_query, camera.single(), player.single()) else {
+        return;
+    };
+
+    let Some(first) = tool.corner.take() else {
+        tool.corner = Some(point);
+        return;
+    };
+
+    let (min, max) = (first.min(point).xz(), first.max(point).xz());
+    let selected = buildings
+        .iter()
+        .map(|(transform, building)| (transform.translation, building))
+        .filter(|(translation, _)| {
+            let position = translation.xz();
+            position.cmpge(min).all() && position.cmple(max).all()
+        });
+
+    let created = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+    let name = format!("blueprint_{created}");
+    let blueprint = match Blueprint::capture(name, selected) {
+        Ok(blueprint) => blueprint,
+        Err(err) => {
+            log::info!("Nothing copied: {err:?}");
+            return;
+        }
+    };
+
+    let saved = std::fs::create_dir_all(BLUEPRINT_DIR)
+        .map_err(|err| format!("{err:?}"))
+        .and_then(|_| {
+            let path = format!("{}/{}.ron", BLUEPRINT_DIR, blueprint.name);
+            blueprint.save(path).map_err(|err| format!("{err:?}"))
+        });
+    if let Err(err) = saved {
+        log::warn!("Could not save blueprint: {err}");
+    }
+
+    match export_blueprint(&blueprint) {
+        Ok(()) => log::info!("Copied blueprint {} to the clipboard", blueprint.name),
+        Err(err) => log::warn!("Could not copy blueprint to the clipboard: {err}"),
+    }
+    tool.blueprint = Some(blueprint);
+}
+
+pub fn toggle_paste(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    mut tool: ResMut<BlueprintTool>,
+    mut build_mode: ResMut<BuildMode>,
+    ghosts: Query<Entity, With<BlueprintGhost>>,
+) {
+    if !actions.just_pressed(Action::Paste) {
+        return;
+    }
+
+    tool.pasting = !tool.pasting;
+    if !tool.pasting {
+        tool.preview.clear();
+        for ghost in ghosts.iter() {
+            commands.entity(ghost).despawn();
+        }
+        return;
+    }
+
+    // A blueprint someone shared takes over from the last one copied.
+    if let Some(blueprint) = import_blueprint() {
+        tool.blueprint = Some(blueprint);
+    }
+    if tool.blueprint.is_none() {
+        match Blueprint::load(SHARED_BLUEPRINT) {
+            Ok(blueprint) => tool.blueprint = Some(blueprint),
+            Err(err) => {
+                log::info!("No blueprint to paste: {err:?}");
+                tool.pasting = false;
+                return;
+            }
+        }
+    }
+    build_mode.selected = None;
+}
+
+/// The blueprint's buildings with its corner snapped to the grid at `point`.
+fn paste_placements(
+    tool: &BlueprintTool,
+    build_mode: &BuildMode,
+    point: Vec3,
+) -> Vec<(Placement, BuildingConfig)> {
+    let Some(blueprint) = tool.blueprint.as_ref() else {
+        return Vec::new();
+    };
+
+    let corner = Vec3::new(
+        (point.x / GRID_SIZE).round() * GRID_SIZE,
+        point.y,
+        (point.z / GRID_SIZE).round() * GRID_SIZE,
+    );
+    blueprint.placements(corner, build_mode.rotation)
+}
+
+/// Shows the blueprint as ghosts where the player is aiming, red where a
+/// building would overlap another.
+#[allow(clippy::too_many_arguments)]
+pub fn update_paste_ghosts(
+    mut commands: Commands,
+    mut tool: ResMut<BlueprintTool>,
+    build_mode: Res<BuildMode>,
+    spatial_query: SpatialQuery,
+    ghost_assets: Res<GhostAssets>,
+    player: Query<Entity, With<Player>>,
+    camera: Query<&Transform, (With<Camera3d>, Without<BlueprintGhost>)>,
+    mut ghosts: Query<(
+        Entity,
+        &mut BlueprintGhost,
+        &mut Transform,
+        &mut Handle<StandardMaterial>,
+    )>,
+) {
+    if !tool.pasting {
+        return;
+    }
+
+    let Some((_, point)) = aim(&spatial_query, camera.single(), player.single()) else {
+        return;
+    };
+    let placements = paste_placements(&tool, &build_mode, point);
+
+    if ghosts.iter().count() != placements.len() {
+        tool.preview.clear();
+        for (entity, ..) in ghosts.iter() {
+            commands.entity(entity).despawn();
+        }
+        for index in 0..placements.len() {
+            commands.spawn((
+                PbrBundle {
+                    mesh: ghost_assets.mesh.clone(),
+                    material: ghost_assets.valid.clone(),
+                    ..Default::default()
+                },
+                BlueprintGhost {
+                    index,
+                    valid: false,
+                },
+            ));
+        }
+        return;
+    }
+
+    for (_, mut ghost, mut transform, mut material) in ghosts.iter_mut() {
+        let (placement, _) = &placements[ghost.index];
+        ghost.valid = !placement.is_blocked(&spatial_query);
+
+        *transform = placement.transform().with_scale(placement.size());
+        *material = match ghost.valid {
+            true => ghost_assets.valid.clone(),
+            false => ghost_assets.invalid.clone(),
+        };
+    }
+    tool.preview = placements;
+}
+
+/// Places the whole blueprint where its ghosts are, as long as every
+/// building fits and the player is carrying all of them. It is undone as one
+/// edit too.
+pub fn place_blueprint(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    tool: Res<BlueprintTool>,
+    ghosts: Query<&BlueprintGhost>,
+    player: Query<&Player>,
+) {
+    let player = player.single();
+    if !tool.pasting || !actions.just_pressed(Action::Place) || !player.movement_enabled {
+        return;
+    }
+
+    let Some(blueprint) = tool.blueprint.as_ref() else {
+        return;
+    };
+    if tool.preview.is_empty() || ghosts.iter().any(|ghost| !ghost.valid) {
+        return;
+    }
+    if let Some(kind) = blueprint.missing(&player.inventory) {
+        log::info!("Not enough {} to paste {}", kind.name(), blueprint.name);
+        return;
+    }
+
+    let places = tool
+        .preview
+        .iter()
+        .map(|(placement, config)| {
+            let place = PlaceBuilding::new(*placement, config.clone());
+            Box::new(place) as Box<dyn EditCommand<BuildEdits>>
+        })
+        .collect();
+    edit_buildings(
+        &mut commands,
+        BuildEdit::Execute(Box::new(Batch(places))),
+        |_, _| {},
+    );
+}
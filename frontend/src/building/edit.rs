@@ -0,0 +1,186 @@
+use backend::buildings::{
+    BuildEdits, BuildWorld, Building, BuildingConfig, BuildingId, BuildingIds, Placement,
+    RotateBuilding, Rotation, GRID_SIZE,
+};
+use backend::history::{EditCommand, History};
+use backend::iams::Inventory;
+use backend::input::{Action, ActionState};
+use backend::machines::MachineProcess;
+use bevy::hierarchy::despawn_with_children_recursive;
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use super::placement::{aim, spawn_building, BuildMode};
+use crate::player::Player;
+
+/// What to do with the building [History].
+pub enum BuildEdit {
+    Execute(Box<dyn EditCommand<BuildEdits>>),
+    Undo,
+    Redo,
+}
+
+/// Runs a building edit once the system's commands are applied. Edits work
+/// on the [World] directly, so every step of a [Batch](backend::history::Batch)
+/// sees what the steps before it did. `then` is told whether the edit did
+/// anything.
+pub fn edit_buildings(
+    commands: &mut Commands,
+    edit: BuildEdit,
+    then: impl FnOnce(&mut World, bool) + Send + 'static,
+) {
+    commands.add(move |world: &mut World| {
+        let done = world.resource_scope(|world, mut history: Mut<History<BuildEdits>>| {
+            let mut context = BuildContext { world };
+            match edit {
+                BuildEdit::Execute(command) => history.execute(command, &mut context),
+                BuildEdit::Undo => history.undo(&mut context),
+                BuildEdit::Redo => history.redo(&mut context),
+            }
+        });
+        then(world, done);
+    });
+}
+
+/// The world as building commands see it.
+struct BuildContext<'w> {
+    world: &'w mut World,
+}
+
+impl BuildContext<'_> {
+    fn find(&mut self, id: BuildingId) -> Option<Entity> {
+        self.world
+            .query::<(Entity, &BuildingId)>()
+            .iter(self.world)
+            .find(|(_, building_id)| **building_id == id)
+            .map(|(entity, _)| entity)
+    }
+}
+
+impl BuildWorld for BuildContext<'_> {
+    fn inventory(&mut self) -> &mut Inventory {
+        let player = self.world.query::<&mut Player>().single_mut(self.world);
+        &mut player.into_inner().inventory
+    }
+
+    fn next_id(&mut self) -> BuildingId {
+        self.world.resource_mut::<BuildingIds>().next_id()
+    }
+
+    fn spawn(&mut self, id: BuildingId, placement: &Placement, config: &BuildingConfig) {
+        spawn_building(self.world, id, placement, config.clone());
+    }
+
+    fn despawn(&mut self, id: BuildingId) -> Option<(Placement, BuildingConfig)> {
+        let entity = self.find(id)?;
+        let (building, transform) = self
+            .world
+            .query::<(&Building, &Transform)>()
+            .get(self.world, entity)
+            .ok()?;
+
+        let placement = Placement {
+            kind: building.kind,
+            translation: transform.translation,
+            rotation: building.rotation,
+        };
+        let config = building.config.clone();
+        despawn_with_children_recursive(self.world, entity);
+        Some((placement, config))
+    }
+
+    fn take_contents(&mut self, id: BuildingId) -> Option<MachineProcess> {
+        let entity = self.find(id)?;
+        let mut process = self.world.get_mut::<MachineProcess>(entity)?;
+        Some(std::mem::take(&mut *process))
+    }
+
+    fn restore_contents(&mut self, id: BuildingId, contents: MachineProcess) {
+        let Some(entity) = self.find(id) else {
+            return;
+        };
+        if let Some(mut process) = self.world.get_mut::<MachineProcess>(entity) {
+            *process = contents;
+        }
+    }
+
+    fn set_rotation(&mut self, id: BuildingId, rotation: Rotation) -> bool {
+        let Some(entity) = self.find(id) else {
+            return false;
+        };
+        let mut query = self.world.query::<(&mut Building, &mut Transform)>();
+        let Ok((mut building, mut transform)) = query.get_mut(self.world, entity) else {
+            return false;
+        };
+
+        // Turning a building with an uneven footprint moves its centre off the
+        // grid, so snap it again from the floor it stands on.
+        let height = building.kind.footprint().y as f32 * GRID_SIZE;
+        let floor = transform.translation - Vec3::Y * height / 2.0;
+        let placement = Placement::at(building.kind, floor, rotation);
+
+        building.rotation = rotation;
+        *transform = placement.transform();
+        true
+    }
+
+    fn set_config(&mut self, id: BuildingId, config: &BuildingConfig) -> bool {
+        let Some(mut building) = self
+            .find(id)
+            .and_then(|entity| self.world.get_mut::<Building>(entity))
+        else {
+            return false;
+        };
+
+        building.config = config.clone();
+        true
+    }
+}
+
+/// Undoes or redoes the last building edit.
+pub fn undo_redo(mut commands: Commands, actions: Res<ActionState>) {
+    if actions.just_pressed(Action::Undo) {
+        edit_buildings(&mut commands, BuildEdit::Undo, |_, undone| {
+            if !undone {
+                log::info!("Nothing to undo");
+            }
+        });
+    }
+
+    if actions.just_pressed(Action::Redo) {
+        edit_buildings(&mut commands, BuildEdit::Redo, |_, redone| {
+            if !redone {
+                log::info!("Nothing to redo");
+            }
+        });
+    }
+}
+
+/// Turns the building being aimed at while nothing is selected for building.
+pub fn rotate_building(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    spatial_query: SpatialQuery,
+    build_mode: Res<BuildMode>,
+    player: Query<Entity, With<Player>>,
+    camera: Query<&Transform, With<Camera3d>>,
+    buildings: Query<(&BuildingId, &Building)>,
+) {
+    if !actions.just_pressed(Action::Rotate) || build_mode.selected.is_some() {
+        return;
+    }
+
+    let Some((entity, _)) = aim(&spatial_query, camera.single(), player.single()) else {
+        return;
+    };
+    let Ok((id, building)) = buildings.get(entity) else {
+        return;
+    };
+
+    let rotate = RotateBuilding::new(*id, building.rotation, building.rotation.next());
+    edit_buildings(
+        &mut commands,
+        BuildEdit::Execute(Box::new(rotate)),
+        |_, _| {},
+    );
+}
@@ -0,0 +1,47 @@
+mod blueprint;
+mod edit;
+mod placement;
+
+use backend::buildings::{BuildEdits, BuildingIds};
+use backend::history::History;
+use bevy::prelude::*;
+
+use crate::pause::playing;
+
+use self::blueprint::{
+    copy_blueprint, place_blueprint, toggle_paste, update_paste_ghosts, BlueprintTool,
+};
+use self::edit::{rotate_building, undo_redo};
+use self::placement::{
+    deconstruct_building, place_building, select_building, spawn_ghost, update_ghost,
+};
+
+pub use self::edit::{edit_buildings, BuildEdit};
+pub use self::placement::{aim, spawn_building, BuildMode};
+
+pub struct BuildingPlugin;
+
+impl Plugin for BuildingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BuildMode>()
+            .init_resource::<BlueprintTool>()
+            .init_resource::<BuildingIds>()
+            .init_resource::<History<BuildEdits>>()
+            .add_systems(Startup, spawn_ghost)
+            .add_systems(
+                Update,
+                (
+                    select_building.run_if(playing),
+                    update_ghost.after(select_building),
+                    place_building.after(update_ghost),
+                    deconstruct_building.run_if(playing),
+                    rotate_building.after(select_building).run_if(playing),
+                    undo_redo.run_if(playing),
+                    copy_blueprint.run_if(playing),
+                    toggle_paste.run_if(playing),
+                    update_paste_ghosts.after(toggle_paste),
+                    place_blueprint.after(update_paste_ghosts),
+                ),
+            );
+    }
+}
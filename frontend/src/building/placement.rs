@@ -0,0 +1,267 @@
+use backend::buildings::{
+    Building, BuildingConfig, BuildingId, BuildingKind, Deconstruct, PlaceBuilding, Placement,
+    Rotation,
+};
+use backend::input::{Action, ActionState};
+use backend::machines::{Machine, MachineProcess, Power, Reliability};
+use backend::physics::{Layer, PhysicsBundle, SurfaceMaterial};
+use backend::player::profile::PlayerLogic;
+use backend::research::TechTree;
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use super::edit::{edit_buildings, BuildEdit};
+use crate::player::Player;
+
+/// How far from the camera buildings can be placed or taken down.
+const BUILD_REACH: f32 = 20.0;
+
+/// What the player is about to build. Build mode is off while nothing is
+/// selected.
+#[derive(Resource, Debug, Default)]
+pub struct BuildMode {
+    pub selected: Option<BuildingKind>,
+    pub rotation: Rotation,
+}
+
+/// Translucent preview of the building about to be placed.
+#[derive(Component, Debug, Default)]
+pub struct BuildGhost {
+    placement: Option<Placement>,
+    valid: bool,
+}
+
+#[derive(Resource)]
+pub struct GhostAssets {
+    /// A unit cube, scaled to the size of each building.
+    pub mesh: Handle<Mesh>,
+    pub valid: Handle<StandardMaterial>,
+    pub invalid: Handle<StandardMaterial>,
+}
+
+pub fn spawn_ghost(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut translucent = |color: Color| {
+        materials.add(StandardMaterial {
+            base_color: color,
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..Default::default()
+        })
+    };
+
+    let ghost_assets = GhostAssets {
+        mesh: meshes.add(Cuboid::new(1.0, 1.0, 1.0)),
+        valid: translucent(Color::rgba(0.2, 0.8, 0.2, 0.4)),
+        invalid: translucent(Color::rgba(0.8, 0.2, 0.2, 0.4)),
+    };
+
+    commands.spawn((
+        PbrBundle {
+            mesh: ghost_assets.mesh.clone(),
+            material: ghost_assets.valid.clone(),
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        },
+        BuildGhost::default(),
+    ));
+    commands.insert_resource(ghost_assets);
+}
+
+/// Spawns a placed building. Machines also get a [Machine] so they start
+/// wearing down, and what they need to run recipes.
+pub fn spawn_building(
+    world: &mut World,
+    id: BuildingId,
+    placement: &Placement,
+    config: BuildingConfig,
+) -> Entity {
+    let size = placement.size();
+    let mesh = world
+        .resource_mut::<Assets<Mesh>>()
+        .add(Cuboid::new(size.x, size.y, size.z));
+    let material = world
+        .resource_mut::<Assets<StandardMaterial>>()
+        .add(Color::rgb(0.5, 0.5, 0.55));
+    let mut building = world.spawn((
+        PbrBundle {
+            mesh,
+            material,
+            transform: placement.transform(),
+            ..Default::default()
+        },
+        PhysicsBundle::fixed(
+            Layer::Building,
+            Collider::cuboid(size.x, size.y, size.z),
+            SurfaceMaterial::Metal,
+        ),
+        Building {
+            kind: placement.kind,
+            rotation: placement.rotation,
+            config,
+        },
+        id,
+        Name::new(placement.kind.name()),
+    ));
+
+    if placement.kind.is_machine() {
+        building.insert((
+            Machine::new(Reliability::default()),
+            MachineProcess::default(),
+            Power::new(placement.kind.power_draw()),
+        ));
+    }
+
+    building.id()
+}
+
+/// Casts a ray from the camera, ignoring the player. Returns the entity hit
+/// and where it was hit.
+pub fn aim(
+    spatial_query: &SpatialQuery,
+    camera: &Transform,
+    player: Entity,
+) -> Option<(Entity, Vec3)> {
+    let filter = SpatialQueryFilter::default().with_excluded_entities([player]);
+    let hit = spatial_query.cast_ray(
+        camera.translation,
+        camera.forward(),
+        BUILD_REACH,
+        true,
+        filter,
+    )?;
+
+    Some((
+        hit.entity,
+        camera.translation + *camera.forward() * hit.time_of_impact,
+    ))
+}
+
+/// Cycles through the buildings in the player's inventory that they have
+/// researched, and rotates the selected one.
+pub fn select_building(
+    actions: Res<ActionState>,
+    tree: Res<TechTree>,
+    mut build_mode: ResMut<BuildMode>,
+    player: Query<(&Player, &PlayerLogic)>,
+) {
+    if actions.just_pressed(Action::Rotate) {
+        build_mode.rotation = build_mode.rotation.next();
+    }
+
+    if !actions.just_pressed(Action::SelectBuilding) {
+        return;
+    }
+
+    let (player, profile) = player.single();
+    let start = build_mode
+        .selected
+        .and_then(|selected| BuildingKind::ALL.iter().position(|kind| *kind == selected))
+        .map_or(0, |index| index + 1);
+
+    build_mode.selected = BuildingKind::ALL[start..]
+        .iter()
+        .find(|kind| {
+            player.inventory.amount_of(kind.name()) >= 1.0 && tree.can_build(kind.id(), profile)
+        })
+        .copied();
+}
+
+/// Moves the ghost to where the player is aiming. It shows as invalid where
+/// the building would overlap another, or if it has not been researched.
+pub fn update_ghost(
+    build_mode: Res<BuildMode>,
+    tree: Res<TechTree>,
+    spatial_query: SpatialQuery,
+    player: Query<(Entity, &PlayerLogic), With<Player>>,
+    camera: Query<&Transform, (With<Camera3d>, Without<BuildGhost>)>,
+    assets: Res<GhostAssets>,
+    mut ghost: Query<(
+        &mut BuildGhost,
+        &mut Transform,
+        &mut Visibility,
+        &mut Handle<StandardMaterial>,
+    )>,
+) {
+    let (mut ghost, mut transform, mut visibility, mut material) = ghost.single_mut();
+    let (player, profile) = player.single();
+
+    let aimed = build_mode
+        .selected
+        .zip(aim(&spatial_query, camera.single(), player));
+    let Some((kind, (_, point))) = aimed else {
+        ghost.placement = None;
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    let placement = Placement::at(kind, point, build_mode.rotation);
+    ghost.valid = tree.can_build(kind.id(), profile) && !placement.is_blocked(&spatial_query);
+    ghost.placement = Some(placement);
+
+    *transform = placement.transform().with_scale(placement.size());
+    *visibility = Visibility::Visible;
+    *material = match ghost.valid {
+        true => assets.valid.clone(),
+        false => assets.invalid.clone(),
+    };
+}
+
+pub fn place_building(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    ghost: Query<&BuildGhost>,
+    player: Query<&Player>,
+) {
+    if !actions.just_pressed(Action::Place) || !player.single().movement_enabled {
+        return;
+    }
+
+    let ghost = ghost.single();
+    let Some(placement) = ghost.placement.filter(|_| ghost.valid) else {
+        return;
+    };
+
+    let place = PlaceBuilding::new(placement, BuildingConfig::default());
+    edit_buildings(
+        &mut commands,
+        BuildEdit::Execute(Box::new(place)),
+        move |world, placed| {
+            let mut players = world.query::<&Player>();
+            let inventory = &players.single(world).inventory;
+            if !placed || inventory.amount_of(placement.kind.name()) < 1.0 {
+                world.resource_mut::<BuildMode>().selected = None;
+            }
+        },
+    );
+}
+
+/// Takes down the building being aimed at and gives its item back.
+pub fn deconstruct_building(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    spatial_query: SpatialQuery,
+    player: Query<Entity, With<Player>>,
+    camera: Query<&Transform, With<Camera3d>>,
+    buildings: Query<&BuildingId>,
+) {
+    if !actions.just_pressed(Action::Deconstruct) {
+        return;
+    }
+
+    let Some((entity, _)) = aim(&spatial_query, camera.single(), player.single()) else {
+        return;
+    };
+
+    if let Ok(id) = buildings.get(entity) {
+        let deconstruct = Deconstruct::new(*id);
+        edit_buildings(
+            &mut commands,
+            BuildEdit::Execute(Box::new(deconstruct)),
+            |_, _| {},
+        );
+    }
+}
@@ -1,9 +1,105 @@
+use std::f32::consts::PI;
+
+use backend::input::{Action, ActionState};
+use backend::player::controller::Frozen;
+use backend::settings::CameraSettings;
 use bevy::prelude::*;
 
+use crate::pause::playing;
+use crate::player::Player;
+
+/// How long switching between camera modes takes, in seconds.
+pub const TRANSITION_TIME: f32 = 0.3;
+/// Speed of the free-fly camera in m/s.
+pub const FREE_FLY_SPEED: f32 = 10.0;
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraState>()
+            .add_systems(Update, switch_camera_mode.run_if(playing));
+    }
+}
+
+/// How the camera follows the player. [Action::SwitchCamera] cycles through
+/// them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CameraMode {
+    #[default]
+    ThirdPerson,
+    FirstPerson,
+    /// Flies freely while the player stands still, for spectating and
+    /// debugging.
+    FreeFly,
+}
+
+impl CameraMode {
+    #[rustfmt::skip]
+    pub fn next(&self) -> Self {
+        match self {
+            CameraMode::ThirdPerson => CameraMode::FirstPerson,
+            CameraMode::FirstPerson => CameraMode::FreeFly,
+            CameraMode::FreeFly     => CameraMode::ThirdPerson,
+        }
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct CameraState {
+    pub mode: CameraMode,
+    /// Seconds left until the camera has moved over to the new mode's view.
+    pub transition: f32,
+    /// Yaw and pitch from the mouse not applied yet because of smoothing.
+    pub pending_look: Vec2,
+}
+
+/// Cycles the camera mode. The player model is hidden in first person so it
+/// does not block the view, and held in place while the camera flies free.
+pub fn switch_camera_mode(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    mut state: ResMut<CameraState>,
+    mut player_model: Query<(Entity, &mut Visibility), With<Player>>,
+) {
+    if !actions.just_pressed(Action::SwitchCamera) {
+        return;
+    }
+
+    state.mode = state.mode.next();
+    // The free-fly camera starts where the camera already is.
+    state.transition = match state.mode {
+        CameraMode::FreeFly => 0.0,
+        _ => TRANSITION_TIME,
+    };
+
+    if let Ok((player, mut visibility)) = player_model.get_single_mut() {
+        *visibility = match state.mode {
+            CameraMode::FirstPerson => Visibility::Hidden,
+            _ => Visibility::Inherited,
+        };
+        match state.mode {
+            CameraMode::FreeFly => commands.entity(player).insert(Frozen),
+            _ => commands.entity(player).remove::<Frozen>(),
+        };
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ThirdPersonCameraData {
+    /// Direction of the camera from the target, before rotation. Its length is
+    /// ignored in favour of [ThirdPersonCameraData::distance].
     pub offset: Vec3,
     pub y_rotation: f32,
+    /// Distance from the target the player has zoomed to.
+    pub distance: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    /// Distance the camera is actually at, after being pulled in by walls and
+    /// smoothed.
+    pub current_distance: f32,
+    /// How quickly the camera moves back out, per second.
+    pub smoothing: f32,
 }
 
 impl Default for ThirdPersonCameraData {
@@ -11,39 +107,245 @@ impl Default for ThirdPersonCameraData {
         Self {
             offset: Vec3::new(0.0, 2.0, -10.0),
             y_rotation: 0.0,
+            distance: 10.0,
+            min_distance: 2.0,
+            max_distance: 20.0,
+            current_distance: 10.0,
+            smoothing: 8.0,
         }
     }
 }
 
+/// Radius of the sphere cast to keep the camera out of walls.
+pub const CAMERA_RADIUS: f32 = 0.3;
+/// How far one line of scrolling zooms.
+pub const ZOOM_STEP: f32 = 1.0;
+
+/// Zooms in for positive `scroll`, in lines, and out for negative.
+pub fn zoom(camera_data: &mut ThirdPersonCameraData, scroll: f32) {
+    camera_data.distance = (camera_data.distance - scroll * ZOOM_STEP)
+        .clamp(camera_data.min_distance, camera_data.max_distance);
+}
+
 /// Orbits about the y axis according to [Player]'s x rotation and about the x
 /// axis according to [ThirdPersonCameraData]'s y rotation. This function
 /// updates [ThirdPersonCameraData]'s y rotation based on camera_y_rotation
-/// input.
+/// input, within the pitch limits of the [CameraSettings]. The camera is
+/// pulled in front of anything between it and the target straight away, and
+/// eases back out once the way is clear.
 ///
 /// # Arguments
-/// * `player_transform` - [Player]'s transform. Rotation & translation are
-/// followed by the camera.
+/// * `target_pos` - Where the camera looks, usually the [Player]'s head.
+/// * `target_rotation` - The [Player]'s rotation, followed by the camera.
+/// * `settings` - Limits the pitch.
 /// * `camera_transform` - The transform to update.
 /// * `camera_data` - The camera data to be updated and used. Holds y rotation
-/// state and offset to use.
-/// * `camera_y_rotation` - How much more to rotate the camera about the x axis
+///   state, zoom and offset to use.
+/// * `camera_y_rotation` - How much more to rotate the camera about the x axis,
+///   in radians.
+/// * `dt` - Seconds since the last update.
+/// * `cast` - Casts the camera from the target along a direction, up to a
+///   distance, returning how far it got before hitting something.
+#[allow(clippy::too_many_arguments)]
 pub fn third_person_camera_update(
     target_pos: &Vec3,
     target_rotation: &Quat,
-    sensitivity: f32,
+    settings: &CameraSettings,
     camera_transform: &mut Transform,
     camera_data: &mut ThirdPersonCameraData,
     camera_y_rotation: f32,
+    dt: f32,
+    cast: impl Fn(Vec3, Vec3, f32) -> Option<f32>,
 ) {
-    camera_data.y_rotation =
-        (camera_y_rotation * sensitivity / 1.5 + camera_data.y_rotation).clamp(-1.0, 1.0);
+    camera_data.y_rotation = settings.clamp_pitch(camera_data.y_rotation + camera_y_rotation);
 
     let player_head_pos = target_pos;
     let player_rotation_mod = target_rotation;
     let camera_rotation_mod = Quat::from_axis_angle(Vec3::X, camera_data.y_rotation);
+    let direction =
+        (*player_rotation_mod * camera_rotation_mod * camera_data.offset).normalize_or_zero();
 
-    let total_offset = *player_head_pos + *player_rotation_mod * camera_rotation_mod * camera_data.offset;
+    let clear_distance = cast(*player_head_pos, direction, camera_data.distance)
+        .map_or(camera_data.distance, |hit| hit.min(camera_data.distance));
+
+    camera_data.current_distance = match clear_distance < camera_data.current_distance {
+        true => clear_distance,
+        false => {
+            let amount = 1.0 - (-camera_data.smoothing * dt).exp();
+            camera_data.current_distance + (clear_distance - camera_data.current_distance) * amount
+        }
+    };
 
-    camera_transform.translation = total_offset;
+    camera_transform.translation = *player_head_pos + direction * camera_data.current_distance;
     camera_transform.look_at(*player_head_pos, Vec3::Y);
 }
+
+/// Looks out of the target's head, turned with it about the y axis and about
+/// the x axis according to [ThirdPersonCameraData]'s y rotation, which is
+/// shared with the third person camera so switching keeps the pitch.
+pub fn first_person_camera_update(
+    target_pos: &Vec3,
+    target_rotation: &Quat,
+    settings: &CameraSettings,
+    camera_transform: &mut Transform,
+    camera_data: &mut ThirdPersonCameraData,
+    camera_y_rotation: f32,
+) {
+    camera_data.y_rotation = settings.clamp_pitch(camera_data.y_rotation + camera_y_rotation);
+
+    // The third person camera sits behind the target on -z, so the target
+    // faces +z.
+    camera_transform.translation = *target_pos;
+    camera_transform.rotation = *target_rotation
+        * Quat::from_rotation_y(PI)
+        * Quat::from_rotation_x(-camera_data.y_rotation);
+}
+
+/// Moves the camera by `distance` along `direction` after turning it by
+/// `look`, the yaw and pitch from [CameraSettings::look_delta]. The x and z of
+/// `direction` are relative to the way the camera faces, its y is straight
+/// up.
+pub fn free_fly_camera_update(
+    camera_transform: &mut Transform,
+    settings: &CameraSettings,
+    direction: Vec3,
+    look: Vec2,
+    distance: f32,
+) {
+    // Euler pitch is positive looking up, the settings' looking down.
+    let (yaw, pitch, _) = camera_transform.rotation.to_euler(EulerRot::YXZ);
+    let pitch = -settings.clamp_pitch(look.y - pitch);
+    camera_transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw + look.x, pitch, 0.0);
+
+    let facing = camera_transform.rotation * Vec3::new(direction.x, 0.0, direction.z);
+    let movement = facing + Vec3::Y * direction.y;
+    camera_transform.translation += movement.normalize_or_zero() * distance;
+}
+
+/// Moves the camera part of the way to `target`, so that it arrives just as the
+/// `remaining` transition time runs out. Returns the time still remaining.
+pub fn blend_camera(
+    camera_transform: &mut Transform,
+    target: &Transform,
+    remaining: f32,
+    dt: f32,
+) -> f32 {
+    let amount = match remaining > dt {
+        true => dt / remaining,
+        false => 1.0,
+    };
+
+    camera_transform.translation = camera_transform
+        .translation
+        .lerp(target.translation, amount);
+    camera_transform.rotation = camera_transform.rotation.slerp(target.rotation, amount);
+    (remaining - dt).max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(camera_data: &mut ThirdPersonCameraData, wall: Option<f32>, dt: f32) -> Transform {
+        let mut camera_transform = Transform::default();
+        third_person_camera_update(
+            &Vec3::ZERO,
+            &Quat::IDENTITY,
+            &CameraSettings::default(),
+            &mut camera_transform,
+            camera_data,
+            0.0,
+            dt,
+            |_, _, max| wall.filter(|distance| *distance < max),
+        );
+        camera_transform
+    }
+
+    #[test]
+    fn test_camera_at_zoom_distance() {
+        let mut camera_data = ThirdPersonCameraData::default();
+        let camera_transform = update(&mut camera_data, None, 0.016);
+
+        assert!((camera_transform.translation.length() - camera_data.distance).abs() < 1e-4);
+        assert!(
+            camera_transform
+                .forward()
+                .dot(-camera_transform.translation.normalize())
+                > 0.999
+        );
+    }
+
+    #[test]
+    fn test_wall_pulls_camera_in_at_once() {
+        let mut camera_data = ThirdPersonCameraData::default();
+        let camera_transform = update(&mut camera_data, Some(3.0), 0.016);
+
+        assert_eq!(camera_data.current_distance, 3.0);
+        assert!((camera_transform.translation.length() - 3.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_camera_eases_back_out() {
+        let mut camera_data = ThirdPersonCameraData::default();
+        update(&mut camera_data, Some(3.0), 0.016);
+
+        update(&mut camera_data, None, 0.016);
+        assert!(camera_data.current_distance > 3.0);
+        assert!(camera_data.current_distance < camera_data.distance);
+
+        for _ in 0..200 {
+            update(&mut camera_data, None, 0.016);
+        }
+        assert!((camera_data.current_distance - camera_data.distance).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_zoom_is_clamped() {
+        let mut camera_data = ThirdPersonCameraData::default();
+
+        zoom(&mut camera_data, 100.0);
+        assert_eq!(camera_data.distance, camera_data.min_distance);
+
+        zoom(&mut camera_data, -100.0);
+        assert_eq!(camera_data.distance, camera_data.max_distance);
+    }
+
+    #[test]
+    fn test_camera_modes_cycle() {
+        let mode = CameraMode::default();
+        assert_eq!(mode.next(), CameraMode::FirstPerson);
+        assert_eq!(mode.next().next(), CameraMode::FreeFly);
+        assert_eq!(mode.next().next().next(), mode);
+    }
+
+    #[test]
+    fn test_free_fly_follows_the_view() {
+        let rotation = Quat::from_euler(EulerRot::YXZ, PI / 2.0, -0.5, 0.0);
+        let mut camera_transform = Transform::from_rotation(rotation);
+        let forward = camera_transform.forward();
+        free_fly_camera_update(
+            &mut camera_transform,
+            &CameraSettings::default(),
+            Vec3::NEG_Z,
+            Vec2::ZERO,
+            2.0,
+        );
+
+        assert!((camera_transform.translation - *forward * 2.0).length() < 1e-4);
+    }
+
+    #[test]
+    fn test_free_fly_rises_straight_up() {
+        let rotation = Quat::from_euler(EulerRot::YXZ, 1.0, -0.5, 0.0);
+        let mut camera_transform = Transform::from_rotation(rotation);
+        free_fly_camera_update(
+            &mut camera_transform,
+            &CameraSettings::default(),
+            Vec3::Y,
+            Vec2::ZERO,
+            2.0,
+        );
+
+        assert!((camera_transform.translation - Vec3::new(0.0, 2.0, 0.0)).length() < 1e-4);
+    }
+}
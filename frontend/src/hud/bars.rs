@@ -0,0 +1,76 @@
+use backend::player::health::Health;
+use backend::player::stamina::Stamina;
+use bevy::prelude::*;
+
+use crate::player::Player;
+
+/// Which of the player's bars a fill belongs to.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarFill {
+    Health,
+    Stamina,
+}
+
+pub fn bars_display(mut commands: Commands) {
+    let bars_ui = NodeBundle {
+        style: Style {
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.0),
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    commands.spawn(bars_ui).with_children(|parent| {
+        for (fill, color) in [
+            (BarFill::Health, Color::rgb(0.8, 0.15, 0.15)),
+            (BarFill::Stamina, Color::rgb(0.85, 0.75, 0.2)),
+        ] {
+            let bar = NodeBundle {
+                style: Style {
+                    width: Val::Px(200.0),
+                    height: Val::Px(12.0),
+                    ..Default::default()
+                },
+                background_color: Color::rgba(0.1, 0.1, 0.1, 0.6).into(),
+                ..Default::default()
+            };
+            parent.spawn(bar).with_children(|bar| {
+                bar.spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            ..Default::default()
+                        },
+                        background_color: color.into(),
+                        ..Default::default()
+                    },
+                    fill,
+                ));
+            });
+        }
+    });
+}
+
+pub fn update_bars(
+    player: Query<(&Health, &Stamina), With<Player>>,
+    mut fills: Query<(&mut Style, &BarFill)>,
+) {
+    let (health, stamina) = player.single();
+
+    for (mut style, fill) in fills.iter_mut() {
+        let (current, max) = match fill {
+            BarFill::Health => (health.current, health.max),
+            BarFill::Stamina => (stamina.current, stamina.max),
+        };
+        let width = Val::Percent((current / max).clamp(0.0, 1.0) * 100.0);
+        if style.width != width {
+            style.width = width;
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! What is drawn over the game while playing: the player's bars, a
+//! crosshair, what the crosshair is on and what was just picked up.
+
+mod bars;
+mod notifications;
+mod target;
+
+use bevy::prelude::*;
+
+use self::bars::{bars_display, update_bars};
+use self::notifications::{fade_notifications, notifications_display, show_pickup_notifications};
+use self::target::{target_display, update_target_info};
+
+/// Side of the crosshair, in px.
+const CROSSHAIR_SIZE: f32 = 4.0;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Startup,
+            (
+                bars_display,
+                crosshair_display,
+                target_display,
+                notifications_display,
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                update_bars,
+                update_target_info,
+                show_pickup_notifications,
+                fade_notifications.after(show_pickup_notifications),
+            ),
+        );
+    }
+}
+
+#[derive(Component)]
+pub struct CrosshairUIMarker;
+
+pub fn crosshair_display(mut commands: Commands) {
+    let crosshair = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(50.0),
+            top: Val::Percent(50.0),
+            width: Val::Px(CROSSHAIR_SIZE),
+            height: Val::Px(CROSSHAIR_SIZE),
+            margin: UiRect::all(Val::Px(-CROSSHAIR_SIZE / 2.0)),
+            ..Default::default()
+        },
+        background_color: Color::rgba(1.0, 1.0, 1.0, 0.8).into(),
+        ..Default::default()
+    };
+
+    commands.spawn((crosshair, CrosshairUIMarker));
+}
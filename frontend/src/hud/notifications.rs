@@ -0,0 +1,89 @@
+use backend::iams::events::InventoryChanged;
+use bevy::prelude::*;
+
+use crate::player::Player;
+
+/// Seconds a pickup stays on screen after the last of it came in.
+const NOTIFICATION_TIME: f32 = 3.0;
+
+/// Seconds at the end of [NOTIFICATION_TIME] spent fading out.
+const FADE_TIME: f32 = 1.0;
+
+#[derive(Component)]
+pub struct NotificationsUIMarker;
+
+/// One line saying what was picked up. More of the same item while it is
+/// showing is added onto it, so mining does not flood the screen.
+#[derive(Component, Debug)]
+pub struct PickupNotification {
+    change: InventoryChanged,
+    remaining: f32,
+}
+
+pub fn notifications_display(mut commands: Commands) {
+    let notifications_ui = NodeBundle {
+        style: Style {
+            display: Display::Flex,
+            flex_direction: FlexDirection::ColumnReverse,
+            align_items: AlignItems::FlexEnd,
+            position_type: PositionType::Absolute,
+            right: Val::Px(10.0),
+            bottom: Val::Px(80.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    commands.spawn((notifications_ui, NotificationsUIMarker));
+}
+
+pub fn show_pickup_notifications(
+    mut commands: Commands,
+    mut changes: EventReader<InventoryChanged>,
+    player: Query<Entity, With<Player>>,
+    root: Query<Entity, With<NotificationsUIMarker>>,
+    mut notifications: Query<(&mut PickupNotification, &mut Text)>,
+) {
+    let player = player.single();
+
+    for change in changes.read() {
+        if change.entity != player || change.change <= 0.0 {
+            continue;
+        }
+
+        let existing = notifications
+            .iter_mut()
+            .find(|(notification, _)| notification.change.item == change.item);
+        if let Some((mut notification, mut text)) = existing {
+            notification.change.change += change.change;
+            notification.remaining = NOTIFICATION_TIME;
+            text.sections[0].value = notification.change.label();
+            continue;
+        }
+
+        let text = TextBundle::from_section(change.label(), Default::default());
+        let notification = PickupNotification {
+            change: change.clone(),
+            remaining: NOTIFICATION_TIME,
+        };
+        let line = commands.spawn((text, notification)).id();
+        commands.entity(root.single()).add_child(line);
+    }
+}
+
+pub fn fade_notifications(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut notifications: Query<(Entity, &mut PickupNotification, &mut Text)>,
+) {
+    for (entity, mut notification, mut text) in notifications.iter_mut() {
+        notification.remaining -= time.delta_seconds();
+        if notification.remaining <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let alpha = (notification.remaining / FADE_TIME).min(1.0);
+        text.sections[0].style.color.set_a(alpha);
+    }
+}
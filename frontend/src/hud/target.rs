@@ -0,0 +1,65 @@
+use backend::machines::Machine;
+use backend::mining::OreDeposit;
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::SpatialQuery;
+
+use crate::building::aim;
+use crate::player::Player;
+
+#[derive(Component)]
+pub struct TargetUIMarker;
+
+pub fn target_display(mut commands: Commands) {
+    let target_ui = NodeBundle {
+        style: Style {
+            display: Display::Flex,
+            justify_content: JustifyContent::Center,
+            position_type: PositionType::Absolute,
+            top: Val::Percent(53.0),
+            width: Val::Percent(100.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let text = TextBundle::from_section(
+        "",
+        TextStyle {
+            font_size: 16.0,
+            ..Default::default()
+        },
+    )
+    .with_text_justify(JustifyText::Center);
+
+    commands.spawn(target_ui).with_children(|parent| {
+        parent.spawn((text, TargetUIMarker));
+    });
+}
+
+/// Names whatever is under the crosshair, with the state of machines and
+/// what is left of ore deposits.
+pub fn update_target_info(
+    spatial_query: SpatialQuery,
+    camera: Query<&Transform, (With<Camera3d>, Without<Player>)>,
+    player: Query<Entity, With<Player>>,
+    targets: Query<(&Name, Option<&Machine>, Option<&OreDeposit>)>,
+    mut text: Query<&mut Text, With<TargetUIMarker>>,
+) {
+    let mut text = text.single_mut();
+
+    let target = aim(&spatial_query, camera.single(), player.single())
+        .and_then(|(target, _)| targets.get(target).ok());
+    let info = match target {
+        Some((name, machine, deposit)) => {
+            let mut lines = vec![name.to_string()];
+            lines.extend(machine.map(|machine| machine.state.label()));
+            lines.extend(deposit.map(|deposit| format!("{:.1} kg left", deposit.remaining)));
+            lines.join("\n")
+        }
+        None => String::new(),
+    };
+
+    if text.sections[0].value != info {
+        text.sections[0].value = info;
+    }
+}
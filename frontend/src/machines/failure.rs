@@ -0,0 +1,68 @@
+use backend::buildings::{Building, GRID_SIZE};
+use backend::machines::{FailureEffect, Machine, MachineSpilled, MachineState, FIRE_RADIUS};
+use bevy::prelude::*;
+
+use crate::player::{container_bundle, ContainerAssets};
+
+/// The flames over a burning machine.
+#[derive(Component, Debug)]
+pub struct MachineFire;
+
+/// Puts what spilling machines threw out in a crate on top of them. The
+/// items are taken out of the events, so nothing else reads them.
+pub fn spill_machine_contents(
+    mut commands: Commands,
+    assets: Res<ContainerAssets>,
+    mut spills: ResMut<Events<MachineSpilled>>,
+    machines: Query<(&Building, &Transform)>,
+) {
+    for spill in spills.drain() {
+        let Ok((building, transform)) = machines.get(spill.machine) else {
+            continue;
+        };
+
+        let height = building.kind.footprint().y as f32 * GRID_SIZE;
+        let position = transform.translation + Vec3::Y * (height / 2.0 + 0.5);
+        commands.spawn(container_bundle(spill.items, position, &assets));
+    }
+}
+
+/// Lights a fire over machines that caught fire, and puts it out once they
+/// are repaired.
+pub fn show_machine_fires(
+    mut commands: Commands,
+    machines: Query<(Entity, &Building, &Machine), Changed<Machine>>,
+    fires: Query<(Entity, &Parent), With<MachineFire>>,
+) {
+    for (entity, building, machine) in machines.iter() {
+        let on_fire = machine.state == MachineState::Failed(FailureEffect::Fire);
+        let fire = fires
+            .iter()
+            .find(|(_, parent)| parent.get() == entity)
+            .map(|(fire, _)| fire);
+
+        match (on_fire, fire) {
+            (true, None) => {
+                let height = building.kind.footprint().y as f32 * GRID_SIZE;
+                let fire = commands
+                    .spawn((
+                        PointLightBundle {
+                            point_light: PointLight {
+                                color: Color::rgb(1.0, 0.45, 0.1),
+                                intensity: 200_000.0,
+                                range: FIRE_RADIUS * 2.0,
+                                ..Default::default()
+                            },
+                            transform: Transform::from_xyz(0.0, height / 2.0 + 0.3, 0.0),
+                            ..Default::default()
+                        },
+                        MachineFire,
+                    ))
+                    .id();
+                commands.entity(entity).add_child(fire);
+            }
+            (false, Some(fire)) => commands.entity(fire).despawn_recursive(),
+            _ => {}
+        }
+    }
+}
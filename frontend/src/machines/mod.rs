@@ -0,0 +1,106 @@
+//! The panel machines are configured through, opened by interacting with a
+//! machine. It is laid out from a [MachinePanel](backend::machines::MachinePanel)
+//! with the shared [widgets](crate::widgets), so every kind of machine gets
+//! one. Machines that fail also show it in the world.
+
+mod failure;
+mod panel;
+
+use backend::input::{Action, ActionState};
+use backend::items::ItemAction;
+use backend::machines::Machine;
+use backend::player::hotbar::Hotbar;
+use bevy::prelude::*;
+use bevy::window::{CursorGrabMode, PrimaryWindow};
+use bevy_xpbd_3d::prelude::SpatialQuery;
+
+use self::failure::{show_machine_fires, spill_machine_contents};
+use self::panel::{
+    handle_machine_input, machine_panel, refresh_machine_panel, select_machine_recipe,
+    update_machine_bars,
+};
+use crate::building::aim;
+use crate::player::Player;
+
+/// How close the player has to be to a machine to open its panel.
+const MACHINE_REACH: f32 = 4.0;
+
+pub struct MachinePanelPlugin;
+
+impl Plugin for MachinePanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OpenMachine>()
+            .add_systems(Startup, machine_panel)
+            .add_systems(
+                Update,
+                (
+                    open_machine_panel,
+                    handle_machine_input.after(open_machine_panel),
+                    select_machine_recipe.after(open_machine_panel),
+                    refresh_machine_panel
+                        .after(handle_machine_input)
+                        .after(select_machine_recipe),
+                    update_machine_bars.after(refresh_machine_panel),
+                    spill_machine_contents,
+                    show_machine_fires,
+                ),
+            );
+    }
+}
+
+/// The machine whose panel is open.
+#[derive(Resource, Debug, Default)]
+pub struct OpenMachine(pub Option<Entity>);
+
+/// Opens the panel of the machine being aimed at, and closes it again on a
+/// second interaction or on pausing. Holding a wrench leaves
+/// interacting for repairs.
+pub fn open_machine_panel(
+    actions: Res<ActionState>,
+    spatial_query: SpatialQuery,
+    mut open: ResMut<OpenMachine>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+    camera: Query<&Transform, (With<Camera3d>, Without<Player>)>,
+    mut player: Query<(Entity, &Transform, &mut Player, &Hotbar)>,
+    machines: Query<(), With<Machine>>,
+) {
+    let mut window = window.single_mut();
+    let (player_entity, player_transform, mut player, hotbar) = player.single_mut();
+
+    if let Some(machine) = open.0 {
+        let closing = actions.just_pressed(Action::Interact)
+            || actions.just_pressed(Action::Pause)
+            || !machines.contains(machine);
+        if closing {
+            close_machine_panel(&mut open, &mut window, &mut player);
+        }
+        return;
+    }
+
+    if !actions.just_pressed(Action::Interact) || !player.movement_enabled {
+        return;
+    }
+    if hotbar.held_action(&player.inventory) == Some(ItemAction::Repair) {
+        return;
+    }
+
+    let Some((target, point)) = aim(&spatial_query, camera.single(), player_entity) else {
+        return;
+    };
+    if !machines.contains(target) || point.distance(player_transform.translation) > MACHINE_REACH {
+        return;
+    }
+
+    open.0 = Some(target);
+    window.cursor.grab_mode = CursorGrabMode::Confined;
+    window.cursor.visible = true;
+    player.movement_enabled = false;
+}
+
+/// Closes the panel and gives the player back control of the camera.
+fn close_machine_panel(open: &mut OpenMachine, window: &mut Window, player: &mut Player) {
+    open.0 = None;
+    window.cursor.grab_mode = CursorGrabMode::Locked;
+    window.cursor.visible = false;
+    player.movement_enabled = true;
+}
@@ -0,0 +1,294 @@
+use backend::buildings::{Building, BuildingConfig, BuildingId, BuildingKind, ConfigureBuilding};
+use backend::iams::inventory::ItemKey;
+use backend::iams::view::ItemView;
+use backend::iams::Inventory;
+use backend::machines::{Machine, MachinePanel, MachineProcess, Power};
+use backend::player::profile::PlayerLogic;
+use backend::recipes::RecipeBook;
+use backend::research::TechTree;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use super::{close_machine_panel, OpenMachine};
+use crate::building::{edit_buildings, BuildEdit};
+use crate::player::{ChangedButton, Player};
+use crate::widgets::{button, heading, label, panel, progress_bar, row, section, ProgressBar};
+
+#[derive(Component)]
+pub struct MachineUIMarker;
+
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub enum MachineUIButton {
+    /// Sets the machine to the recipe with this id, or stops it if it is
+    /// already making it.
+    Recipe(String),
+    /// Moves everything the recipe takes from the player into the machine.
+    Load,
+    /// Gives everything loaded back to the player.
+    Unload,
+    /// Gives everything made to the player.
+    Collect,
+    Close,
+}
+
+/// Which of the machine's bars a fill belongs to.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MachineUIBar {
+    Progress,
+    Wear,
+}
+
+pub fn machine_panel(mut commands: Commands) {
+    commands.spawn((panel(Val::Vw(40.0), Val::Vh(70.0)), MachineUIMarker));
+}
+
+fn item_label(item: &ItemView) -> String {
+    match &item.purity {
+        Some(purity) => format!("{} {} ({purity})", item.type_name, item.amount),
+        None => format!("{} {}", item.type_name, item.amount),
+    }
+}
+
+fn items_section(
+    parent: &mut ChildBuilder,
+    title: &str,
+    items: &[ItemView],
+    buttons: &[(&str, MachineUIButton)],
+) {
+    section(parent, title, |section| {
+        if items.is_empty() {
+            label(section, "Empty");
+        }
+        for item in items {
+            label(section, item_label(item));
+        }
+        row(section, |row| {
+            for (text, marker) in buttons {
+                button(row, *text, false, marker.clone());
+            }
+        });
+    });
+}
+
+fn recipe_section(parent: &mut ChildBuilder, view: &MachinePanel) {
+    section(parent, "Recipe", |section| {
+        if view.recipes.is_empty() {
+            label(section, "Nothing to make");
+            return;
+        }
+        row(section, |row| {
+            for choice in &view.recipes {
+                let marker = MachineUIButton::Recipe(choice.id.clone());
+                button(row, choice.name.clone(), choice.selected, marker);
+            }
+        });
+        if !view.needs.is_empty() {
+            label(section, format!("Takes {}", view.needs.join(", ")));
+        }
+        if !view.makes.is_empty() {
+            label(section, format!("Makes {}", view.makes.join(", ")));
+        }
+    });
+}
+
+/// Draws the panel of the open machine, and hides it while none is open.
+/// Redrawn when it is opened and whenever what it shows changes.
+pub fn refresh_machine_panel(
+    mut commands: Commands,
+    open: Res<OpenMachine>,
+    recipes: Res<RecipeBook>,
+    player: Query<&PlayerLogic, With<Player>>,
+    machines: Query<(&Building, &Machine, &MachineProcess, &Power)>,
+    mut menu: Query<(Entity, &mut Visibility), With<MachineUIMarker>>,
+    mut shown: Local<Option<MachinePanel>>,
+) {
+    let (menu, mut visibility) = menu.single_mut();
+    let profile = player.single();
+    let view = open.0.and_then(|entity| machines.get(entity).ok()).map(
+        |(building, machine, process, power)| {
+            MachinePanel::new(building, machine, process, power, &recipes, profile)
+        },
+    );
+
+    let Some(view) = view else {
+        visibility.set_if_neq(Visibility::Hidden);
+        *shown = None;
+        return;
+    };
+    visibility.set_if_neq(Visibility::Visible);
+    if shown.as_ref() == Some(&view) {
+        return;
+    }
+
+    commands
+        .entity(menu)
+        .despawn_descendants()
+        .with_children(|parent| {
+            row(parent, |header| {
+                heading(header, view.title);
+                button(header, "Close", false, MachineUIButton::Close);
+            });
+            label(parent, view.state.clone());
+            label(parent, view.power.clone());
+            recipe_section(parent, &view);
+            section(parent, "Progress", |section| {
+                progress_bar(section, Color::rgb(0.2, 0.6, 0.9), MachineUIBar::Progress);
+            });
+            section(parent, "Wear", |section| {
+                progress_bar(section, Color::rgb(0.9, 0.5, 0.1), MachineUIBar::Wear);
+            });
+            items_section(
+                parent,
+                "Input",
+                &view.input,
+                &[
+                    ("Load", MachineUIButton::Load),
+                    ("Unload", MachineUIButton::Unload),
+                ],
+            );
+            items_section(
+                parent,
+                "Output",
+                &view.output,
+                &[("Collect", MachineUIButton::Collect)],
+            );
+        });
+
+    *shown = Some(view);
+}
+
+/// Keeps the progress and wear bars up with the open machine.
+pub fn update_machine_bars(
+    open: Res<OpenMachine>,
+    machines: Query<(&Machine, &MachineProcess)>,
+    mut bars: Query<(&MachineUIBar, &mut ProgressBar)>,
+) {
+    let Some((machine, process)) = open.0.and_then(|entity| machines.get(entity).ok()) else {
+        return;
+    };
+
+    for (bar, mut progress) in bars.iter_mut() {
+        let value = match bar {
+            MachineUIBar::Progress => process.progress,
+            MachineUIBar::Wear => machine.wear_fraction(),
+        };
+        progress.set_if_neq(ProgressBar(value));
+    }
+}
+
+/// Moves every item of `from` that `moves` picks into `to`.
+fn move_items(from: &mut Inventory, to: &mut Inventory, moves: impl Fn(&str) -> bool) {
+    let keys: Vec<ItemKey> = from
+        .get_all()
+        .into_iter()
+        .filter(|item| moves(item.type_name()))
+        .map(ItemKey::of)
+        .collect();
+
+    for key in keys {
+        from.move_item(key, None, to);
+    }
+}
+
+/// Moves items between the player and the open machine, and closes the
+/// panel. Labs are loaded with whatever the research on offer costs.
+pub fn handle_machine_input(
+    mut open: ResMut<OpenMachine>,
+    recipes: Res<RecipeBook>,
+    tree: Res<TechTree>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+    mut player: Query<(Entity, &mut Player, &PlayerLogic)>,
+    mut machines: Query<(&Building, &mut MachineProcess)>,
+    interaction: Query<(&Interaction, &MachineUIButton), ChangedButton>,
+) {
+    let Some(machine) = open.0 else {
+        return;
+    };
+    let Ok((building, mut process)) = machines.get_mut(machine) else {
+        return;
+    };
+    let (player_entity, mut player, profile) = player.single_mut();
+    let process = &mut *process;
+
+    for (interaction, button) in interaction.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            MachineUIButton::Load if building.kind == BuildingKind::Lab => {
+                move_items(&mut player.inventory, &mut process.input, |item| {
+                    tree.available(profile)
+                        .any(|technology| technology.cost.iter().any(|cost| cost.item == item))
+                });
+            }
+            MachineUIButton::Load => {
+                let recipe = building
+                    .config
+                    .recipe
+                    .as_deref()
+                    .and_then(|id| recipes.get(id));
+                let Some(recipe) = recipe else {
+                    continue;
+                };
+                move_items(&mut player.inventory, &mut process.input, |item| {
+                    recipe.uses(item)
+                });
+                process.operator = Some(player_entity);
+            }
+            MachineUIButton::Unload => {
+                move_items(&mut process.input, &mut player.inventory, |_| true);
+            }
+            MachineUIButton::Collect => {
+                move_items(&mut process.output, &mut player.inventory, |_| true);
+            }
+            MachineUIButton::Close => {
+                close_machine_panel(&mut open, &mut window.single_mut(), &mut player);
+            }
+            MachineUIButton::Recipe(_) => {}
+        }
+    }
+}
+
+/// Sets the open machine to the recipe picked, through the building
+/// [History] so it can be undone.
+pub fn select_machine_recipe(
+    mut commands: Commands,
+    open: Res<OpenMachine>,
+    player: Query<&PlayerLogic, With<Player>>,
+    buildings: Query<(&BuildingId, &Building)>,
+    interaction: Query<(&Interaction, &MachineUIButton), ChangedButton>,
+) {
+    let Some(machine) = open.0 else {
+        return;
+    };
+
+    for (interaction, button) in interaction.iter() {
+        let (Interaction::Pressed, MachineUIButton::Recipe(recipe)) = (interaction, button) else {
+            continue;
+        };
+        let Ok((id, building)) = buildings.get(machine) else {
+            continue;
+        };
+
+        let from = building.config.clone();
+        let recipe = match from.recipe.as_ref() == Some(recipe) {
+            true => None,
+            false if !player.single().has_recipe(recipe) => {
+                log::info!("{recipe} has not been researched yet");
+                continue;
+            }
+            false => Some(recipe.clone()),
+        };
+        let to = BuildingConfig {
+            recipe,
+            ..from.clone()
+        };
+        let configure = ConfigureBuilding::new(*id, from, to);
+        edit_buildings(
+            &mut commands,
+            BuildEdit::Execute(Box::new(configure)),
+            |_, _| {},
+        );
+    }
+}
@@ -1,24 +1,57 @@
 #![feature(stmt_expr_attributes)]
+mod building;
 mod camera;
+mod hud;
+mod machines;
+mod pause;
 mod player;
 mod scene;
-mod entities;
+mod widgets;
 
+use backend::fluids::FluidPlugin;
+use backend::input::ActionPlugin;
+use backend::machines::MachinePlugin;
+use backend::physics::PhysicsMaterialPlugin;
+use backend::player::controller::CharacterControllerPlugin;
+use backend::player::floor::FloorPlugin;
+use backend::player::health::HealthPlugin;
+use backend::player::profile::ProfilePlugin;
+use backend::settings::SettingsPlugin;
 use bevy::prelude::*;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use bevy_xpbd_3d::plugins::PhysicsPlugins;
 
+use self::building::BuildingPlugin;
+use self::camera::CameraPlugin;
+use self::hud::HudPlugin;
+use self::machines::MachinePanelPlugin;
+use self::pause::PausePlugin;
 use self::player::PlayerPlugin;
 use self::scene::ScenePlugin;
+use self::widgets::WidgetsPlugin;
 
 fn main() {
     App::new()
         .add_plugins((
             DefaultPlugins,
+            SettingsPlugin::default(),
+            ActionPlugin,
             PlayerPlugin,
+            CameraPlugin,
             ScenePlugin,
+            BuildingPlugin,
+            (HudPlugin, WidgetsPlugin, MachinePanelPlugin, PausePlugin),
             WorldInspectorPlugin::new(),
-            PhysicsPlugins::default(),
+            (
+                PhysicsPlugins::default(),
+                PhysicsMaterialPlugin,
+                CharacterControllerPlugin,
+                FloorPlugin,
+            ),
+            FluidPlugin,
+            MachinePlugin::default(),
+            HealthPlugin,
+            ProfilePlugin,
         ))
         .run();
 }
@@ -0,0 +1,186 @@
+use backend::settings::{AudioSettings, CameraSettings, GraphicsSettings, Setting, Settings};
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_xpbd_3d::prelude::Physics;
+
+use super::{set_paused, PausePage, PauseStatus};
+use crate::player::{ControlsUIMarker, Player};
+use crate::widgets::{button, heading, label, panel, section, stepper};
+
+#[derive(Component)]
+pub struct PauseUIMarker;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseUIButton {
+    Resume,
+    Settings,
+    Controls,
+    Save,
+    Load,
+    Quit,
+    Back,
+    /// Steps the setting up, or down when false.
+    Step(Setting, bool),
+}
+
+/// Pause menu buttons whose interaction changed this frame.
+pub type PauseButtons<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Interaction, &'static PauseUIButton),
+    (Changed<Interaction>, With<Button>),
+>;
+
+/// The settings page, in sections.
+const SETTINGS_SECTIONS: [(&str, &[Setting]); 3] = [
+    (
+        "Graphics",
+        &[
+            Setting::Vsync,
+            Setting::Shadows,
+            Setting::Fov,
+            Setting::Msaa,
+        ],
+    ),
+    (
+        "Camera",
+        &[Setting::Sensitivity, Setting::InvertY, Setting::Smoothing],
+    ),
+    (
+        "Audio",
+        &[
+            Setting::MasterVolume,
+            Setting::MusicVolume,
+            Setting::EffectsVolume,
+        ],
+    ),
+];
+
+pub fn pause_menu(mut commands: Commands) {
+    let mut pause_ui = panel(Val::Vw(35.0), Val::Vh(75.0));
+    // Over every other menu.
+    pause_ui.z_index = ZIndex::Global(10);
+    commands.spawn((pause_ui, PauseUIMarker));
+}
+
+fn main_page(parent: &mut ChildBuilder, status: &str) {
+    heading(parent, "Paused");
+    for (text, marker) in [
+        ("Resume", PauseUIButton::Resume),
+        ("Settings", PauseUIButton::Settings),
+        ("Key bindings", PauseUIButton::Controls),
+        ("Save", PauseUIButton::Save),
+        ("Load", PauseUIButton::Load),
+        ("Quit", PauseUIButton::Quit),
+    ] {
+        button(parent, text, false, marker);
+    }
+    if !status.is_empty() {
+        label(parent, status);
+    }
+}
+
+fn settings_page(parent: &mut ChildBuilder, settings: &Settings) {
+    heading(parent, "Settings");
+    for (title, section_settings) in SETTINGS_SECTIONS {
+        section(parent, title, |section| {
+            for setting in section_settings {
+                stepper(
+                    section,
+                    setting.name(),
+                    setting.label(settings),
+                    PauseUIButton::Step(*setting, false),
+                    PauseUIButton::Step(*setting, true),
+                );
+            }
+        });
+    }
+    button(parent, "Back", false, PauseUIButton::Back);
+}
+
+/// Shows the page of the pause menu being looked at. Redrawn when the page,
+/// the status or a setting changes.
+pub fn refresh_pause_menu(
+    mut commands: Commands,
+    page: Res<PausePage>,
+    status: Res<PauseStatus>,
+    camera: Res<CameraSettings>,
+    graphics: Res<GraphicsSettings>,
+    audio: Res<AudioSettings>,
+    mut menu: Query<(Entity, &mut Visibility), With<PauseUIMarker>>,
+) {
+    let changed = page.is_changed()
+        || status.is_changed()
+        || camera.is_changed()
+        || graphics.is_changed()
+        || audio.is_changed();
+    if !changed {
+        return;
+    }
+
+    let (menu, mut visibility) = menu.single_mut();
+    // The key bindings are shown by the controls menu in place of this one.
+    let shown = matches!(*page, PausePage::Main | PausePage::Settings);
+    visibility.set_if_neq(match shown {
+        true => Visibility::Visible,
+        false => Visibility::Hidden,
+    });
+
+    let settings = Settings {
+        camera: camera.clone(),
+        graphics: graphics.clone(),
+        audio: audio.clone(),
+        ..Default::default()
+    };
+    commands
+        .entity(menu)
+        .despawn_descendants()
+        .with_children(|parent| match *page {
+            PausePage::Main => main_page(parent, &status.0),
+            PausePage::Settings => settings_page(parent, &settings),
+            PausePage::Closed | PausePage::Controls => {}
+        });
+}
+
+/// Moves between the pages of the pause menu, resumes and quits.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_pause_input(
+    mut page: ResMut<PausePage>,
+    mut time: ResMut<Time<Virtual>>,
+    mut physics: ResMut<Time<Physics>>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+    mut player: Query<&mut Player>,
+    mut controls_menu: Query<&mut Visibility, With<ControlsUIMarker>>,
+    mut exit: EventWriter<AppExit>,
+    interaction: PauseButtons,
+) {
+    for (interaction, button) in interaction.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            PauseUIButton::Resume => {
+                *page = PausePage::Closed;
+                set_paused(
+                    false,
+                    &mut time,
+                    &mut physics,
+                    &mut window.single_mut(),
+                    &mut player.single_mut(),
+                );
+            }
+            PauseUIButton::Settings => *page = PausePage::Settings,
+            PauseUIButton::Controls => {
+                *controls_menu.single_mut() = Visibility::Visible;
+                *page = PausePage::Controls;
+            }
+            PauseUIButton::Back => *page = PausePage::Main,
+            PauseUIButton::Quit => {
+                exit.send(AppExit);
+            }
+            PauseUIButton::Save | PauseUIButton::Load | PauseUIButton::Step(..) => {}
+        }
+    }
+}
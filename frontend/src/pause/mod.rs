@@ -0,0 +1,132 @@
+//! The pause menu. Pausing stops virtual time, which the fixed timestep
+//! machines run on follows, and physics, and hands the cursor to the menu.
+//! From it the game is saved, loaded or quit, and settings are changed.
+
+mod menu;
+mod save;
+mod settings;
+
+use backend::input::{Action, ActionState};
+use bevy::prelude::*;
+use bevy::window::{CursorGrabMode, PrimaryWindow};
+use bevy_xpbd_3d::prelude::{Physics, PhysicsTime};
+
+use self::menu::{handle_pause_input, pause_menu, refresh_pause_menu};
+use self::save::{load_game, save_game};
+use self::settings::{apply_audio_settings, apply_graphics_settings, change_settings};
+use crate::machines::open_machine_panel;
+use crate::player::{action_input_handler, ControlsUIMarker, Player};
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PausePage>()
+            .init_resource::<PauseStatus>()
+            .add_systems(Startup, pause_menu)
+            .add_systems(
+                Update,
+                (
+                    toggle_pause
+                        .before(action_input_handler)
+                        .before(open_machine_panel),
+                    handle_pause_input.after(toggle_pause),
+                    change_settings,
+                    save_game,
+                    load_game,
+                    refresh_pause_menu
+                        .after(handle_pause_input)
+                        .after(change_settings)
+                        .after(save_game)
+                        .after(load_game),
+                    apply_graphics_settings.after(change_settings),
+                    apply_audio_settings.after(change_settings),
+                ),
+            );
+    }
+}
+
+/// What the pause menu shows. The game is paused whenever it is open.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PausePage {
+    #[default]
+    Closed,
+    Main,
+    Settings,
+    /// The key bindings, shown in the controls menu.
+    Controls,
+}
+
+/// The outcome of the last save or load, shown in the menu.
+#[derive(Resource, Debug, Default)]
+pub struct PauseStatus(pub String);
+
+/// Run condition for systems that act on player input, which should do
+/// nothing while the game is paused.
+pub fn playing(time: Res<Time<Virtual>>) -> bool {
+    !time.is_paused()
+}
+
+/// Stops or starts the game and moves the cursor between the menu and the
+/// camera.
+fn set_paused(
+    paused: bool,
+    time: &mut Time<Virtual>,
+    physics: &mut Time<Physics>,
+    window: &mut Window,
+    player: &mut Player,
+) {
+    match paused {
+        true => {
+            time.pause();
+            physics.pause();
+            window.cursor.grab_mode = CursorGrabMode::Confined;
+            window.cursor.visible = true;
+        }
+        false => {
+            time.unpause();
+            physics.unpause();
+            window.cursor.grab_mode = CursorGrabMode::Locked;
+            window.cursor.visible = false;
+        }
+    }
+    player.movement_enabled = !paused;
+}
+
+/// Pauses, or goes back a page in the pause menu until the game is resumed.
+/// While another menu is open, pausing closes it instead.
+#[allow(clippy::too_many_arguments)]
+pub fn toggle_pause(
+    actions: Res<ActionState>,
+    mut page: ResMut<PausePage>,
+    mut status: ResMut<PauseStatus>,
+    mut time: ResMut<Time<Virtual>>,
+    mut physics: ResMut<Time<Physics>>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+    mut player: Query<&mut Player>,
+    mut controls_menu: Query<&mut Visibility, With<ControlsUIMarker>>,
+) {
+    if !actions.just_pressed(Action::Pause) {
+        return;
+    }
+
+    let mut player = player.single_mut();
+    let mut window = window.single_mut();
+    match *page {
+        PausePage::Closed if player.movement_enabled => {
+            status.0.clear();
+            *page = PausePage::Main;
+            set_paused(true, &mut time, &mut physics, &mut window, &mut player);
+        }
+        PausePage::Closed => {}
+        PausePage::Main => {
+            *page = PausePage::Closed;
+            set_paused(false, &mut time, &mut physics, &mut window, &mut player);
+        }
+        PausePage::Settings => *page = PausePage::Main,
+        PausePage::Controls => {
+            *controls_menu.single_mut() = Visibility::Hidden;
+            *page = PausePage::Main;
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use backend::buildings::{BuildEdits, Building, BuildingIds};
+use backend::history::History;
+use backend::machines::{Machine, MachineProcess};
+use backend::player::controller::CharacterState;
+use backend::player::hotbar::Hotbar;
+use backend::player::profile::{PlayerIds, PlayerLogic};
+use backend::save::{SaveGame, SavedBuilding, SAVE_FILE};
+use bevy::prelude::*;
+
+use super::menu::{PauseButtons, PauseUIButton};
+use super::PauseStatus;
+use crate::building::spawn_building;
+use crate::player::Player;
+
+fn pressed(interaction: &PauseButtons, wanted: PauseUIButton) -> bool {
+    interaction
+        .iter()
+        .any(|(interaction, button)| *interaction == Interaction::Pressed && *button == wanted)
+}
+
+type SavedBuildingData<'a> = (
+    &'a Building,
+    &'a Transform,
+    Option<(&'a Machine, &'a MachineProcess)>,
+);
+
+/// Writes the player and every placed building, with what its machine was
+/// doing, to the save file.
+pub fn save_game(
+    mut status: ResMut<PauseStatus>,
+    player: Query<(&Transform, &Player, &PlayerLogic)>,
+    buildings: Query<SavedBuildingData>,
+    interaction: PauseButtons,
+) {
+    if !pressed(&interaction, PauseUIButton::Save) {
+        return;
+    }
+
+    let (transform, player, profile) = player.single();
+    let buildings = buildings
+        .iter()
+        .map(|(building, transform, machine)| {
+            let saved = SavedBuilding::new(building, transform.translation);
+            match machine {
+                Some((machine, process)) => saved.with_machine(machine, process),
+                None => saved,
+            }
+        })
+        .collect();
+    let save = SaveGame::new(profile, transform.translation, &player.inventory, buildings);
+
+    status.0 = match save.save(SAVE_FILE) {
+        Ok(()) => "Game saved".to_string(),
+        Err(err) => {
+            log::warn!("Could not save the game: {err:?}");
+            "Could not save the game".to_string()
+        }
+    };
+}
+
+/// What [load_game] puts back on the player.
+type LoadedPlayerData<'a> = (
+    &'a mut Transform,
+    &'a mut CharacterState,
+    &'a mut Player,
+    &'a mut Hotbar,
+    &'a mut PlayerLogic,
+);
+
+/// Puts the player and the buildings back as they were saved. Everything
+/// built since is taken down, and can no longer be undone.
+pub fn load_game(
+    mut commands: Commands,
+    mut player_ids: ResMut<PlayerIds>,
+    mut history: ResMut<History<BuildEdits>>,
+    mut status: ResMut<PauseStatus>,
+    mut player: Query<LoadedPlayerData>,
+    buildings: Query<Entity, With<Building>>,
+    interaction: PauseButtons,
+) {
+    if !pressed(&interaction, PauseUIButton::Load) {
+        return;
+    }
+
+    let save = match SaveGame::load(SAVE_FILE) {
+        Ok(save) => save,
+        Err(err) => {
+            log::warn!("Could not load the game: {err:?}");
+            status.0 = "Could not load the game".to_string();
+            return;
+        }
+    };
+
+    for building in buildings.iter() {
+        commands.entity(building).despawn_recursive();
+    }
+    let saved = save.buildings.clone();
+    commands.add(move |world: &mut World| {
+        for saved in saved {
+            let id = world.resource_mut::<BuildingIds>().next_id();
+            let entity = spawn_building(world, id, &saved.placement(), saved.config);
+            let Some(saved) = saved.machine else {
+                continue;
+            };
+            let mut machines = world.query::<(&mut Machine, &mut MachineProcess)>();
+            if let Ok((mut machine, mut process)) = machines.get_mut(world, entity) {
+                saved.restore(&mut machine, &mut process);
+            }
+        }
+    });
+    history.clear();
+
+    let (mut transform, mut state, mut player, mut hotbar, mut profile) = player.single_mut();
+    transform.translation = Vec3::from_array(save.position);
+    state.reset();
+    // The restored items have new ids, so the old slots would point at
+    // whatever took their place.
+    player.inventory = save.restore_inventory();
+    hotbar.slots = Default::default();
+    player_ids.reserve(save.profile.id);
+    player.profile = save.profile.id;
+    *profile = save.profile;
+
+    status.0 = "Game loaded".to_string();
+}
@@ -0,0 +1,78 @@
+use backend::input::InputBindings;
+use backend::settings::{AudioSettings, CameraSettings, GraphicsSettings, Settings};
+use bevy::audio::GlobalVolume;
+use bevy::prelude::*;
+use bevy::window::{PresentMode, PrimaryWindow};
+
+use super::menu::{PauseButtons, PauseUIButton};
+
+/// Steps the setting whose button was pressed. Only the sections that
+/// changed are written back, so only they count as changed.
+pub fn change_settings(
+    mut camera: ResMut<CameraSettings>,
+    mut graphics: ResMut<GraphicsSettings>,
+    mut audio: ResMut<AudioSettings>,
+    interaction: PauseButtons,
+) {
+    for (interaction, button) in interaction.iter() {
+        let (Interaction::Pressed, PauseUIButton::Step(setting, up)) = (interaction, button) else {
+            continue;
+        };
+
+        let mut settings = Settings {
+            camera: camera.clone(),
+            controls: InputBindings::default(),
+            graphics: graphics.clone(),
+            audio: audio.clone(),
+        };
+        setting.step(&mut settings, *up);
+
+        camera.set_if_neq(settings.camera);
+        graphics.set_if_neq(settings.graphics);
+        audio.set_if_neq(settings.audio);
+    }
+}
+
+/// Applies the graphics settings to the window, lights and cameras when they
+/// change, and once at startup.
+pub fn apply_graphics_settings(
+    settings: Res<GraphicsSettings>,
+    mut msaa: ResMut<Msaa>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+    mut point_lights: Query<&mut PointLight>,
+    mut directional_lights: Query<&mut DirectionalLight>,
+    mut spot_lights: Query<&mut SpotLight>,
+    mut cameras: Query<&mut Projection, With<Camera3d>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    msaa.set_if_neq(settings.msaa());
+    window.single_mut().present_mode = match settings.vsync {
+        true => PresentMode::AutoVsync,
+        false => PresentMode::AutoNoVsync,
+    };
+    for mut light in point_lights.iter_mut() {
+        light.shadows_enabled = settings.shadows;
+    }
+    for mut light in directional_lights.iter_mut() {
+        light.shadows_enabled = settings.shadows;
+    }
+    for mut light in spot_lights.iter_mut() {
+        light.shadows_enabled = settings.shadows;
+    }
+    for mut projection in cameras.iter_mut() {
+        if let Projection::Perspective(perspective) = projection.as_mut() {
+            perspective.fov = settings.fov.to_radians();
+        }
+    }
+}
+
+/// Sets the volume everything plays at. Nothing plays music or effects yet,
+/// so only the master volume applies.
+pub fn apply_audio_settings(settings: Res<AudioSettings>, mut volume: ResMut<GlobalVolume>) {
+    if settings.is_changed() {
+        *volume = GlobalVolume::new(settings.master);
+    }
+}
@@ -1,14 +1,28 @@
+use backend::iams::inventory::ItemKey;
 use backend::iams::Inventory;
-use bevy::ecs::system::Commands;
+use bevy::prelude::*;
 
+use super::container::{container_bundle, ContainerAssets};
+
+/// How far in front of the player dropped items land, in m.
+const DROP_DISTANCE: f32 = 1.0;
+
+/// Drops the item picked out by `key`, or only `amount` of it, in a crate in
+/// front of the player. Returns whether anything was dropped.
 pub fn drop_item(
-    mut commands: Commands,
+    commands: &mut Commands,
+    assets: &ContainerAssets,
+    player: &Transform,
     inventory: &mut Inventory,
-    item_id: usize,
-) {
-    let to_drop = inventory.remove_by_id(item_id);
-    
-
+    key: ItemKey,
+    amount: Option<f32>,
+) -> bool {
+    let mut dropped = Inventory::default();
+    if inventory.move_item(key, amount, &mut dropped).is_none() {
+        return false;
+    }
 
-    Ok(to_drop)
+    let position = player.translation + *player.forward() * DROP_DISTANCE + Vec3::Y;
+    commands.spawn(container_bundle(dropped, position, assets));
+    true
 }
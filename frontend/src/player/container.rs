@@ -0,0 +1,96 @@
+use backend::iams::Inventory;
+use backend::physics::{PhysicsBundle, SurfaceMaterial};
+use bevy::prelude::*;
+use bevy_xpbd_3d::plugins::collision::Collider;
+
+use super::ui::tab_menu::InventoryUIMarker;
+use super::Player;
+
+/// How close the player has to be to a container to open it.
+const CONTAINER_REACH: f32 = 3.0;
+
+/// Side of the crate containers are drawn as, in m.
+const CRATE_SIZE: f32 = 0.4;
+
+/// Items lying in the world in a crate, like ones the player dropped or
+/// everything they were carrying when they died.
+#[derive(Component, Debug, Default)]
+pub struct Container(pub Inventory);
+
+/// The container shown beside the inventory: the closest one in reach while
+/// the inventory is open.
+#[derive(Resource, Debug, Default)]
+pub struct OpenContainer(pub Option<Entity>);
+
+#[derive(Resource)]
+pub struct ContainerAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+pub fn load_container_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(ContainerAssets {
+        mesh: meshes.add(Cuboid::new(CRATE_SIZE, CRATE_SIZE, CRATE_SIZE)),
+        material: materials.add(Color::rgb(0.55, 0.4, 0.25)),
+    });
+}
+
+/// A crate holding `inventory`, lying at `position`.
+pub fn container_bundle(
+    inventory: Inventory,
+    position: Vec3,
+    assets: &ContainerAssets,
+) -> impl Bundle {
+    (
+        Container(inventory),
+        PbrBundle {
+            mesh: assets.mesh.clone(),
+            material: assets.material.clone(),
+            transform: Transform::from_translation(position),
+            ..Default::default()
+        },
+        PhysicsBundle::item(
+            Collider::cuboid(CRATE_SIZE, CRATE_SIZE, CRATE_SIZE),
+            SurfaceMaterial::Wood,
+        ),
+        Name::new("Crate"),
+    )
+}
+
+pub fn find_open_container(
+    player: Query<&Transform, With<Player>>,
+    containers: Query<(Entity, &Transform), With<Container>>,
+    menu: Query<&Visibility, With<InventoryUIMarker>>,
+    mut open: ResMut<OpenContainer>,
+) {
+    let player = player.single();
+    let closest = match menu.single() {
+        Visibility::Visible => containers
+            .iter()
+            .map(|(entity, transform)| (transform.translation.distance(player.translation), entity))
+            .filter(|(distance, _)| *distance <= CONTAINER_REACH)
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, entity)| entity),
+        _ => None,
+    };
+
+    if open.0 != closest {
+        open.0 = closest;
+    }
+}
+
+/// Takes away crates once everything has been taken out of them.
+pub fn remove_empty_containers(
+    mut commands: Commands,
+    containers: Query<(Entity, &Container), Changed<Container>>,
+) {
+    for (entity, container) in containers.iter() {
+        if container.0.is_empty() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use backend::player::controller::CharacterState;
+use backend::player::health::{DeathEvent, Health};
+use backend::player::profile::PlayerLogic;
+use bevy::prelude::*;
+
+use super::container::{container_bundle, ContainerAssets};
+use super::Player;
+
+/// Where players come back after dying.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct SpawnPoint(pub Vec3);
+
+pub fn respawn_dead_players(
+    mut commands: Commands,
+    mut deaths: EventReader<DeathEvent>,
+    spawn_point: Res<SpawnPoint>,
+    container_assets: Res<ContainerAssets>,
+    mut players: Query<(
+        &mut Transform,
+        &mut Player,
+        &mut PlayerLogic,
+        &mut Health,
+        &mut CharacterState,
+    )>,
+) {
+    for death in deaths.read() {
+        let Ok((mut transform, mut player, mut profile, mut health, mut state)) =
+            players.get_mut(death.entity)
+        else {
+            continue;
+        };
+
+        log::info!("{} died from {:?}", profile.name, death.cause);
+        profile.stats.deaths += 1;
+
+        // Everything they were carrying is left where they died, to be
+        // picked back up.
+        let inventory = std::mem::take(&mut player.inventory);
+        commands.spawn(container_bundle(
+            inventory,
+            transform.translation + Vec3::Y,
+            &container_assets,
+        ));
+
+        transform.translation = spawn_point.0;
+        state.reset();
+        health.revive();
+    }
+}
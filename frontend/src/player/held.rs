@@ -0,0 +1,272 @@
+use backend::buildings::BuildingKind;
+use backend::iams::inventory::ItemKey;
+use backend::input::{Action, ActionState};
+use backend::items::food::RATION_HEALING;
+use backend::items::tool::{repair_tool, wear_tool};
+use backend::items::ItemAction;
+use backend::mining::{MiningProgress, OreDeposit, MINING_REACH, SWING_STAMINA, SWING_YIELD};
+use backend::player::health::Health;
+use backend::player::hotbar::Hotbar;
+use backend::player::profile::PlayerLogic;
+use backend::player::skills::{ExperienceGained, Skill, MINING_EXPERIENCE};
+use backend::player::stamina::Stamina;
+use backend::recipes::RecipeBook;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_xpbd_3d::prelude::SpatialQuery;
+
+use super::{Player, MODEL_SCALE};
+use crate::building::{aim, BuildMode};
+
+/// Bones of the player model the held item is attached to, by the names
+/// different exporters give the right hand.
+const HAND_BONES: [&str; 4] = ["RightHand", "mixamorig:RightHand", "hand_r", "Hand_R"];
+
+/// Where the held item goes until the model's hand has loaded, relative to
+/// the player's feet.
+const HAND_FALLBACK: Vec3 = Vec3::new(0.35, 1.0, -0.3);
+
+/// The model of the item in the player's hand.
+#[derive(Component, Debug)]
+pub struct HeldModel {
+    key: ItemKey,
+    parent: Entity,
+}
+
+#[derive(Resource)]
+pub struct HeldModelAssets {
+    mesh: Handle<Mesh>,
+    /// One material per kind of item, made the first time it is held.
+    materials: HashMap<&'static str, Handle<StandardMaterial>>,
+}
+
+pub fn load_held_model_assets(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.insert_resource(HeldModelAssets {
+        mesh: meshes.add(Cuboid::new(0.08, 0.08, 0.3)),
+        materials: HashMap::new(),
+    });
+}
+
+/// Picks the hotbar slot with the number keys or scrolls through it. While
+/// zoom is held the wheel zooms the camera instead.
+pub fn select_hotbar_slot(actions: Res<ActionState>, mut hotbar: Query<&mut Hotbar>) {
+    let mut hotbar = hotbar.single_mut();
+
+    if let Some(slot) = Action::HOTBAR_SLOTS
+        .iter()
+        .position(|action| actions.just_pressed(*action))
+    {
+        hotbar.select(slot);
+    }
+    if actions.pressed(Action::Zoom) {
+        return;
+    }
+    if actions.just_pressed(Action::HotbarNext) {
+        hotbar.scroll(1);
+    }
+    if actions.just_pressed(Action::HotbarPrevious) {
+        hotbar.scroll(-1);
+    }
+}
+
+/// Holding a building item selects it for placing, and switching to
+/// anything else leaves build mode.
+pub fn follow_held_building(
+    player: Query<(&Player, &Hotbar), Changed<Hotbar>>,
+    mut build_mode: ResMut<BuildMode>,
+) {
+    let Ok((player, hotbar)) = player.get_single() else {
+        return;
+    };
+
+    let selected = hotbar
+        .held(&player.inventory)
+        .filter(|item| item.action() == Some(ItemAction::Place))
+        .and_then(|item| {
+            BuildingKind::ALL
+                .into_iter()
+                .find(|kind| kind.name() == item.type_name())
+        });
+    if build_mode.selected != selected {
+        build_mode.selected = selected;
+    }
+}
+
+/// What [use_held_item] reads and writes for the player holding the item.
+type HolderData<'a> = (
+    Entity,
+    &'a Transform,
+    &'a mut Player,
+    &'a mut PlayerLogic,
+    &'a mut Health,
+    &'a mut Stamina,
+    &'a Hotbar,
+    &'a mut MiningProgress,
+);
+
+/// Mines with a held pickaxe or drill while the use button is held, and eats
+/// held food when it is pressed. Placing held buildings is left to build
+/// mode, and repairing machines to [super::repair].
+#[allow(clippy::too_many_arguments)]
+pub fn use_held_item(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    time: Res<Time>,
+    spatial_query: SpatialQuery,
+    camera: Query<&Transform, (With<Camera3d>, Without<Player>)>,
+    mut player: Query<HolderData>,
+    mut deposits: Query<(&Transform, &mut OreDeposit), Without<Player>>,
+    mut experience: EventWriter<ExperienceGained>,
+) {
+    let (entity, transform, mut player, mut profile, mut health, mut stamina, hotbar, mut mining) =
+        player.single_mut();
+    if !player.movement_enabled {
+        mining.tick(None, 0.0, 1.0);
+        return;
+    }
+
+    match hotbar.held_action(&player.inventory) {
+        Some(ItemAction::Mine) if actions.pressed(Action::Place) => {
+            let Some(tool) = hotbar.held_tool(&player.inventory).copied() else {
+                return;
+            };
+            // Deposits too hard for the tool cannot be swung at at all.
+            let target = aim(&spatial_query, camera.single(), entity)
+                .map(|(target, _)| target)
+                .filter(|target| {
+                    deposits
+                        .get(*target)
+                        .is_ok_and(|(deposit_transform, deposit)| {
+                            deposit_transform
+                                .translation
+                                .distance(transform.translation)
+                                <= MINING_REACH
+                                && tool.kind.can_mine(deposit.kind)
+                        })
+                });
+            let speed = tool.kind.mining_speed() * profile.skills.speed_multiplier(Skill::Mining);
+            if !mining.tick(target, time.delta_seconds(), speed) || !stamina.spend(SWING_STAMINA) {
+                return;
+            }
+
+            let Some((target, (_, mut deposit))) =
+                target.and_then(|target| Some((target, deposits.get_mut(target).ok()?)))
+            else {
+                return;
+            };
+            let mined = deposit.mine(SWING_YIELD);
+            let purity = profile.skills.mined_purity(deposit.purity);
+            deposit.kind.give(mined, purity, &mut player.inventory);
+            wear_tool(&mut player.inventory, ItemKey::of(&tool));
+            profile.stats.ore_mined += mined;
+            experience.send(ExperienceGained {
+                player: entity,
+                skill: Skill::Mining,
+                amount: MINING_EXPERIENCE,
+            });
+
+            if deposit.is_exhausted() {
+                commands.entity(target).despawn_recursive();
+            }
+        }
+        Some(ItemAction::Eat) if actions.just_pressed(Action::Place) => {
+            let Some(food) = hotbar.held(&player.inventory).map(|item| item.type_name()) else {
+                return;
+            };
+            if player.inventory.take_by_name(food, 1.0) {
+                health.heal(RATION_HEALING);
+            }
+        }
+        _ => {
+            mining.tick(None, 0.0, 1.0);
+        }
+    }
+}
+
+/// Repairs the tool in hand with the recipe for it, paying from the
+/// player's inventory.
+pub fn repair_held_tool(
+    actions: Res<ActionState>,
+    recipes: Res<RecipeBook>,
+    mut player: Query<(&mut Player, &Hotbar)>,
+) {
+    if !actions.just_pressed(Action::RepairTool) {
+        return;
+    }
+
+    let (mut player, hotbar) = player.single_mut();
+    let Some(tool) = hotbar.held_tool(&player.inventory).copied() else {
+        return;
+    };
+    let Some(recipe) = recipes.repairing(tool.kind.name()) else {
+        log::info!("Nothing repairs a {}", tool.kind.name());
+        return;
+    };
+    if let Err(err) = repair_tool(&mut player.inventory, ItemKey::of(&tool), recipe) {
+        log::info!("Could not repair tool: {err:?}");
+    }
+}
+
+/// Puts a model of the held item in the player's right hand. Items have no
+/// models of their own yet, so a bar of the item's colour stands in.
+#[allow(clippy::too_many_arguments)]
+pub fn attach_held_model(
+    mut commands: Commands,
+    mut assets: ResMut<HeldModelAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    player: Query<(Entity, &Player, &Hotbar)>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+    held: Query<(Entity, &HeldModel)>,
+    mut hand: Local<Option<Entity>>,
+) {
+    let (player_entity, player, hotbar) = player.single();
+
+    // The model loads after the player spawns, so keep looking until the
+    // hand turns up.
+    if hand.is_none() {
+        *hand = children.iter_descendants(player_entity).find(|entity| {
+            names
+                .get(*entity)
+                .is_ok_and(|name| HAND_BONES.contains(&name.as_str()))
+        });
+    }
+    let parent = hand.unwrap_or(player_entity);
+
+    let item = hotbar.held(&player.inventory);
+    let key = item.map(ItemKey::of);
+    let current = held.get_single().ok();
+    if current.map(|(_, model)| (model.key, model.parent)) == key.map(|key| (key, parent)) {
+        return;
+    }
+
+    if let Some((entity, _)) = current {
+        commands.entity(entity).despawn_recursive();
+    }
+    let (Some(item), Some(key)) = (item, key) else {
+        return;
+    };
+
+    // Bones inherit the model's scale, so the item is scaled back up.
+    let transform = match hand.is_some() {
+        true => Transform::from_scale(Vec3::splat(1.0 / MODEL_SCALE)),
+        false => Transform::from_translation(HAND_FALLBACK),
+    };
+    let material = assets
+        .materials
+        .entry(item.type_name())
+        .or_insert_with(|| materials.add(item.icon_color()))
+        .clone();
+    let model = commands
+        .spawn((
+            PbrBundle {
+                mesh: assets.mesh.clone(),
+                material,
+                transform,
+                ..Default::default()
+            },
+            HeldModel { key, parent },
+        ))
+        .id();
+    commands.entity(parent).add_child(model);
+}
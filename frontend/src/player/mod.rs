@@ -1,31 +1,155 @@
 mod actions;
-mod gravity;
+mod container;
+mod death;
+mod held;
 mod movement;
+mod repair;
 mod ui;
 
+use backend::buildings::{BuildingItem, BuildingKind};
+use backend::iams::events::{InventoryChanged, InventoryTotals};
+use backend::input::{Action, ActionState};
+use backend::items::food::Food;
 use backend::items::ore::{CopperOre, IronOre, Ore};
+use backend::items::spare_part::SparePart;
+use backend::items::tool::{Tool, ToolKind};
+use backend::mining::MiningProgress;
+use backend::physics::PhysicsBundle;
+use backend::player::controller::{
+    move_characters, CharacterController, CharacterInput, CharacterState,
+};
+use backend::player::health::Health;
+use backend::player::hotbar::Hotbar;
+use backend::player::profile::{PlayerId, PlayerIds, PlayerLogic};
+use backend::player::stamina::Stamina;
 use bevy::prelude::*;
 use bevy::window::{CursorGrabMode, PrimaryWindow};
-use bevy_xpbd_3d::components::RigidBody;
 use bevy_xpbd_3d::plugins::collision::Collider;
 
 use crate::camera::ThirdPersonCameraData;
-use crate::player::gravity::FloorDetector;
+use crate::pause::playing;
 
-use self::movement::player_movement;
-use self::ui::tab_menu::{handle_inventory_input, inventory_popup, InventoryUIMarker};
+use self::container::{
+    find_open_container, load_container_assets, remove_empty_containers, OpenContainer,
+};
+use self::death::{respawn_dead_players, SpawnPoint};
+use self::held::{
+    attach_held_model, follow_held_building, load_held_model_assets, repair_held_tool,
+    select_hotbar_slot, use_held_item,
+};
+use self::movement::{player_camera, player_movement};
+use self::repair::repair_nearby_machine;
+use self::ui::controls_menu::{
+    capture_binding, controls_menu, handle_controls_input, refresh_controls_menu, Rebinding,
+};
+use self::ui::drag::{
+    drag_items, drag_ui, handle_split_input, open_split_dialog, refresh_split_dialog,
+    show_drag_ghost, Dragging, Splitting,
+};
+use self::ui::encumbrance::{encumbrance_display, update_encumbrance_display};
+use self::ui::hotbar::{
+    forget_missing_hotbar_items, handle_hotbar_input, hotbar_display, refresh_hotbar,
+};
+use self::ui::research_menu::{
+    handle_research_input, load_tech_tree, refresh_research_menu, research_menu, ResearchUIMarker,
+};
+use self::ui::tab_menu::{
+    handle_inventory_input, inventory_popup, refresh_inventory_menu, InventoryUILayout,
+    InventoryUIMarker, InventoryUIPage,
+};
+use self::ui::tooltip::{load_recipe_book, show_item_tooltip, tooltip_popup};
+
+pub use self::container::{container_bundle, ContainerAssets};
+pub use self::ui::controls_menu::ControlsUIMarker;
+pub use self::ui::ChangedButton;
+
+/// Scale of the player model, which is exported far too big.
+pub const MODEL_SCALE: f32 = 0.022;
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, (spawn_player, inventory_popup.after(spawn_player)))
+        app.insert_resource(SpawnPoint(Vec3::new(0.0, 1.0, 0.0)))
+            .init_resource::<Rebinding>()
+            .init_resource::<InventoryUIPage>()
+            .init_resource::<InventoryUILayout>()
+            .init_resource::<OpenContainer>()
+            .init_resource::<Dragging>()
+            .init_resource::<Splitting>()
+            .add_event::<InventoryChanged>()
+            .add_systems(
+                Startup,
+                (
+                    spawn_player,
+                    inventory_popup,
+                    encumbrance_display,
+                    load_tech_tree.after(load_recipe_book),
+                    research_menu,
+                    controls_menu,
+                    load_container_assets,
+                    hotbar_display,
+                    drag_ui,
+                    load_recipe_book,
+                    tooltip_popup,
+                    load_held_model_assets,
+                ),
+            )
             .add_systems(
                 Update,
                 (
-                    player_movement,
+                    player_movement.before(move_characters),
+                    player_camera.after(move_characters),
                     action_input_handler,
                     handle_inventory_input,
+                    refresh_inventory_menu
+                        .after(handle_inventory_input)
+                        .after(find_open_container)
+                        .after(drag_items),
+                    repair_nearby_machine
+                        .after(select_hotbar_slot)
+                        .run_if(playing),
+                    respawn_dead_players,
+                    update_encumbrance_display,
+                    refresh_research_menu,
+                    handle_research_input,
+                    refresh_controls_menu,
+                    capture_binding.before(handle_controls_input),
+                    handle_controls_input,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    find_open_container,
+                    remove_empty_containers.after(drag_items),
+                    handle_hotbar_input.before(drag_items),
+                    drag_items,
+                    show_drag_ghost.after(handle_split_input),
+                    open_split_dialog,
+                    handle_split_input.after(drag_items),
+                    refresh_split_dialog
+                        .after(open_split_dialog)
+                        .after(handle_split_input),
+                    forget_missing_hotbar_items.after(drag_items),
+                    show_item_tooltip.after(drag_items),
+                    refresh_hotbar
+                        .after(forget_missing_hotbar_items)
+                        .after(handle_hotbar_input)
+                        .after(select_hotbar_slot),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    select_hotbar_slot.run_if(playing),
+                    follow_held_building.after(select_hotbar_slot),
+                    use_held_item.after(select_hotbar_slot),
+                    repair_held_tool.after(select_hotbar_slot).run_if(playing),
+                    track_inventory_changes,
+                    attach_held_model
+                        .after(select_hotbar_slot)
+                        .after(forget_missing_hotbar_items),
                 ),
             );
     }
@@ -33,37 +157,67 @@ impl Plugin for PlayerPlugin {
 
 #[derive(Component, Debug)]
 pub struct Player {
+    /// The [PlayerLogic] profile this player plays as.
+    pub profile: PlayerId,
+    /// Walking speed in m/s.
     pub speed: f32,
+    /// Inventory mass in kg the player can carry before being overloaded.
+    pub carry_capacity: f32,
     pub inventory: backend::iams::Inventory,
     pub camera_data: ThirdPersonCameraData,
-    pub mouse_sensitivity: f32,
     pub movement_enabled: bool,
 }
 
-impl Default for Player {
-    fn default() -> Self {
+impl Player {
+    pub fn new(profile: PlayerId) -> Self {
         let mut inventory = backend::iams::Inventory::default();
         for i in 0..10 {
             inventory.add(Ore::<IronOre>::new(i as f32, i as f32, i));
             inventory.add(Ore::<CopperOre>::new(i as f32, i as f32, i));
         }
+        inventory.add(SparePart::new(4, 0));
 
         Self {
-            speed: 250.0,
+            profile,
+            speed: 4.5,
+            carry_capacity: 200.0,
             camera_data: ThirdPersonCameraData::default(),
-            mouse_sensitivity: 0.001,
             inventory,
             movement_enabled: true,
         }
     }
 }
 
+type OnlyTabMenu = (
+    With<InventoryUIMarker>,
+    Without<ResearchUIMarker>,
+    Without<ControlsUIMarker>,
+);
+type OnlyResearchMenu = (
+    With<ResearchUIMarker>,
+    Without<InventoryUIMarker>,
+    Without<ControlsUIMarker>,
+);
+type OnlyControlsMenu = (
+    With<ControlsUIMarker>,
+    Without<InventoryUIMarker>,
+    Without<ResearchUIMarker>,
+);
+
 pub fn action_input_handler(
-    keys: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     mut window: Query<&mut Window, With<PrimaryWindow>>,
     mut query: Query<&mut Player>,
-    mut tab_menu: Query<&mut Visibility, With<InventoryUIMarker>>,
+    mut tab_menu: Query<&mut Visibility, OnlyTabMenu>,
+    mut research_menu: Query<&mut Visibility, OnlyResearchMenu>,
+    mut controls_menu: Query<&mut Visibility, OnlyControlsMenu>,
+    time: Res<Time<Virtual>>,
 ) {
+    // The pause menu has the cursor while paused.
+    if time.is_paused() {
+        return;
+    }
+
     let mut player = query.single_mut();
     let mut primary_window = window.single_mut();
 
@@ -79,7 +233,7 @@ pub fn action_input_handler(
         player.movement_enabled = false;
     };
 
-    if keys.just_pressed(KeyCode::Escape) {
+    if actions.just_pressed(Action::ToggleCursor) {
         match primary_window.cursor.grab_mode {
             CursorGrabMode::Confined | CursorGrabMode::None => {
                 capture_cursor(&mut primary_window, &mut player)
@@ -88,22 +242,59 @@ pub fn action_input_handler(
         }
     }
 
-    if keys.just_pressed(KeyCode::Tab) {
-        let mut tab_menu = tab_menu.single_mut();
-        match *tab_menu {
+    let toggle_menu =
+        |menu: &mut Visibility, primary_window: &mut Window, player: &mut Player| match *menu {
             Visibility::Visible => {
-                free_cursor(&mut primary_window, &mut player);
-                *tab_menu = Visibility::Hidden;
+                free_cursor(primary_window, player);
+                *menu = Visibility::Hidden;
             }
             Visibility::Hidden => {
-                capture_cursor(&mut primary_window, &mut player);
-                *tab_menu = Visibility::Visible;
+                capture_cursor(primary_window, player);
+                *menu = Visibility::Visible;
             }
             Visibility::Inherited => {
-                free_cursor(&mut primary_window, &mut player);
-                *tab_menu = Visibility::Hidden;
+                free_cursor(primary_window, player);
+                *menu = Visibility::Hidden;
+            }
+        };
+
+    if actions.just_pressed(Action::ToggleInventory) {
+        toggle_menu(&mut tab_menu.single_mut(), &mut primary_window, &mut player);
+    }
+
+    if actions.just_pressed(Action::ToggleResearch) {
+        toggle_menu(
+            &mut research_menu.single_mut(),
+            &mut primary_window,
+            &mut player,
+        );
+    }
+
+    if actions.just_pressed(Action::ToggleControls) {
+        toggle_menu(
+            &mut controls_menu.single_mut(),
+            &mut primary_window,
+            &mut player,
+        );
+    }
+
+    // Pausing with a menu open closes the menus instead.
+    if actions.just_pressed(Action::Pause) {
+        let mut closed = false;
+        let menus = [
+            tab_menu.single_mut(),
+            research_menu.single_mut(),
+            controls_menu.single_mut(),
+        ];
+        for mut menu in menus {
+            if *menu == Visibility::Visible {
+                *menu = Visibility::Hidden;
+                closed = true;
             }
         }
+        if closed {
+            free_cursor(&mut primary_window, &mut player);
+        }
     }
 }
 
@@ -112,31 +303,75 @@ pub fn spawn_player(
     mut commands: Commands,
     assets: Res<AssetServer>,
     mut window: Query<&mut Window, With<PrimaryWindow>>,
+    mut player_ids: ResMut<PlayerIds>,
+    spawn_point: Res<SpawnPoint>,
 ) {
     let mut primary_window = window.single_mut();
     primary_window.cursor.grab_mode = bevy::window::CursorGrabMode::Locked;
     primary_window.cursor.visible = false;
 
+    // The model is scaled down on its own entity so the colliders keep their
+    // size.
     let model = SceneBundle {
         scene: assets.load("gman.glb#Scene0"),
-        transform: Transform {
-            translation: Vec3::new(0.0, 0.0, 0.0),
-            scale: Vec3::splat(0.022),
-            ..Default::default()
-        },
+        transform: Transform::from_scale(Vec3::splat(MODEL_SCALE)),
         ..Default::default()
     };
 
     let camera = Camera3dBundle::default();
 
-    commands.spawn((
-        model,
-        Player::default(),
-        RigidBody::Kinematic,
-        Collider::capsule(10.0, 1.0),
-        FloorDetector::default(),
-    ));
+    // Start with the tools, rations and buildings to hand.
+    let mut player = Player::new(player_ids.next_id());
+    let profile = PlayerLogic::new(player.profile, "Player");
+    let mut hotbar = Hotbar::default();
+    let inventory = &mut player.inventory;
+    let starting_items = [
+        inventory.stack(Tool::new(ToolKind::Pickaxe, 0)),
+        inventory.stack(Tool::new(ToolKind::Wrench, 0)),
+        inventory.stack(Food::new(5, 0)),
+    ]
+    .into_iter()
+    .chain(BuildingKind::ALL.map(|kind| inventory.stack(BuildingItem::new(kind, 2, 0))));
+    for (slot, key) in starting_items.enumerate() {
+        hotbar.assign(slot, key);
+    }
+
+    commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_translation(spawn_point.0)),
+            InventoryTotals::new(&player.inventory),
+            player,
+            profile,
+            Health::default(),
+            Stamina::default(),
+            hotbar,
+            MiningProgress::default(),
+            // The player stands on its origin, so the capsule is lifted to
+            // cover it.
+            PhysicsBundle::player(Collider::compound(vec![(
+                Vec3::Y * 1.0,
+                Quat::IDENTITY,
+                Collider::capsule(1.2, 0.4),
+            )])),
+            CharacterController::default(),
+            CharacterInput::default(),
+            CharacterState::default(),
+        ))
+        .with_children(|player| {
+            player.spawn(model);
+        });
     commands.spawn(camera);
 
     log::debug!("Player spawned");
 }
+
+/// Sends an [InventoryChanged] for everything that came into or left the
+/// player's inventory since last frame.
+pub fn track_inventory_changes(
+    mut players: Query<(Entity, &Player, &mut InventoryTotals), Changed<Player>>,
+    mut changes: EventWriter<InventoryChanged>,
+) {
+    for (entity, player, mut totals) in players.iter_mut() {
+        changes.send_batch(totals.update(entity, &player.inventory));
+    }
+}
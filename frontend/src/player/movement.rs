@@ -1,119 +1,240 @@
-use bevy::input::mouse::MouseMotion;
+use backend::input::{Action, ActionState};
+use backend::player::controller::{CharacterInput, CharacterState};
+use backend::player::encumbrance::Encumbrance;
+use backend::player::health::{fall_damage, DamageEvent, DamageType};
+use backend::player::stamina::Stamina;
+use backend::settings::CameraSettings;
+use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
 use bevy::prelude::*;
-use bevy_xpbd_3d::components::LinearVelocity;
-use bevy_xpbd_3d::plugins::spatial_query::RayHits;
+use bevy_xpbd_3d::prelude::{Collider, SpatialQuery, SpatialQueryFilter};
 
-use crate::camera::third_person_camera_update;
+use crate::camera::{
+    blend_camera, first_person_camera_update, free_fly_camera_update, third_person_camera_update,
+    zoom, CameraMode, CameraState, CAMERA_RADIUS, FREE_FLY_SPEED,
+};
 
 use super::Player;
 
+/// Upwards velocity of a jump by an unencumbered player.
+pub const JUMP_VELOCITY: f32 = 4.9;
+pub const SPRINT_MULTIPLIER: f32 = 1.6;
+/// Mouse movement in pixels per second a fully pushed look stick stands in
+/// for.
+pub const GAMEPAD_LOOK_SPEED: f32 = 800.0;
+
+/// Turns movement actions into [CharacterInput] for the controller, and hurts
+/// the player when they land hard.
 pub fn player_movement(
     time: Res<Time>,
-    mut player_query: Query<(&mut Transform, &mut Player)>,
-    mut player_physics: Query<(&mut LinearVelocity, &RayHits)>,
-    keys: Res<ButtonInput<KeyCode>>,
+    mut player_query: Query<(
+        Entity,
+        &Player,
+        &mut Stamina,
+        &mut CharacterInput,
+        &CharacterState,
+    )>,
+    actions: Res<ActionState>,
+    camera_transform: Query<&Transform, (With<Camera3d>, Without<Player>)>,
+    camera_state: Res<CameraState>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    let Ok((player_entity, player, mut stamina, mut input, state)) = player_query.get_single_mut()
+    else {
+        return;
+    };
+
+    if let Some(amount) = state.landed.and_then(fall_damage) {
+        damage.send(DamageEvent {
+            target: player_entity,
+            amount,
+            damage_type: DamageType::Fall,
+        });
+    }
+
+    // The player stands still while flying the camera around.
+    if !player.movement_enabled || camera_state.mode == CameraMode::FreeFly {
+        input.movement = Vec3::ZERO;
+        return;
+    }
+
+    let dt = time.delta_seconds();
+    let encumbrance = Encumbrance::from_mass(player.inventory.total_mass(), player.carry_capacity);
+    let wants_sprint = actions.pressed(Action::Sprint) && encumbrance.can_sprint();
+    let sprint_multiplier = match stamina.tick(dt, wants_sprint) {
+        true => SPRINT_MULTIPLIER,
+        false => 1.0,
+    };
+
+    let speed = player.speed * encumbrance.speed_multiplier() * sprint_multiplier;
+    input.movement = movement_direction(&actions, camera_transform.single()) * speed;
+    if actions.just_pressed(Action::Jump) {
+        input.jump = Some(JUMP_VELOCITY * encumbrance.jump_multiplier());
+    }
+}
+
+/// Turns the player and moves the camera after them. Runs once the
+/// controller has moved the player, so the camera never lags a frame behind.
+#[allow(clippy::too_many_arguments)]
+pub fn player_camera(
+    time: Res<Time>,
+    mut player_query: Query<(Entity, &mut Transform, &mut Player)>,
+    actions: Res<ActionState>,
     mut camera_transform: Query<&mut Transform, (With<Camera3d>, Without<Player>)>,
-    mouse_motion: EventReader<MouseMotion>,
+    mut camera_state: ResMut<CameraState>,
+    camera_settings: Res<CameraSettings>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    spatial_query: SpatialQuery,
 ) {
-    let (mut player_transform, mut player) = player_query.single_mut();
+    let (player_entity, mut player_transform, mut player) = player_query.single_mut();
     if !player.movement_enabled {
         return;
     }
 
     let mut camera_transform = camera_transform.single_mut();
-    let (mut player_vel, ray_hits) = match player_physics.get_single_mut() {
-        Ok((vel, hits)) => (vel, hits),
-        Err(_) => return,
-    };
-
-    player_linear_movement(
-        time,
-        &mut player_vel,
-        &player,
-        keys,
-        &mut camera_transform,
-        ray_hits,
-    );
+    let dt = time.delta_seconds();
+    if camera_state.mode == CameraMode::FreeFly {
+        let look = smoothed_look(
+            &mut mouse_motion,
+            &actions,
+            &camera_settings,
+            &mut camera_state.pending_look,
+            dt,
+        );
+        free_fly_camera_update(
+            &mut camera_transform,
+            &camera_settings,
+            free_fly_direction(&actions),
+            look,
+            FREE_FLY_SPEED * dt,
+        );
+        return;
+    }
 
     let camera_y_rotation = player_rotation(
         &mut player_transform,
-        mouse_motion,
-        player.mouse_sensitivity,
+        &mut mouse_motion,
+        &actions,
+        &camera_settings,
+        &mut camera_state.pending_look,
+        dt,
     );
 
-    third_person_camera_update(
-        &(player_transform.translation + Vec3::new(0.0, 2.0, 0.0)),
-        &player_transform.rotation,
-        player.mouse_sensitivity,
-        &mut camera_transform,
-        &mut player.camera_data,
-        camera_y_rotation,
-    );
-}
-
-pub fn player_linear_movement(
-    time: Res<Time>,
-    player_vel: &mut LinearVelocity,
-    player: &Player,
-    keys: Res<ButtonInput<KeyCode>>,
-    camera_transform: &mut Transform,
-    ray_hits: &RayHits,
-) {
-    let on_ground = ray_hits.iter().any(|hit| hit.time_of_impact < 0.01);
-
-    // Movement
-    let direction_change = keys
-        .get_pressed()
-        .fold(Vec3::ZERO, |mut acc, key| {
-            let planar_movement = match key {
-                KeyCode::KeyW => camera_transform.forward().xz(),
-                KeyCode::KeyA => camera_transform.left().xz(),
-                KeyCode::KeyS => camera_transform.back().xz(),
-                KeyCode::KeyD => camera_transform.right().xz(),
-                _ => Vec2::ZERO,
+    let head = player_transform.translation + Vec3::new(0.0, 2.0, 0.0);
+    let mut target = *camera_transform;
+    match camera_state.mode {
+        CameraMode::FirstPerson => first_person_camera_update(
+            &head,
+            &player_transform.rotation,
+            &camera_settings,
+            &mut target,
+            &mut player.camera_data,
+            camera_y_rotation,
+        ),
+        _ => {
+            let scroll = mouse_wheel
+                .read()
+                .map(|wheel| match wheel.unit {
+                    MouseScrollUnit::Line => wheel.y,
+                    MouseScrollUnit::Pixel => wheel.y / 16.0,
+                })
+                .sum();
+            // The wheel scrolls the hotbar unless zoom is held.
+            if actions.pressed(Action::Zoom) {
+                zoom(&mut player.camera_data, scroll);
+            }
+
+            let sphere = Collider::sphere(CAMERA_RADIUS);
+            let filter = SpatialQueryFilter::default().with_excluded_entities([player_entity]);
+            let cast = |origin: Vec3, direction: Vec3, max: f32| {
+                let direction = Direction3d::new(direction).ok()?;
+                spatial_query
+                    .cast_shape(
+                        &sphere,
+                        origin,
+                        Quat::IDENTITY,
+                        direction,
+                        max,
+                        true,
+                        filter.clone(),
+                    )
+                    .map(|hit| hit.time_of_impact)
             };
 
-            acc.x += planar_movement.x;
-            acc.z += planar_movement.y;
-
-            acc
-        })
-        .normalize_or_zero();
-
-    // Gravity and jumping
-    if !on_ground {
-        player_vel.0.y -= 9.8 * time.delta_seconds();
-    } else if keys.just_pressed(KeyCode::Space) {
-        player_vel.0.y = 4.9;
-    } else {
-        player_vel.0.y = 0.0;
+            third_person_camera_update(
+                &head,
+                &player_transform.rotation,
+                &camera_settings,
+                &mut target,
+                &mut player.camera_data,
+                camera_y_rotation,
+                dt,
+                cast,
+            );
+        }
     }
+    camera_state.transition =
+        blend_camera(&mut camera_transform, &target, camera_state.transition, dt);
+}
 
-    let new_change = direction_change * time.delta_seconds() * player.speed;
+/// Camera relative direction to fly in. Jumping and [Action::FlyDown] fly
+/// straight up and down.
+fn free_fly_direction(actions: &ActionState) -> Vec3 {
+    Vec3::new(
+        actions.axis(Action::MoveLeft, Action::MoveRight),
+        actions.axis(Action::FlyDown, Action::Jump),
+        actions.axis(Action::MoveForward, Action::MoveBack),
+    )
+}
+
+/// Horizontal direction the movement actions point in, relative to the
+/// camera. A stick pushed part of the way moves slower, but going diagonally
+/// is no faster than going straight.
+fn movement_direction(actions: &ActionState, camera_transform: &Transform) -> Vec3 {
+    let planar_movement = (camera_transform.forward().xz().normalize_or_zero()
+        * actions.axis(Action::MoveBack, Action::MoveForward)
+        + camera_transform.right().xz().normalize_or_zero()
+            * actions.axis(Action::MoveLeft, Action::MoveRight))
+    .clamp_length_max(1.0);
+    Vec3::new(planar_movement.x, 0.0, planar_movement.y)
+}
 
-    // How much of the new change to apply. This is smoothing.
-    let percent_change = time.delta_seconds()
-        * match on_ground {
-            true => 10.0,
-            false => 1.0,
-        };
+/// Yaw and pitch to turn by this frame. With smoothing on, part of the mouse
+/// movement is kept in `pending_look` for the following frames.
+fn smoothed_look(
+    mouse_motion: &mut EventReader<MouseMotion>,
+    actions: &ActionState,
+    settings: &CameraSettings,
+    pending_look: &mut Vec2,
+    dt: f32,
+) -> Vec2 {
+    let mouse = mouse_motion
+        .read()
+        .fold(Vec2::ZERO, |acc, motion| acc + motion.delta);
+    let stick = Vec2::new(
+        actions.axis(Action::LookLeft, Action::LookRight),
+        actions.axis(Action::LookUp, Action::LookDown),
+    );
+    let mouse = mouse + stick * GAMEPAD_LOOK_SPEED * dt;
 
-    #[rustfmt::skip]
-    player_vel.0 = new_change   * percent_change
-                 + player_vel.0 * (1.0 - percent_change);
+    *pending_look += settings.look_delta(mouse);
+    let look = *pending_look * settings.smoothing_factor(dt);
+    *pending_look -= look;
+    look
 }
 
+/// Turns the player about the y axis and returns how far the camera should
+/// pitch.
 pub fn player_rotation(
     player_transform: &mut Transform,
-    mut mouse_motion: EventReader<MouseMotion>,
-    sensitivity: f32,
+    mouse_motion: &mut EventReader<MouseMotion>,
+    actions: &ActionState,
+    settings: &CameraSettings,
+    pending_look: &mut Vec2,
+    dt: f32,
 ) -> f32 {
-    let change = -mouse_motion
-        .read()
-        .fold(Vec2::ZERO, |acc, motion| acc + motion.delta);
-
-    let player_quat = Quat::from_rotation_y(change.x * sensitivity);
-    player_transform.rotation *= player_quat;
+    let look = smoothed_look(mouse_motion, actions, settings, pending_look, dt);
+    player_transform.rotation *= Quat::from_rotation_y(look.x);
 
-    -change.y
+    look.y
 }
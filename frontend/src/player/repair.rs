@@ -0,0 +1,64 @@
+use backend::iams::inventory::ItemKey;
+use backend::input::{Action, ActionState};
+use backend::items::tool::wear_tool;
+use backend::items::{Item, ItemAction};
+use backend::machines::{Machine, MachineState};
+use backend::player::hotbar::Hotbar;
+use backend::player::profile::PlayerLogic;
+use backend::player::skills::{ExperienceGained, Skill, REPAIR_EXPERIENCE};
+use bevy::prelude::*;
+
+use super::Player;
+
+/// How close the player has to be to a machine to repair it.
+const REPAIR_REACH: f32 = 3.0;
+
+/// Repairs the closest broken machine within reach with the wrench in hand,
+/// paying with spare parts from the player's inventory.
+pub fn repair_nearby_machine(
+    actions: Res<ActionState>,
+    mut player: Query<(Entity, &Transform, &mut Player, &mut PlayerLogic, &Hotbar)>,
+    mut machines: Query<(&Transform, &mut Machine)>,
+    mut experience: EventWriter<ExperienceGained>,
+) {
+    if !actions.just_pressed(Action::Interact) {
+        return;
+    }
+
+    let (player_entity, player_transform, mut player, mut profile, hotbar) = player.single_mut();
+    let Some(wrench) = hotbar
+        .held_tool(&player.inventory)
+        .filter(|tool| tool.action() == Some(ItemAction::Repair))
+        .map(|tool| ItemKey::of(tool))
+    else {
+        return;
+    };
+    let closest = machines
+        .iter_mut()
+        .filter(|(_, machine)| matches!(machine.state, MachineState::Failed(_)))
+        .map(|(transform, machine)| {
+            (
+                transform.translation.distance(player_transform.translation),
+                machine,
+            )
+        })
+        .filter(|(distance, _)| *distance <= REPAIR_REACH)
+        .min_by(|(a, _), (b, _)| a.total_cmp(b));
+
+    if let Some((_, mut machine)) = closest {
+        match machine.repair(&mut player.inventory) {
+            Ok(()) => {
+                wear_tool(&mut player.inventory, wrench);
+                // Better engineers do a more thorough job.
+                machine.failure_odds = profile.skills.failure_odds_multiplier(Skill::Engineering);
+                profile.stats.machines_repaired += 1;
+                experience.send(ExperienceGained {
+                    player: player_entity,
+                    skill: Skill::Engineering,
+                    amount: REPAIR_EXPERIENCE,
+                });
+            }
+            Err(err) => log::info!("Could not repair machine: {err:?}"),
+        }
+    }
+}
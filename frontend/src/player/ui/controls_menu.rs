@@ -0,0 +1,238 @@
+use backend::input::{Action, ActionState, AxisDirection, Binding, InputBindings};
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+
+use super::ChangedButton;
+
+/// How far a stick has to be pushed to be picked up as a new binding.
+const AXIS_BINDING_THRESHOLD: f32 = 0.7;
+
+#[derive(Component)]
+pub struct ControlsUIMarker;
+
+#[derive(Component)]
+pub struct ControlsUIButton {
+    action: Action,
+}
+
+/// The action waiting for the player to press its new binding.
+#[derive(Resource, Debug, Default)]
+pub struct Rebinding(Option<Action>);
+
+pub fn controls_menu(mut commands: Commands) {
+    let controls_ui = NodeBundle {
+        style: Style {
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            flex_wrap: FlexWrap::Wrap,
+            position_type: PositionType::Absolute,
+            width: Val::Vw(60.0),
+            height: Val::Vh(80.0),
+            justify_self: JustifySelf::Center,
+            align_self: AlignSelf::Center,
+            padding: UiRect::all(Val::Px(10.0)),
+            ..Default::default()
+        },
+        visibility: Visibility::Hidden,
+        background_color: Color::rgba(0.1, 0.1, 0.1, 0.5).into(),
+        ..Default::default()
+    };
+
+    commands.spawn((controls_ui, ControlsUIMarker));
+}
+
+fn action_label(action: Action, bindings: &InputBindings, rebinding: &Rebinding) -> String {
+    if rebinding.0 == Some(action) {
+        return format!("{}: press a key, Escape to cancel", action.name());
+    }
+
+    let bound = bindings
+        .get(action)
+        .iter()
+        .map(Binding::label)
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("{}: {}", action.name(), bound)
+}
+
+/// Lists every action with its bindings. Rebuilt when the menu is opened and
+/// whenever a binding changes.
+pub fn refresh_controls_menu(
+    mut commands: Commands,
+    bindings: Res<InputBindings>,
+    rebinding: Res<Rebinding>,
+    menu: Query<(Entity, Ref<Visibility>), With<ControlsUIMarker>>,
+) {
+    let (menu, visibility) = menu.single();
+    let changed = visibility.is_changed() || bindings.is_changed() || rebinding.is_changed();
+    if *visibility != Visibility::Visible || !changed {
+        return;
+    }
+
+    commands
+        .entity(menu)
+        .despawn_descendants()
+        .with_children(|parent| {
+            for action in Action::ALL {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                margin: UiRect::all(Val::Px(2.0)),
+                                padding: UiRect::all(Val::Px(5.0)),
+                                ..Default::default()
+                            },
+                            background_color: Color::rgba(0.1, 0.1, 0.1, 0.5).into(),
+                            ..Default::default()
+                        },
+                        ControlsUIButton { action },
+                    ))
+                    .with_children(|button| {
+                        button.spawn(TextBundle::from_section(
+                            action_label(action, &bindings, &rebinding),
+                            Default::default(),
+                        ));
+                    });
+            }
+        });
+}
+
+/// Clicking an action waits for its new binding. Actions stop firing while
+/// waiting, so the new binding does not also trigger its old action.
+pub fn handle_controls_input(
+    mut rebinding: ResMut<Rebinding>,
+    mut actions: ResMut<ActionState>,
+    interaction: Query<(&Interaction, &ControlsUIButton), ChangedButton>,
+) {
+    for (interaction, button) in interaction.iter() {
+        if *interaction == Interaction::Pressed {
+            rebinding.0 = Some(button.action);
+            actions.suspended = true;
+        }
+    }
+}
+
+/// The first key, mouse button, wheel scroll, gamepad button or stick pushed
+/// this frame. Keys pressed with control, shift or alt held become chords.
+fn pressed_binding(
+    keys: &ButtonInput<KeyCode>,
+    mouse: &ButtonInput<MouseButton>,
+    mouse_wheel: f32,
+    gamepads: &Gamepads,
+    gamepad_buttons: &ButtonInput<GamepadButton>,
+    gamepad_axes: &Axis<GamepadAxis>,
+) -> Option<Binding> {
+    const MODIFIERS: [KeyCode; 6] = [
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+        KeyCode::ShiftLeft,
+        KeyCode::ShiftRight,
+        KeyCode::AltLeft,
+        KeyCode::AltRight,
+    ];
+    const STICKS: [GamepadAxisType; 4] = [
+        GamepadAxisType::LeftStickX,
+        GamepadAxisType::LeftStickY,
+        GamepadAxisType::RightStickX,
+        GamepadAxisType::RightStickY,
+    ];
+
+    let mut held = MODIFIERS
+        .into_iter()
+        .filter(|modifier| keys.pressed(*modifier));
+    let modifiers = (held.next(), held.next());
+    let key = keys
+        .get_just_pressed()
+        .find(|key| !MODIFIERS.contains(key))
+        .map(|key| match modifiers {
+            (Some(first), Some(second)) => Binding::DoubleChord(first, second, *key),
+            (Some(modifier), None) => Binding::Chord(modifier, *key),
+            _ => Binding::Key(*key),
+        });
+    let mouse_button = || {
+        mouse
+            .get_just_pressed()
+            .next()
+            .map(|button| Binding::Mouse(*button))
+    };
+    let wheel = || match mouse_wheel {
+        up if up > 0.0 => Some(Binding::MouseWheel(AxisDirection::Positive)),
+        down if down < 0.0 => Some(Binding::MouseWheel(AxisDirection::Negative)),
+        _ => None,
+    };
+    let gamepad_button = || {
+        gamepad_buttons
+            .get_just_pressed()
+            .next()
+            .map(|button| Binding::GamepadButton(button.button_type))
+    };
+    let stick = || {
+        gamepads.iter().find_map(|gamepad| {
+            STICKS.into_iter().find_map(|axis| {
+                let value = gamepad_axes.get(GamepadAxis::new(gamepad, axis))?;
+                match value.abs() >= AXIS_BINDING_THRESHOLD {
+                    true if value > 0.0 => {
+                        Some(Binding::GamepadAxis(axis, AxisDirection::Positive))
+                    }
+                    true => Some(Binding::GamepadAxis(axis, AxisDirection::Negative)),
+                    false => None,
+                }
+            })
+        })
+    };
+
+    key.or_else(mouse_button)
+        .or_else(wheel)
+        .or_else(gamepad_button)
+        .or_else(stick)
+}
+
+/// Binds whatever is pressed to the action waiting for it. The
+/// [SettingsPlugin](backend::settings::SettingsPlugin) saves the new bindings.
+#[allow(clippy::too_many_arguments)]
+pub fn capture_binding(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut wheel: EventReader<MouseWheel>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut bindings: ResMut<InputBindings>,
+    mut rebinding: ResMut<Rebinding>,
+    mut actions: ResMut<ActionState>,
+) {
+    // Read every frame, so scrolling from before the menu was waiting is not
+    // picked up.
+    let mouse_wheel = wheel.read().map(|event| event.y).sum();
+    let Some(action) = rebinding.0 else {
+        return;
+    };
+
+    let Some(binding) = pressed_binding(
+        &keys,
+        &mouse,
+        mouse_wheel,
+        &gamepads,
+        &gamepad_buttons,
+        &gamepad_axes,
+    ) else {
+        return;
+    };
+
+    // Whatever pauses the game cancels instead of being bound.
+    if bindings.get(Action::Pause).contains(&binding) {
+        rebinding.0 = None;
+        actions.suspended = false;
+        return;
+    }
+
+    for unbound in bindings.bind(action, binding) {
+        log::info!(
+            "{} is no longer bound to {}",
+            binding.label(),
+            unbound.name()
+        );
+    }
+    rebinding.0 = None;
+    actions.suspended = false;
+}
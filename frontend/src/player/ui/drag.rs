@@ -0,0 +1,553 @@
+use backend::iams::inventory::ItemKey;
+use backend::iams::view::format_amount;
+use backend::items::{Item, ItemWeight};
+use backend::player::hotbar::Hotbar;
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use bevy::window::PrimaryWindow;
+
+use super::tab_menu::{InventoryUIItem, InventoryUILayout, InventoryUIMarker};
+use super::ChangedButton;
+use crate::player::actions::drop_item;
+use crate::player::container::{Container, ContainerAssets, OpenContainer};
+use crate::player::Player;
+
+/// Which inventory an item is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemSource {
+    Player,
+    Container(Entity),
+}
+
+/// Somewhere a dragged item can be let go of. Found under the cursor through
+/// its [RelativeCursorPosition].
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub enum DropTarget {
+    /// An item slot. Dropping there puts the dragged item in its place.
+    Slot(ItemSource, ItemKey),
+    /// A whole inventory.
+    Panel(ItemSource),
+    Hotbar(usize),
+}
+
+impl DropTarget {
+    /// When the cursor is over several targets, the one drawn on top wins.
+    #[rustfmt::skip]
+    fn priority(&self) -> u8 {
+        match self {
+            DropTarget::Slot(..)                        => 2,
+            DropTarget::Hotbar(_)                       => 2,
+            DropTarget::Panel(ItemSource::Container(_)) => 1,
+            DropTarget::Panel(ItemSource::Player)       => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Drag {
+    pub source: ItemSource,
+    pub key: ItemKey,
+    /// How much of the item to move, or all of it.
+    pub amount: Option<ItemWeight>,
+    /// Unit continuous amounts of the item are in.
+    pub unit: &'static str,
+    /// Let go of on the next click instead of when the button comes up, for
+    /// drags started from the split dialog.
+    pub sticky: bool,
+}
+
+/// The item being dragged.
+#[derive(Resource, Debug, Default)]
+pub struct Dragging(pub Option<Drag>);
+
+#[derive(Component)]
+pub struct DragGhostMarker;
+
+/// An item being split, with how much of it to take.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Split {
+    pub source: ItemSource,
+    pub key: ItemKey,
+    pub amount: ItemWeight,
+    pub whole: ItemWeight,
+    pub unit: &'static str,
+}
+
+impl Split {
+    /// Starts at half of `item`. Single items cannot be split.
+    fn new(source: ItemSource, item: &dyn Item) -> Option<Self> {
+        let whole = item.amount();
+        let amount = match whole {
+            ItemWeight::Continuous(kg) if kg > 0.0 => ItemWeight::Continuous(kg / 2.0),
+            ItemWeight::Discrete(count) if count > 1 => ItemWeight::Discrete(count / 2),
+            _ => return None,
+        };
+        Some(Self {
+            source,
+            key: ItemKey::of(item),
+            amount,
+            whole,
+            unit: item.unit(),
+        })
+    }
+
+    /// Changes the amount by `steps` tenths of a mass or single items.
+    fn adjust(&mut self, steps: i32) {
+        self.amount = match (self.amount, self.whole) {
+            (ItemWeight::Continuous(amount), ItemWeight::Continuous(whole)) => {
+                let step = whole / 10.0;
+                ItemWeight::Continuous((amount + step * steps as f32).clamp(step, whole))
+            }
+            (ItemWeight::Discrete(count), ItemWeight::Discrete(whole)) => {
+                let count = count as i64 + steps as i64;
+                ItemWeight::Discrete(count.clamp(1, whole as i64) as usize)
+            }
+            (amount, _) => amount,
+        };
+    }
+}
+
+/// The item the split dialog is open for.
+#[derive(Resource, Debug, Default)]
+pub struct Splitting(pub Option<Split>);
+
+#[derive(Component)]
+pub struct SplitUIMarker;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitUIButton {
+    Less,
+    More,
+    Take,
+    Cancel,
+}
+
+pub fn drag_ui(mut commands: Commands) {
+    commands.spawn((
+        TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(20),
+            background_color: Color::rgba(0.1, 0.1, 0.1, 0.8).into(),
+            ..Default::default()
+        },
+        DragGhostMarker,
+    ));
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(5.0),
+                position_type: PositionType::Absolute,
+                justify_self: JustifySelf::Center,
+                align_self: AlignSelf::Center,
+                padding: UiRect::all(Val::Px(10.0)),
+                ..Default::default()
+            },
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(10),
+            background_color: Color::rgba(0.1, 0.1, 0.1, 0.9).into(),
+            ..Default::default()
+        },
+        SplitUIMarker,
+    ));
+}
+
+/// Moves an item between the player and the open container. Returns where it
+/// landed.
+fn transfer(
+    player: &mut Player,
+    containers: &mut Query<&mut Container>,
+    from: ItemSource,
+    to: ItemSource,
+    key: ItemKey,
+    amount: Option<ItemWeight>,
+) -> Option<ItemKey> {
+    let amount = amount.map(|amount| amount.as_f32());
+    match (from, to) {
+        (ItemSource::Player, ItemSource::Container(entity)) => {
+            let mut container = containers.get_mut(entity).ok()?;
+            player.inventory.move_item(key, amount, &mut container.0)
+        }
+        (ItemSource::Container(entity), ItemSource::Player) => {
+            let mut container = containers.get_mut(entity).ok()?;
+            container.0.move_item(key, amount, &mut player.inventory)
+        }
+        // Only one container is ever open.
+        _ => None,
+    }
+}
+
+/// Splits `amount` of an item off into an item of its own, in the same
+/// inventory. Returns the new item's key.
+fn split(
+    player: &mut Player,
+    containers: &mut Query<&mut Container>,
+    source: ItemSource,
+    key: ItemKey,
+    amount: ItemWeight,
+) -> Option<ItemKey> {
+    match source {
+        ItemSource::Player => player.inventory.split_item(key, amount.as_f32()),
+        ItemSource::Container(entity) => containers
+            .get_mut(entity)
+            .ok()?
+            .0
+            .split_item(key, amount.as_f32()),
+    }
+}
+
+/// Picks items up from the inventory screen and lets go of them over a slot,
+/// the container, the hotbar, or away from the menu to drop them in the
+/// world. Shift-clicking an item moves it straight between the player and
+/// the open container.
+#[allow(clippy::too_many_arguments)]
+pub fn drag_items(
+    mut commands: Commands,
+    mouse: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut dragging: ResMut<Dragging>,
+    mut layout: ResMut<InventoryUILayout>,
+    open: Res<OpenContainer>,
+    container_assets: Res<ContainerAssets>,
+    menu: Query<&Visibility, With<InventoryUIMarker>>,
+    slots: Query<(&Interaction, &InventoryUIItem), Changed<Interaction>>,
+    targets: Query<(&DropTarget, &RelativeCursorPosition, &InheritedVisibility)>,
+    mut players: Query<(&Transform, &mut Player, &mut Hotbar)>,
+    mut containers: Query<&mut Container>,
+) {
+    if *menu.single() != Visibility::Visible {
+        if dragging.0.is_some() {
+            dragging.0 = None;
+        }
+        return;
+    }
+
+    let (transform, mut player, mut hotbar) = players.single_mut();
+
+    if let Some(drag) = dragging.0 {
+        let let_go = match drag.sticky {
+            true => mouse.just_pressed(MouseButton::Left),
+            false => mouse.just_released(MouseButton::Left),
+        };
+        if !let_go {
+            return;
+        }
+        dragging.0 = None;
+
+        // Outside the window nothing is under the cursor.
+        let in_window = window.single().cursor_position().is_some();
+        let target = targets
+            .iter()
+            .filter(|(_, cursor, visible)| in_window && cursor.mouse_over() && visible.get())
+            .max_by_key(|(target, ..)| target.priority())
+            .map(|(target, ..)| *target);
+
+        match target {
+            Some(DropTarget::Slot(to, target_key)) => {
+                // Part of an item let go of in its own inventory is split off
+                // there.
+                let landed = match (to == drag.source, drag.amount) {
+                    (true, Some(amount)) => {
+                        split(&mut player, &mut containers, to, drag.key, amount)
+                    }
+                    (true, None) => Some(drag.key),
+                    (false, _) => transfer(
+                        &mut player,
+                        &mut containers,
+                        drag.source,
+                        to,
+                        drag.key,
+                        drag.amount,
+                    ),
+                };
+                let placed = landed.filter(|landed| *landed != target_key);
+                if let Some(landed) = placed.filter(|_| to == ItemSource::Player) {
+                    layout.0.place(&player.inventory, landed, target_key);
+                }
+            }
+            Some(DropTarget::Panel(to)) => {
+                transfer(
+                    &mut player,
+                    &mut containers,
+                    drag.source,
+                    to,
+                    drag.key,
+                    drag.amount,
+                );
+            }
+            Some(DropTarget::Hotbar(slot)) => {
+                let held = match drag.source {
+                    ItemSource::Player => Some(drag.key),
+                    ItemSource::Container(_) => transfer(
+                        &mut player,
+                        &mut containers,
+                        drag.source,
+                        ItemSource::Player,
+                        drag.key,
+                        drag.amount,
+                    ),
+                };
+                if let Some(held) = held {
+                    hotbar.assign(slot, held);
+                }
+            }
+            None => {
+                let amount = drag.amount.map(|amount| amount.as_f32());
+                match drag.source {
+                    ItemSource::Player => {
+                        drop_item(
+                            &mut commands,
+                            &container_assets,
+                            transform,
+                            &mut player.inventory,
+                            drag.key,
+                            amount,
+                        );
+                    }
+                    ItemSource::Container(entity) => {
+                        if let Ok(mut container) = containers.get_mut(entity) {
+                            drop_item(
+                                &mut commands,
+                                &container_assets,
+                                transform,
+                                &mut container.0,
+                                drag.key,
+                                amount,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        return;
+    }
+
+    let quick = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    for (interaction, slot) in slots.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match quick {
+            true => {
+                let to = match slot.source {
+                    ItemSource::Player => match open.0 {
+                        Some(container) => ItemSource::Container(container),
+                        None => continue,
+                    },
+                    ItemSource::Container(_) => ItemSource::Player,
+                };
+                transfer(
+                    &mut player,
+                    &mut containers,
+                    slot.source,
+                    to,
+                    slot.key,
+                    None,
+                );
+            }
+            false => {
+                let inventory = match slot.source {
+                    ItemSource::Player => Some(&player.inventory),
+                    ItemSource::Container(entity) => {
+                        containers.get(entity).ok().map(|container| &container.0)
+                    }
+                };
+                let Some(item) = inventory.and_then(|inventory| inventory.get(slot.key)) else {
+                    continue;
+                };
+                dragging.0 = Some(Drag {
+                    source: slot.source,
+                    key: slot.key,
+                    amount: None,
+                    unit: item.unit(),
+                    sticky: false,
+                });
+            }
+        }
+    }
+}
+
+/// Keeps the name of the dragged item under the cursor.
+pub fn show_drag_ghost(
+    dragging: Res<Dragging>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut ghost: Query<(&mut Style, &mut Visibility, &mut Text), With<DragGhostMarker>>,
+) {
+    let (mut style, mut visibility, mut text) = ghost.single_mut();
+    let cursor = window.single().cursor_position();
+    let (Some(drag), Some(cursor)) = (dragging.0, cursor) else {
+        if *visibility != Visibility::Hidden {
+            *visibility = Visibility::Hidden;
+        }
+        return;
+    };
+
+    // Just off the cursor, so the slot under it stays visible.
+    style.left = Val::Px(cursor.x + 12.0);
+    style.top = Val::Px(cursor.y + 12.0);
+    *visibility = Visibility::Visible;
+    if dragging.is_changed() {
+        let label = match drag.amount {
+            Some(amount) => format!(
+                "{} ({})",
+                drag.key.type_name,
+                format_amount(amount, drag.unit)
+            ),
+            None => drag.key.type_name.to_string(),
+        };
+        *text = Text::from_section(label, Default::default());
+    }
+}
+
+/// Right-clicking an item opens the split dialog for it.
+pub fn open_split_dialog(
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut splitting: ResMut<Splitting>,
+    menu: Query<&Visibility, With<InventoryUIMarker>>,
+    slots: Query<(&Interaction, &InventoryUIItem)>,
+    player: Query<&Player>,
+    containers: Query<&Container>,
+) {
+    if *menu.single() != Visibility::Visible {
+        if splitting.0.is_some() {
+            splitting.0 = None;
+        }
+        return;
+    }
+    if !mouse.just_pressed(MouseButton::Right) {
+        return;
+    }
+
+    let Some((_, slot)) = slots
+        .iter()
+        .find(|(interaction, _)| **interaction != Interaction::None)
+    else {
+        return;
+    };
+    let inventory = match slot.source {
+        ItemSource::Player => &player.single().inventory,
+        ItemSource::Container(entity) => match containers.get(entity) {
+            Ok(container) => &container.0,
+            Err(_) => return,
+        },
+    };
+    let Some(item) = inventory.get(slot.key) else {
+        return;
+    };
+
+    splitting.0 = Split::new(slot.source, item);
+}
+
+fn split_button(parent: &mut ChildBuilder, button: SplitUIButton) {
+    let label = match button {
+        SplitUIButton::Less => "-",
+        SplitUIButton::More => "+",
+        SplitUIButton::Take => "Take",
+        SplitUIButton::Cancel => "Cancel",
+    };
+
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::horizontal(Val::Px(10.0)),
+                    ..Default::default()
+                },
+                background_color: Color::rgba(0.2, 0.2, 0.2, 0.8).into(),
+                ..Default::default()
+            },
+            button,
+        ))
+        .with_children(|button| {
+            button.spawn(TextBundle::from_section(label, Default::default()));
+        });
+}
+
+pub fn refresh_split_dialog(
+    mut commands: Commands,
+    splitting: Res<Splitting>,
+    mut dialog: Query<(Entity, &mut Visibility), With<SplitUIMarker>>,
+) {
+    if !splitting.is_changed() {
+        return;
+    }
+
+    let (dialog, mut visibility) = dialog.single_mut();
+    let Some(split) = splitting.0 else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    *visibility = Visibility::Visible;
+    commands
+        .entity(dialog)
+        .despawn_descendants()
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!(
+                    "Split {}: {} of {}",
+                    split.key.type_name,
+                    format_amount(split.amount, split.unit),
+                    format_amount(split.whole, split.unit)
+                ),
+                Default::default(),
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        display: Display::Flex,
+                        column_gap: Val::Px(10.0),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with_children(|buttons| {
+                    split_button(buttons, SplitUIButton::Less);
+                    split_button(buttons, SplitUIButton::More);
+                    split_button(buttons, SplitUIButton::Take);
+                    split_button(buttons, SplitUIButton::Cancel);
+                });
+        });
+}
+
+/// Taking the split off starts dragging it, to be let go of with the next
+/// click.
+pub fn handle_split_input(
+    mut splitting: ResMut<Splitting>,
+    mut dragging: ResMut<Dragging>,
+    interaction: Query<(&Interaction, &SplitUIButton), ChangedButton>,
+) {
+    for (interaction, button) in interaction.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(split) = splitting.0.as_mut() else {
+            continue;
+        };
+
+        match button {
+            SplitUIButton::Less => split.adjust(-1),
+            SplitUIButton::More => split.adjust(1),
+            SplitUIButton::Take => {
+                dragging.0 = Some(Drag {
+                    source: split.source,
+                    key: split.key,
+                    amount: Some(split.amount),
+                    unit: split.unit,
+                    sticky: true,
+                });
+                splitting.0 = None;
+            }
+            SplitUIButton::Cancel => splitting.0 = None,
+        }
+    }
+}
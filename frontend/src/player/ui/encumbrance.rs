@@ -0,0 +1,41 @@
+use backend::player::encumbrance::Encumbrance;
+use bevy::prelude::*;
+
+use crate::player::Player;
+
+#[derive(Component)]
+pub struct EncumbranceUIMarker;
+
+pub fn encumbrance_display(mut commands: Commands) {
+    let text = TextBundle::from_section("", Default::default()).with_style(Style {
+        position_type: PositionType::Absolute,
+        bottom: Val::Px(10.0),
+        left: Val::Px(10.0),
+        ..Default::default()
+    });
+
+    commands.spawn((text, EncumbranceUIMarker));
+}
+
+/// Shows carried mass against the [Encumbrance] thresholds.
+pub fn update_encumbrance_display(
+    player: Query<&Player>,
+    mut text: Query<&mut Text, With<EncumbranceUIMarker>>,
+) {
+    let player = player.single();
+    let mut text = text.single_mut();
+
+    let mass = player.inventory.total_mass();
+    let encumbrance = Encumbrance::from_mass(mass, player.carry_capacity);
+    let next_threshold = match encumbrance {
+        Encumbrance::Light => Encumbrance::BURDENED,
+        _ => Encumbrance::OVERLOADED,
+    } * player.carry_capacity;
+
+    text.sections[0].value = format!(
+        "{} ({:.1} / {:.1} kg)",
+        encumbrance.label(),
+        mass,
+        next_threshold,
+    );
+}
@@ -0,0 +1,110 @@
+use backend::player::hotbar::{Hotbar, HOTBAR_SLOTS};
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+
+use super::drag::DropTarget;
+use super::ChangedButton;
+use crate::player::Player;
+
+#[derive(Component)]
+pub struct HotbarUIMarker;
+
+#[derive(Component)]
+pub struct HotbarUISlot(pub usize);
+
+pub fn hotbar_display(mut commands: Commands) {
+    let hotbar_ui = NodeBundle {
+        style: Style {
+            display: Display::Flex,
+            flex_direction: FlexDirection::Row,
+            column_gap: Val::Px(4.0),
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            justify_self: JustifySelf::Center,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    commands
+        .spawn((hotbar_ui, HotbarUIMarker))
+        .with_children(|parent| {
+            for slot in 0..HOTBAR_SLOTS {
+                parent.spawn((
+                    ButtonBundle {
+                        style: Style {
+                            display: Display::Flex,
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            width: Val::Px(64.0),
+                            height: Val::Px(48.0),
+                            padding: UiRect::all(Val::Px(2.0)),
+                            ..Default::default()
+                        },
+                        background_color: Color::rgba(0.1, 0.1, 0.1, 0.5).into(),
+                        ..Default::default()
+                    },
+                    HotbarUISlot(slot),
+                    DropTarget::Hotbar(slot),
+                    RelativeCursorPosition::default(),
+                ));
+            }
+        });
+}
+
+/// Labels each slot with its key and the item in it, and lights up the one
+/// in hand.
+pub fn refresh_hotbar(
+    mut commands: Commands,
+    hotbar: Query<&Hotbar, Changed<Hotbar>>,
+    mut slots: Query<(Entity, &HotbarUISlot, &mut BackgroundColor)>,
+) {
+    let Ok(hotbar) = hotbar.get_single() else {
+        return;
+    };
+
+    let small = TextStyle {
+        font_size: 14.0,
+        ..Default::default()
+    };
+    for (entity, slot, mut background) in slots.iter_mut() {
+        *background = match slot.0 == hotbar.selected {
+            true => Color::rgba(0.4, 0.4, 0.4, 0.8).into(),
+            false => Color::rgba(0.1, 0.1, 0.1, 0.5).into(),
+        };
+        let name = hotbar.slots[slot.0].map_or("", |key| key.type_name);
+        commands
+            .entity(entity)
+            .despawn_descendants()
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    (slot.0 + 1).to_string(),
+                    small.clone(),
+                ));
+                parent.spawn(TextBundle::from_section(name, small.clone()));
+            });
+    }
+}
+
+/// Empties slots whose item has left the inventory.
+pub fn forget_missing_hotbar_items(mut players: Query<(&Player, &mut Hotbar), Changed<Player>>) {
+    for (player, mut hotbar) in players.iter_mut() {
+        let mut updated = hotbar.clone();
+        updated.forget_missing(&player.inventory);
+        if updated != *hotbar {
+            *hotbar = updated;
+        }
+    }
+}
+
+/// Clicking a slot empties it.
+pub fn handle_hotbar_input(
+    mut hotbar: Query<&mut Hotbar>,
+    interaction: Query<(&Interaction, &HotbarUISlot), ChangedButton>,
+) {
+    for (interaction, slot) in interaction.iter() {
+        if *interaction == Interaction::Pressed {
+            hotbar.single_mut().clear(slot.0);
+        }
+    }
+}
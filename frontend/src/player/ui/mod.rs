@@ -1 +1,12 @@
+pub mod controls_menu;
+pub mod drag;
+pub mod encumbrance;
+pub mod hotbar;
+pub mod research_menu;
 pub mod tab_menu;
+pub mod tooltip;
+
+use bevy::prelude::*;
+
+/// Buttons whose interaction changed this frame.
+pub type ChangedButton = (Changed<Interaction>, With<Button>);
@@ -0,0 +1,151 @@
+use backend::buildings::{Building, BuildingKind};
+use backend::machines::MachineProcess;
+use backend::player::profile::PlayerLogic;
+use backend::recipes::RecipeBook;
+use backend::research::{TechTree, Technology};
+use bevy::prelude::*;
+
+use super::ChangedButton;
+use crate::player::Player;
+
+/// How close a lab has to be for research to be paid for out of it rather
+/// than the player's inventory.
+const LAB_REACH: f32 = 5.0;
+
+#[derive(Component)]
+pub struct ResearchUIMarker;
+
+#[derive(Component)]
+pub struct ResearchUIButton {
+    id: String,
+}
+
+/// The tech tree is validated here, at startup, so a broken definition is
+/// caught before anyone can play with it. Runs after the [RecipeBook] is
+/// loaded, so the recipes it unlocks can be checked.
+pub fn load_tech_tree(mut commands: Commands, recipes: Res<RecipeBook>) {
+    let tree = TechTree::from_ron(include_str!("../../../assets/research.ron"), &recipes)
+        .unwrap_or_else(|err| panic!("Invalid tech tree: {err:?}"));
+    commands.insert_resource(tree);
+}
+
+pub fn research_menu(mut commands: Commands) {
+    let research_ui = NodeBundle {
+        style: Style {
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            position_type: PositionType::Absolute,
+            width: Val::Vw(40.0),
+            height: Val::Vh(60.0),
+            justify_self: JustifySelf::Center,
+            align_self: AlignSelf::Center,
+            padding: UiRect::all(Val::Px(10.0)),
+            ..Default::default()
+        },
+        visibility: Visibility::Hidden,
+        background_color: Color::rgba(0.1, 0.1, 0.1, 0.5).into(),
+        ..Default::default()
+    };
+
+    commands.spawn((research_ui, ResearchUIMarker));
+}
+
+fn technology_label(technology: &Technology) -> String {
+    let cost = technology
+        .cost
+        .iter()
+        .map(|cost| format!("{} {}", cost.amount, cost.item))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "{}\n{}\nCost: {}",
+        technology.name, technology.description, cost
+    )
+}
+
+/// Lists the technologies that can be researched next. Rebuilt when the menu
+/// is opened and whenever something gets researched.
+pub fn refresh_research_menu(
+    mut commands: Commands,
+    tree: Res<TechTree>,
+    player: Query<&PlayerLogic, With<Player>>,
+    menu: Query<(Entity, Ref<Visibility>), With<ResearchUIMarker>>,
+    mut researched: Local<usize>,
+) {
+    let profile = player.single();
+    let (menu, visibility) = menu.single();
+
+    let research_changed = *researched != profile.researched.len();
+    *researched = profile.researched.len();
+    if *visibility != Visibility::Visible || !(visibility.is_changed() || research_changed) {
+        return;
+    }
+
+    commands
+        .entity(menu)
+        .despawn_descendants()
+        .with_children(|parent| {
+            for technology in tree.available(profile) {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                margin: UiRect::all(Val::Px(5.0)),
+                                padding: UiRect::all(Val::Px(5.0)),
+                                ..Default::default()
+                            },
+                            background_color: Color::rgba(0.1, 0.1, 0.1, 0.5).into(),
+                            ..Default::default()
+                        },
+                        ResearchUIButton {
+                            id: technology.id.clone(),
+                        },
+                    ))
+                    .with_children(|button| {
+                        button.spawn(TextBundle::from_section(
+                            technology_label(technology),
+                            Default::default(),
+                        ));
+                    });
+            }
+        });
+}
+
+/// Researches the technology picked, paying with what is loaded into the
+/// closest lab in reach, or with the player's items if there is none.
+pub fn handle_research_input(
+    tree: Res<TechTree>,
+    mut player: Query<(&Transform, &mut Player, &mut PlayerLogic)>,
+    mut labs: Query<(&Building, &Transform, &mut MachineProcess)>,
+    interaction: Query<(&Interaction, &ResearchUIButton), ChangedButton>,
+) {
+    let (player_transform, mut player, mut profile) = player.single_mut();
+    let mut lab = labs
+        .iter_mut()
+        .filter(|(building, _, _)| building.kind == BuildingKind::Lab)
+        .map(|(_, transform, process)| {
+            (
+                transform.translation.distance(player_transform.translation),
+                process,
+            )
+        })
+        .filter(|(distance, _)| *distance <= LAB_REACH)
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, process)| process);
+
+    for (interaction, button) in interaction.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let inventory = match lab.as_mut() {
+            Some(lab) => &mut lab.input,
+            None => &mut player.inventory,
+        };
+        match tree.research(&button.id, &mut profile, inventory) {
+            Ok(()) => log::info!("Researched {}", button.id),
+            Err(err) => log::info!("Could not research {}: {err:?}", button.id),
+        }
+    }
+}
@@ -1,32 +1,58 @@
-use backend::items::Items;
+use backend::iams::inventory::ItemKey;
+use backend::iams::view::{InventoryPage, ItemView, Layout};
 use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
 
+use super::drag::{DropTarget, ItemSource};
+use super::ChangedButton;
+use crate::player::container::{Container, OpenContainer};
 use crate::player::Player;
 
+/// Items shown on one page of the inventory.
+pub const ITEMS_PER_PAGE: usize = 24;
+
 #[derive(Component)]
 pub struct InventoryUIItem {
-    id: usize,
+    pub source: ItemSource,
+    pub key: ItemKey,
 }
 
 #[derive(Component)]
 pub struct InventoryUIMarker;
 
-pub fn inventory_popup(mut commands: Commands, player: Query<&Player>) {
-    let player = player.single();
-    let inventory = &player.inventory;
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InventoryUIPageButton {
+    Previous,
+    Next,
+}
+
+/// The inventory page being looked at, from 0.
+#[derive(Resource, Debug, Default)]
+pub struct InventoryUIPage(pub usize);
+
+/// The order the player has dragged their items into.
+#[derive(Resource, Debug, Default)]
+pub struct InventoryUILayout(pub Layout);
 
+/// Everything the inventory screen shows, to tell when it needs redrawing.
+#[derive(Debug, PartialEq)]
+pub struct InventoryMenuView {
+    player: InventoryPage,
+    container: Option<(Entity, InventoryPage)>,
+}
+
+pub fn inventory_popup(mut commands: Commands) {
     let tab_ui = NodeBundle {
         style: Style {
             display: Display::Flex,
             flex_direction: FlexDirection::Row,
-            position_type: PositionType::Relative,
-            width: Val::Vw(50.0),
-            height: Val::Vh(50.0),
+            column_gap: Val::Px(10.0),
+            position_type: PositionType::Absolute,
+            width: Val::Vw(70.0),
+            height: Val::Vh(60.0),
             justify_self: JustifySelf::Center,
             align_self: AlignSelf::Center,
-            flex_wrap: FlexWrap::Wrap,
-            align_items: AlignItems::FlexStart,
-            align_content: AlignContent::FlexStart,
+            padding: UiRect::all(Val::Px(10.0)),
             ..Default::default()
         },
         visibility: Visibility::Hidden,
@@ -34,62 +60,255 @@ pub fn inventory_popup(mut commands: Commands, player: Query<&Player>) {
         ..Default::default()
     };
 
-    let item_box = move |parent: &mut ChildBuilder, item: &Items| {
-        parent
-            .spawn((
-                ButtonBundle {
+    // Dropping anywhere on the menu that is not a slot or the container
+    // keeps the item in the player's inventory.
+    commands.spawn((
+        tab_ui,
+        InventoryUIMarker,
+        DropTarget::Panel(ItemSource::Player),
+        RelativeCursorPosition::default(),
+    ));
+}
+
+fn item_box(parent: &mut ChildBuilder, source: ItemSource, item: &ItemView) {
+    let key = ItemKey {
+        type_name: item.type_name,
+        id: item.id,
+    };
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    width: Val::Px(90.0),
+                    height: Val::Px(100.0),
+                    margin: UiRect::all(Val::Px(5.0)),
+                    padding: UiRect::all(Val::Px(4.0)),
+                    ..Default::default()
+                },
+                background_color: Color::rgba(0.1, 0.1, 0.1, 0.5).into(),
+                ..Default::default()
+            },
+            InventoryUIItem { source, key },
+            DropTarget::Slot(source, key),
+            RelativeCursorPosition::default(),
+        ))
+        .with_children(|slot| {
+            // Items have no pictures yet, so the icon is a swatch of the
+            // item's colour with its initial.
+            slot.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(32.0),
+                    height: Val::Px(32.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                background_color: item.icon.into(),
+                ..Default::default()
+            })
+            .with_children(|icon| {
+                let initial = item.type_name.chars().next().unwrap_or('?');
+                icon.spawn(TextBundle::from_section(
+                    initial.to_string(),
+                    Default::default(),
+                ));
+            });
+
+            let small = TextStyle {
+                font_size: 14.0,
+                ..Default::default()
+            };
+            slot.spawn(TextBundle::from_section(item.type_name, small.clone()));
+            slot.spawn(TextBundle::from_section(item.amount.clone(), small.clone()));
+            if let Some(purity) = &item.purity {
+                slot.spawn(TextBundle::from_section(purity.clone(), small));
+            }
+        });
+}
+
+fn page_button(parent: &mut ChildBuilder, button: InventoryUIPageButton, enabled: bool) {
+    let label = match button {
+        InventoryUIPageButton::Previous => "<",
+        InventoryUIPageButton::Next => ">",
+    };
+    let background = match enabled {
+        true => Color::rgba(0.1, 0.1, 0.1, 0.5),
+        false => Color::rgba(0.1, 0.1, 0.1, 0.2),
+    };
+
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::horizontal(Val::Px(10.0)),
+                    ..Default::default()
+                },
+                background_color: background.into(),
+                ..Default::default()
+            },
+            button,
+        ))
+        .with_children(|button| {
+            button.spawn(TextBundle::from_section(label, Default::default()));
+        });
+}
+
+fn item_grid(parent: &mut ChildBuilder, source: ItemSource, page: &InventoryPage) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                flex_grow: 1.0,
+                align_items: AlignItems::FlexStart,
+                align_content: AlignContent::FlexStart,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with_children(|grid| {
+            for item in &page.items {
+                item_box(grid, source, item);
+            }
+        });
+}
+
+fn player_panel(parent: &mut ChildBuilder, view: &InventoryPage) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                flex_grow: 2.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with_children(|panel| {
+            item_grid(panel, ItemSource::Player, view);
+
+            panel
+                .spawn(NodeBundle {
                     style: Style {
                         display: Display::Flex,
-                        position_type: PositionType::Relative,
-                        width: Val::Px(50.0),
-                        height: Val::Px(50.0),
-                        margin: UiRect {
-                            left: Val::Px(5.0),
-                            right: Val::Px(5.0),
-                            top: Val::Px(5.0),
-                            bottom: Val::Px(5.0),
-                        },
+                        justify_content: JustifyContent::Center,
+                        column_gap: Val::Px(10.0),
                         ..Default::default()
                     },
-                    background_color: Color::rgba(0.1, 0.1, 0.1, 0.5).into(),
+                    ..Default::default()
+                })
+                .with_children(|footer| {
+                    page_button(footer, InventoryUIPageButton::Previous, view.page > 0);
+                    footer.spawn(TextBundle::from_section(
+                        format!("Page {} / {}", view.page + 1, view.pages),
+                        Default::default(),
+                    ));
+                    page_button(
+                        footer,
+                        InventoryUIPageButton::Next,
+                        view.page + 1 < view.pages,
+                    );
+                });
+        });
+}
+
+/// The open container beside the player's items. Containers only ever hold a
+/// few things, so they are not paged.
+fn container_panel(parent: &mut ChildBuilder, container: Entity, view: &InventoryPage) {
+    let source = ItemSource::Container(container);
+    parent
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Column,
+                    flex_grow: 1.0,
+                    padding: UiRect::all(Val::Px(5.0)),
                     ..Default::default()
                 },
-                InventoryUIItem { id: item.id() },
-            ))
-            .with_children(|subparent| {
-                subparent.spawn(TextBundle::from_section(
-                    item.type_name(),
-                    Default::default(),
-                ));
-                subparent.spawn(TextBundle::from_section(
-                    item.quantity().to_string(),
-                    Default::default(),
-                ));
-            });
+                background_color: Color::rgba(0.2, 0.15, 0.1, 0.5).into(),
+                ..Default::default()
+            },
+            DropTarget::Panel(source),
+            RelativeCursorPosition::default(),
+        ))
+        .with_children(|panel| {
+            panel.spawn(TextBundle::from_section("Container", Default::default()));
+            item_grid(panel, source, view);
+        });
+}
+
+/// Draws the current page of the player's inventory, and the open container
+/// if there is one. Redrawn when the menu is opened and whenever what it
+/// shows changes.
+#[allow(clippy::too_many_arguments)]
+pub fn refresh_inventory_menu(
+    mut commands: Commands,
+    player: Query<&Player>,
+    containers: Query<&Container>,
+    open: Res<OpenContainer>,
+    layout: Res<InventoryUILayout>,
+    mut page: ResMut<InventoryUIPage>,
+    menu: Query<(Entity, Ref<Visibility>), With<InventoryUIMarker>>,
+    mut shown: Local<Option<InventoryMenuView>>,
+) {
+    let (menu, visibility) = menu.single();
+    if *visibility != Visibility::Visible {
+        *shown = None;
+        return;
+    }
+
+    let view = InventoryMenuView {
+        player: InventoryPage::new(
+            &player.single().inventory,
+            &layout.0,
+            page.0,
+            ITEMS_PER_PAGE,
+        ),
+        container: open.0.and_then(|entity| {
+            let container = containers.get(entity).ok()?;
+            let view = InventoryPage::new(&container.0, &Layout::default(), 0, ITEMS_PER_PAGE);
+            Some((entity, view))
+        }),
     };
+    if shown.as_ref() == Some(&view) {
+        return;
+    }
+    // Items taken out can leave the page past the end.
+    if page.0 != view.player.page {
+        page.0 = view.player.page;
+    }
 
     commands
-        .spawn((tab_ui, InventoryUIMarker))
-        .with_children(|parent: &mut ChildBuilder| {
-            for item in &inventory.items {
-                item_box(parent, item);
+        .entity(menu)
+        .despawn_descendants()
+        .with_children(|parent| {
+            player_panel(parent, &view.player);
+            if let Some((container, container_view)) = &view.container {
+                container_panel(parent, *container, container_view);
             }
         });
+
+    *shown = Some(view);
 }
 
+/// Turns the page. Going past the last page is undone when the page is drawn.
 pub fn handle_inventory_input(
-    mut commands: Commands,
-    mut player: Query<&mut Player>,
-    mut interaction: Query<
-        (&Interaction, &InventoryUIItem, &mut Visibility),
-        (Changed<Interaction>, With<Button>),
-    >,
+    mut page: ResMut<InventoryUIPage>,
+    interaction: Query<(&Interaction, &InventoryUIPageButton), ChangedButton>,
 ) {
-    let mut player = player.single_mut();
-    for (interaction, item, mut vis) in interaction.iter_mut() {
-        if *interaction == Interaction::Pressed {
-            player.inventory.remove_by_id(item.id);
-            *vis = Visibility::Hidden;
+    for (interaction, button) in interaction.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
         }
+
+        page.0 = match button {
+            InventoryUIPageButton::Previous => page.0.saturating_sub(1),
+            InventoryUIPageButton::Next => page.0 + 1,
+        };
     }
 }
@@ -0,0 +1,97 @@
+use backend::iams::tooltip::Tooltip;
+use backend::recipes::RecipeBook;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use super::drag::{Dragging, ItemSource};
+use super::tab_menu::InventoryUIItem;
+use crate::player::container::Container;
+use crate::player::Player;
+
+#[derive(Component)]
+pub struct TooltipUIMarker;
+
+pub fn load_recipe_book(mut commands: Commands) {
+    let book = RecipeBook::from_ron(include_str!("../../../assets/recipes.ron"))
+        .unwrap_or_else(|err| panic!("Invalid recipe book: {err:?}"));
+    commands.insert_resource(book);
+}
+
+pub fn tooltip_popup(mut commands: Commands) {
+    let tooltip_ui = NodeBundle {
+        style: Style {
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            position_type: PositionType::Absolute,
+            max_width: Val::Px(320.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..Default::default()
+        },
+        visibility: Visibility::Hidden,
+        z_index: ZIndex::Global(15),
+        background_color: Color::rgba(0.05, 0.05, 0.05, 0.9).into(),
+        ..Default::default()
+    };
+
+    commands.spawn((tooltip_ui, TooltipUIMarker));
+}
+
+/// Shows the details of the item under the cursor beside it. Hidden while
+/// dragging, so it does not cover where the item is going.
+#[allow(clippy::too_many_arguments)]
+pub fn show_item_tooltip(
+    mut commands: Commands,
+    recipes: Res<RecipeBook>,
+    dragging: Res<Dragging>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    slots: Query<(&Interaction, &InventoryUIItem, &InheritedVisibility)>,
+    player: Query<&Player>,
+    containers: Query<&Container>,
+    mut popup: Query<(Entity, &mut Style, &mut Visibility), With<TooltipUIMarker>>,
+    mut shown: Local<Option<Tooltip>>,
+) {
+    let (popup, mut style, mut visibility) = popup.single_mut();
+    let hovered = slots
+        .iter()
+        .find(|(interaction, _, visible)| **interaction == Interaction::Hovered && visible.get())
+        .map(|(_, slot, _)| slot);
+
+    let tooltip = hovered.and_then(|slot| {
+        let inventory = match slot.source {
+            ItemSource::Player => &player.single().inventory,
+            ItemSource::Container(entity) => &containers.get(entity).ok()?.0,
+        };
+        Some(Tooltip::new(inventory.get(slot.key)?, &recipes))
+    });
+    let cursor = window.single().cursor_position();
+    let (Some(tooltip), Some(cursor), None) = (tooltip, cursor, dragging.0) else {
+        if *visibility != Visibility::Hidden {
+            *visibility = Visibility::Hidden;
+        }
+        *shown = None;
+        return;
+    };
+
+    style.left = Val::Px(cursor.x + 16.0);
+    style.top = Val::Px(cursor.y + 16.0);
+    *visibility = Visibility::Visible;
+    if shown.as_ref() == Some(&tooltip) {
+        return;
+    }
+
+    commands
+        .entity(popup)
+        .despawn_descendants()
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(tooltip.name, Default::default()));
+            let small = TextStyle {
+                font_size: 14.0,
+                ..Default::default()
+            };
+            for line in tooltip.lines() {
+                parent.spawn(TextBundle::from_section(line, small.clone()));
+            }
+        });
+
+    *shown = Some(tooltip);
+}
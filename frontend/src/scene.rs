@@ -1,5 +1,6 @@
+use backend::mining::{OreDeposit, OreKind};
+use backend::physics::{Layer, PhysicsBundle, SurfaceMaterial};
 use bevy::prelude::*;
-use bevy_xpbd_3d::components::RigidBody;
 use bevy_xpbd_3d::plugins::collision::Collider;
 
 pub struct ScenePlugin;
@@ -22,7 +23,11 @@ pub fn create_scene(
         ..default()
     };
 
-    let collider = (RigidBody::Static, Collider::cuboid(10.0, 10.0, 1.0));
+    let collider = PhysicsBundle::fixed(
+        Layer::World,
+        Collider::cuboid(10.0, 10.0, 1.0),
+        SurfaceMaterial::Stone,
+    );
 
     let light = PointLightBundle {
         point_light: PointLight {
@@ -33,7 +38,7 @@ pub fn create_scene(
         ..default()
     };
 
-    commands.spawn(( platform, collider ));
+    commands.spawn((platform, collider));
     commands.spawn(light);
     commands.spawn(PbrBundle {
         mesh: meshes.add(Cuboid::new(1.0, 0.01, 1.0)),
@@ -41,4 +46,34 @@ pub fn create_scene(
         transform: Transform::from_xyz(0.0, 2.0, 0.0),
         ..Default::default()
     });
+
+    let deposits = [
+        (
+            OreKind::Iron,
+            Color::rgb(0.45, 0.3, 0.25),
+            Vec3::new(3.0, 1.0, -3.0),
+        ),
+        (
+            OreKind::Copper,
+            Color::rgb(0.7, 0.45, 0.2),
+            Vec3::new(-3.0, 1.0, -3.0),
+        ),
+    ];
+    for (kind, color, position) in deposits {
+        commands.spawn((
+            PbrBundle {
+                mesh: meshes.add(Cuboid::new(1.0, 1.0, 1.0)),
+                material: materials.add(color),
+                transform: Transform::from_translation(position),
+                ..default()
+            },
+            PhysicsBundle::fixed(
+                Layer::World,
+                Collider::cuboid(1.0, 1.0, 1.0),
+                SurfaceMaterial::Stone,
+            ),
+            OreDeposit::new(kind, 0.6, 20.0),
+            Name::new(format!("{} Deposit", kind.name())),
+        ));
+    }
 }
@@ -0,0 +1,224 @@
+//! Pieces menus are put together from, so every panel looks and behaves the
+//! same. Panels are rebuilt from these whenever what they show changes; only
+//! [ProgressBar]s are updated in place.
+
+use bevy::prelude::*;
+
+const PANEL_COLOR: Color = Color::rgba(0.1, 0.1, 0.1, 0.8);
+const SECTION_COLOR: Color = Color::rgba(0.2, 0.2, 0.2, 0.5);
+const BUTTON_COLOR: Color = Color::rgba(0.25, 0.25, 0.25, 0.8);
+const HOVERED_COLOR: Color = Color::rgba(0.35, 0.35, 0.35, 0.8);
+const SELECTED_COLOR: Color = Color::rgba(0.2, 0.4, 0.6, 0.8);
+const BAR_COLOR: Color = Color::rgba(0.05, 0.05, 0.05, 0.8);
+
+pub struct WidgetsPlugin;
+
+impl Plugin for WidgetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (update_progress_bars, highlight_buttons));
+    }
+}
+
+/// How full a bar is, from 0.0 to 1.0.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct ProgressBar(pub f32);
+
+/// A button made by [button], remembering whether it is drawn as selected.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WidgetButton {
+    pub selected: bool,
+}
+
+impl WidgetButton {
+    fn color(&self, interaction: Interaction) -> Color {
+        match (self.selected, interaction) {
+            (true, _) => SELECTED_COLOR,
+            (false, Interaction::None) => BUTTON_COLOR,
+            (false, _) => HOVERED_COLOR,
+        }
+    }
+}
+
+/// A hidden window in the middle of the screen, laid out top to bottom.
+pub fn panel(width: Val, height: Val) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(8.0),
+            position_type: PositionType::Absolute,
+            width,
+            height,
+            justify_self: JustifySelf::Center,
+            align_self: AlignSelf::Center,
+            padding: UiRect::all(Val::Px(10.0)),
+            overflow: Overflow::clip(),
+            ..Default::default()
+        },
+        visibility: Visibility::Hidden,
+        background_color: PANEL_COLOR.into(),
+        ..Default::default()
+    }
+}
+
+pub fn heading(parent: &mut ChildBuilder, text: impl Into<String>) {
+    parent.spawn(TextBundle::from_section(
+        text,
+        TextStyle {
+            font_size: 24.0,
+            ..Default::default()
+        },
+    ));
+}
+
+pub fn label(parent: &mut ChildBuilder, text: impl Into<String>) {
+    parent.spawn(TextBundle::from_section(
+        text,
+        TextStyle {
+            font_size: 16.0,
+            ..Default::default()
+        },
+    ));
+}
+
+/// Things laid out side by side, wrapping onto more lines when they do not
+/// fit.
+pub fn row(parent: &mut ChildBuilder, children: impl FnOnce(&mut ChildBuilder)) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(8.0),
+                row_gap: Val::Px(4.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with_children(children);
+}
+
+/// A titled, shaded group of things laid out top to bottom.
+pub fn section(parent: &mut ChildBuilder, title: &str, children: impl FnOnce(&mut ChildBuilder)) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                padding: UiRect::all(Val::Px(6.0)),
+                ..Default::default()
+            },
+            background_color: SECTION_COLOR.into(),
+            ..Default::default()
+        })
+        .with_children(|section| {
+            label(section, title);
+            children(section);
+        });
+}
+
+/// A button saying `text`. `marker` tells what was pressed in the systems
+/// handling [Interaction]s.
+pub fn button(
+    parent: &mut ChildBuilder,
+    text: impl Into<String>,
+    selected: bool,
+    marker: impl Bundle,
+) {
+    let widget = WidgetButton { selected };
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                    ..Default::default()
+                },
+                background_color: widget.color(Interaction::None).into(),
+                ..Default::default()
+            },
+            widget,
+            marker,
+        ))
+        .with_children(|button| {
+            label(button, text);
+        });
+}
+
+/// A named value with buttons beside it stepping it down and up.
+pub fn stepper(
+    parent: &mut ChildBuilder,
+    name: &str,
+    value: impl Into<String>,
+    down: impl Bundle,
+    up: impl Bundle,
+) {
+    row(parent, |row| {
+        row.spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(180.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with_children(|name_box| label(name_box, name));
+        button(row, "-", false, down);
+        row.spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(70.0),
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with_children(|value_box| label(value_box, value));
+        button(row, "+", false, up);
+    });
+}
+
+/// A bar filled with `color` up to its [ProgressBar], which is put on the
+/// fill along with `marker` to tell bars apart.
+pub fn progress_bar(parent: &mut ChildBuilder, color: Color, marker: impl Bundle) {
+    let bar = NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Px(12.0),
+            ..Default::default()
+        },
+        background_color: BAR_COLOR.into(),
+        ..Default::default()
+    };
+
+    parent.spawn(bar).with_children(|bar| {
+        bar.spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(0.0),
+                    height: Val::Percent(100.0),
+                    ..Default::default()
+                },
+                background_color: color.into(),
+                ..Default::default()
+            },
+            ProgressBar::default(),
+            marker,
+        ));
+    });
+}
+
+pub fn update_progress_bars(mut bars: Query<(&ProgressBar, &mut Style), Changed<ProgressBar>>) {
+    for (bar, mut style) in bars.iter_mut() {
+        style.width = Val::Percent(bar.0.clamp(0.0, 1.0) * 100.0);
+    }
+}
+
+/// Lightens buttons under the cursor.
+pub fn highlight_buttons(
+    mut buttons: Query<(&Interaction, &WidgetButton, &mut BackgroundColor), Changed<Interaction>>,
+) {
+    for (interaction, button, mut background) in buttons.iter_mut() {
+        *background = button.color(*interaction).into();
+    }
+}